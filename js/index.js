@@ -60,4 +60,86 @@ import("../pkg/index.js").then(module => {
     document.getElementById("config-load-default").onclick = _ => {
         loadScene('default');
     };
+
+    // Swaps the intersection handler, keeping the current scene & camera
+    document.getElementById("config-handler").onchange = event => {
+        module.update_handler(JSON.stringify(event.target.value));
+    };
+
+    // Draws a second handler next to the first, `null` stops comparing
+    const compare = { handler: null, mode: "split" };
+    const updateCompare = () => {
+        module.update_compare(JSON.stringify(compare.handler === null ? null : compare));
+    };
+
+    document.getElementById("config-compare").onchange = event => {
+        compare.handler = event.target.value || null;
+
+        updateCompare();
+    };
+
+    document.getElementById("config-compare-mode").onchange = event => {
+        compare.mode = event.target.value;
+
+        updateCompare();
+    };
+
+    document.getElementById("config-capture").onclick = _ => {
+        module.capture();
+    };
+
+    // `update_config` replaces the entire config,
+    // so every control writes into this object
+    const config = {
+        compute: { debug: "shaded" },
+        overlay: { enabled: false, leaves: false, depth_min: 0 },
+        hud: false,
+    };
+
+    const updateConfig = _ => {
+        module.update_config(JSON.stringify(config));
+    };
+
+    document.getElementById("config-debug").onchange = event => {
+        config.compute.debug = event.target.value;
+
+        updateConfig();
+    };
+
+    document.getElementById("config-hud").onchange = event => {
+        config.hud = event.target.checked;
+
+        updateConfig();
+    };
+
+    document.getElementById("config-overlay").onchange = event => {
+        config.overlay.enabled = event.target.checked;
+
+        updateConfig();
+    };
+
+    document.getElementById("config-overlay-leaves").onchange = event => {
+        config.overlay.leaves = event.target.checked;
+
+        updateConfig();
+    };
+
+    document.getElementById("config-overlay-depth-min").onchange = event => {
+        config.overlay.depth_min = Math.max(0, parseInt(event.target.value) || 0);
+
+        updateConfig();
+    };
+
+    document.getElementById("config-overlay-depth-max").onchange = event => {
+        const depth = parseInt(event.target.value);
+
+        // An empty field removes the upper bound
+        if(isNaN(depth)) {
+            delete config.overlay.depth_max;
+        } else {
+            config.overlay.depth_max = Math.max(0, depth);
+        }
+
+        updateConfig();
+    };
 }).catch(console.error);
\ No newline at end of file
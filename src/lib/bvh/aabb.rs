@@ -4,6 +4,22 @@ use once_cell::sync::OnceCell;
 
 use crate::{geom, scene};
 
+// Subtrees are built in parallel on native, wasm stays single-threaded.
+// Each subtree only depends on its own items, so the result is identical
+// whatever the number of threads
+cfg_if::cfg_if! {
+    if #[cfg(target_arch = "wasm32")] {
+        pub(super) fn join<A, B>(a: impl FnOnce() -> A, b: impl FnOnce() -> B) -> (A, B) {
+            (a(), b())
+        }
+    } else {
+        pub(super) use rayon::join;
+    }
+}
+
+// Nodes with fewer items than this aren't worth handing to another thread
+pub(super) const PARALLEL_ITEM_COUNT: usize = 256;
+
 #[repr(C)]
 #[derive(Clone, Copy)]
 #[derive(bytemuck::Pod, bytemuck::Zeroable)]
@@ -74,11 +90,11 @@ impl<'de> serde::Deserialize<'de> for Bounds {
 }
 
 impl Bounds {
-    fn new<P>(prims: P, vertices: &[geom::PrimVertex]) -> Self
+    pub(super) fn new<P>(prims: P, vertices: &[geom::PrimVertex]) -> Self
         where P: Iterator<Item = geom::Prim> {
 
         let mut min = [f32::MAX; 3];
-        let mut max = [f32::MAX * -1.; 3];
+        let mut max = [-f32::MAX; 3];
 
         fn extrema_vertex(
             vertex: [f32; 3], 
@@ -107,6 +123,41 @@ impl Bounds {
         Self { min, _p0: 0, max, _p1: 0 }
     }
 
+    pub(super) const fn from_corners(min: [f32; 3], max: [f32; 3]) -> Self {
+        Self { min, _p0: 0, max, _p1: 0 }
+    }
+
+    // Inverted, so it can be grown with `Bounds::union`
+    pub(super) const fn empty() -> Self {
+        Self::from_corners([f32::MAX; 3], [-f32::MAX; 3])
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        (0..3).any(|i| self.min[i] > self.max[i])
+    }
+
+    pub(super) fn union(&self, other: &Self) -> Self {
+        Self::from_corners(
+            [0, 1, 2].map(|i| self.min[i].min(other.min[i])),
+            [0, 1, 2].map(|i| self.max[i].max(other.max[i])),
+        )
+    }
+
+    pub(super) fn intersection(&self, other: &Self) -> Self {
+        Self::from_corners(
+            [0, 1, 2].map(|i| self.min[i].max(other.min[i])),
+            [0, 1, 2].map(|i| self.max[i].min(other.max[i])),
+        )
+    }
+
+    pub(super) fn surface_area(&self) -> f32 {
+        if self.is_empty() { return 0.; }
+
+        let [x, y, z] = [0, 1, 2].map(|i| self.max[i] - self.min[i]);
+
+        2. * (x * y + y * z + z * x)
+    }
+
     fn contains(&self, point: [f32; 3]) -> bool {
         point[0] >= self.min[0] &&
         point[0] <= self.max[0] &&
@@ -218,14 +269,16 @@ impl Aabb {
             }
         }
 
-        if fst.items.is_empty() {
-            self.bounds = snd.bounds;
+        if fst.items.is_empty() || snd.items.is_empty() {
+            // Narrow the region being split, but the node itself
+            // has to keep bounding the full extent of its prims
+            let bounds = self.bounds;
 
-            self.split(eps, prims, vertices, target_item_count);
-        } else if snd.items.is_empty() {
-            self.bounds = fst.bounds;
+            self.bounds = if fst.items.is_empty() { snd.bounds } else { fst.bounds };
 
             self.split(eps, prims, vertices, target_item_count);
+
+            self.bounds = bounds;
         } else {
             self.items.clear();
 
@@ -239,8 +292,15 @@ impl Aabb {
                 vertices
             );
 
-            fst.split(eps, prims, vertices, target_item_count);
-            snd.split(eps, prims, vertices, target_item_count);
+            if fst.items.len() + snd.items.len() >= PARALLEL_ITEM_COUNT {
+                join(
+                    || fst.split(eps, prims, vertices, target_item_count),
+                    || snd.split(eps, prims, vertices, target_item_count),
+                );
+            } else {
+                fst.split(eps, prims, vertices, target_item_count);
+                snd.split(eps, prims, vertices, target_item_count);
+            }
 
             self.fst.set(Box::new(fst)).unwrap();
             self.snd.set(Box::new(snd)).unwrap();
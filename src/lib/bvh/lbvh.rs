@@ -0,0 +1,270 @@
+use once_cell::sync::OnceCell;
+
+use crate::{geom, scene};
+
+use super::aabb::{Aabb, Bounds};
+
+// Each axis is quantised to 10 bits, giving 30-bit codes
+const MORTON_BITS: u32 = 10;
+
+// Spreads the lower 10 bits of `v` so there are two zeroes between each
+// The multiplications intentionally discard their upper bits
+fn expand(v: u32) -> u32 {
+    let v = v.wrapping_mul(0x00010001) & 0xFF0000FF;
+    let v = v.wrapping_mul(0x00000101) & 0x0F00F00F;
+    let v = v.wrapping_mul(0x00000011) & 0xC30C30C3;
+
+    v.wrapping_mul(0x00000005) & 0x49249249
+}
+
+// Interleaves the quantised coordinates of a point inside `bounds`
+fn morton(point: [f32; 3], bounds: &Bounds) -> u32 {
+    let scale = ((1 << MORTON_BITS) - 1) as f32;
+
+    let [x, y, z] = [0, 1, 2].map(|i| {
+        let extent = bounds.max[i] - bounds.min[i];
+
+        let t = if extent > 0. { (point[i] - bounds.min[i]) / extent } else { 0. };
+
+        expand((t.clamp(0., 1.) * scale) as u32)
+    });
+
+    (x << 2) | (y << 1) | z
+}
+
+// Least-significant-digit radix sort on (code, prim) pairs
+// Counting sorts are stable, so equal codes stay in prim order
+fn radix_sort(mut keys: Vec<(u32, usize)>) -> Vec<(u32, usize)> {
+    let mut scratch = keys.clone();
+
+    for pass in 0..4 {
+        let shift = pass * 8;
+
+        let mut offsets = [0usize; 256];
+        for &(code, _) in keys.iter() {
+            offsets[((code >> shift) & 0xFF) as usize] += 1;
+        }
+
+        let mut total = 0;
+        for offset in offsets.iter_mut() {
+            let count = *offset; *offset = total; total += count;
+        }
+
+        for &key in keys.iter() {
+            let digit = ((key.0 >> shift) & 0xFF) as usize;
+
+            scratch[offsets[digit]] = key;
+            offsets[digit] += 1;
+        }
+
+        std::mem::swap(&mut keys, &mut scratch);
+    }
+
+    keys
+}
+
+// A child in the radix tree, internal nodes share indices with leaves
+#[derive(Clone, Copy)]
+enum Node {
+    Internal(usize),
+    Leaf(usize),
+}
+
+// Internal node `i` covers the sorted prims `first..=last`
+#[derive(Clone, Copy)]
+struct Internal {
+    first: usize,
+    last: usize,
+    left: Node,
+    right: Node,
+}
+
+// Builds the binary radix tree over sorted codes (Karras, 2012)
+fn karras(codes: &[u32]) -> Vec<Internal> {
+    let n = codes.len() as isize;
+
+    // Length of the common prefix of two keys, -1 when `j` is out of range.
+    // Duplicate codes are disambiguated by their position
+    let delta = |i: isize, j: isize| -> i32 {
+        if j < 0 || j >= n { return -1; }
+
+        let (a, b) = (codes[i as usize], codes[j as usize]);
+
+        if a == b {
+            32 + (i as u32 ^ j as u32).leading_zeros() as i32
+        } else {
+            (a ^ b).leading_zeros() as i32
+        }
+    };
+
+    (0..(n - 1)).map(|i| {
+        // Direction of the range covered by this node
+        let d: isize = if delta(i, i + 1) > delta(i, i - 1) { 1 } else { -1 };
+
+        // Upper bound on the length of the range
+        let delta_min = delta(i, i - d);
+
+        let mut l_max = 2;
+        while delta(i, i + l_max * d) > delta_min {
+            l_max *= 2;
+        }
+
+        // Binary search for the other end
+        let mut l = 0;
+        let mut t = l_max / 2;
+        while t >= 1 {
+            if delta(i, i + (l + t) * d) > delta_min {
+                l += t;
+            }
+
+            t /= 2;
+        }
+
+        let j = i + l * d;
+
+        // Binary search for the split position
+        let delta_node = delta(i, j);
+
+        let mut s = 0;
+        let mut t = l;
+        loop {
+            t = (t + 1) / 2;
+
+            if delta(i, i + (s + t) * d) > delta_node {
+                s += t;
+            }
+
+            if t == 1 { break; }
+        }
+
+        let gamma = i + s * d + d.min(0);
+
+        let (first, last) = (i.min(j), i.max(j));
+
+        let left = if first == gamma {
+            Node::Leaf(gamma as usize)
+        } else {
+            Node::Internal(gamma as usize)
+        };
+
+        let right = if last == gamma + 1 {
+            Node::Leaf(gamma as usize + 1)
+        } else {
+            Node::Internal(gamma as usize + 1)
+        };
+
+        Internal { first: first as usize, last: last as usize, left, right }
+    }).collect()
+}
+
+impl Aabb {
+    // Builds a linear BVH from the Morton order of prim centroids
+    // Subtrees covering at most `target_item_count` prims become leaves
+    pub fn from_scene_lbvh(
+        scene: &scene::Scene,
+        target_item_count: usize,
+    ) -> Self {
+        let scene::Scene::Active {
+            prims,
+            vertices, ..
+        } = scene else {
+            return Self::from_scene_unloaded();
+        };
+
+        let bounds = prims
+            .iter()
+            .map(|geom::Prim { indices, .. }| {
+                let [a, b, c] = indices.map(|idx| vertices[idx as usize].pos);
+
+                Bounds::from_corners(
+                    [0, 1, 2].map(|i| a[i].min(b[i]).min(c[i])),
+                    [0, 1, 2].map(|i| a[i].max(b[i]).max(c[i])),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let centroid = |bounds: &Bounds| {
+            [0, 1, 2].map(|i| (bounds.min[i] + bounds.max[i]) * 0.5)
+        };
+
+        // Codes are relative to the bounds of the centroids
+        let extent = bounds
+            .iter()
+            .map(|bounds| {
+                let c = centroid(bounds);
+
+                Bounds::from_corners(c, c)
+            })
+            .fold(Bounds::empty(), |acc, bounds| acc.union(&bounds));
+
+        let sorted = radix_sort({
+            bounds
+                .iter()
+                .enumerate()
+                .map(|(prim, bounds)| (morton(centroid(bounds), &extent), prim))
+                .collect()
+        });
+
+        let codes = sorted.iter().map(|&(code, _)| code).collect::<Vec<_>>();
+        let order = sorted.iter().map(|&(_, prim)| prim).collect::<Vec<_>>();
+
+        let internal = karras(&codes);
+
+        let target_item_count = target_item_count.max(1);
+
+        let leaf = |first: usize, last: usize| {
+            let items = order[first..=last].to_vec();
+
+            Aabb {
+                fst: OnceCell::new(),
+                snd: OnceCell::new(),
+                bounds: items
+                    .iter()
+                    .fold(Bounds::empty(), |acc, &prim| acc.union(&bounds[prim])),
+                items,
+            }
+        };
+
+        fn convert(
+            node: Node,
+            internal: &[Internal],
+            target_item_count: usize,
+            leaf: &dyn Fn(usize, usize) -> Aabb,
+        ) -> Aabb {
+            let Internal { first, last, left, right } = match node {
+                Node::Leaf(idx) => return leaf(idx, idx),
+                Node::Internal(idx) => internal[idx],
+            };
+
+            if last - first < target_item_count {
+                return leaf(first, last);
+            }
+
+            let fst = convert(left, internal, target_item_count, leaf);
+            let snd = convert(right, internal, target_item_count, leaf);
+
+            let node = Aabb {
+                fst: OnceCell::new(),
+                snd: OnceCell::new(),
+                bounds: fst.bounds.union(&snd.bounds),
+                items: Vec::new(),
+            };
+
+            node.fst.set(Box::new(fst)).unwrap();
+            node.snd.set(Box::new(snd)).unwrap();
+            node
+        }
+
+        match prims.len() {
+            // Matches `Aabb::from_scene` on a scene without prims
+            0 => Aabb {
+                fst: OnceCell::new(),
+                snd: OnceCell::new(),
+                bounds: Bounds::empty(),
+                items: Vec::new(),
+            },
+            1 => leaf(0, 0),
+            _ => convert(Node::Internal(0), &internal, target_item_count, &leaf),
+        }
+    }
+}
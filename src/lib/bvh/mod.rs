@@ -2,6 +2,74 @@ mod aabb;
 
 pub use aabb::{Aabb, Bounds};
 
+// Adds `Aabb::from_scene_sbvh`
+mod sbvh;
+
+// Adds `Aabb::from_scene_lbvh`
+mod lbvh;
+
+mod wide;
+
+pub use wide::{WideBvhData, WideChild};
+
+mod quant;
+
+pub use quant::{QuantBvhData, QuantPrecision};
+
+// The strategies available for constructing the Aabb tree
+#[derive(Clone, Copy)]
+#[derive(PartialEq, Eq)]
+#[derive(Default)]
+#[derive(Debug)]
+pub enum BvhBuilder {
+    // Midpoint splits over prim centroids
+    #[default]
+    Object,
+    // Spatial splits, prims may be referenced by several leaves
+    Sbvh,
+    // Morton-ordered radix tree, fast to build but lower quality
+    Lbvh,
+}
+
+impl BvhBuilder {
+    pub fn build(
+        self,
+        eps: f32,
+        scene: &crate::scene::Scene,
+        target_item_count: usize,
+    ) -> Aabb {
+        match self {
+            BvhBuilder::Object => Aabb::from_scene(eps, scene, target_item_count),
+            BvhBuilder::Sbvh => Aabb::from_scene_sbvh(eps, scene, target_item_count),
+            BvhBuilder::Lbvh => Aabb::from_scene_lbvh(scene, target_item_count),
+        }
+    }
+}
+
+impl std::str::FromStr for BvhBuilder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "object" => Ok(BvhBuilder::Object),
+            "sbvh" => Ok(BvhBuilder::Sbvh),
+            "lbvh" => Ok(BvhBuilder::Lbvh),
+            _ => Err(format!("Unknown BVH builder [{s}], expected object, sbvh or lbvh")),
+        }
+    }
+}
+
+// Runs a construction step, returning its result and duration (MS)
+pub fn timed<T>(build: impl FnOnce() -> T) -> (T, f32) {
+    let start = chrono::Local::now();
+
+    let result = build();
+
+    let elapsed = chrono::Local::now() - start;
+
+    (result, elapsed.num_microseconds().unwrap_or(i64::MAX) as f32 * 0.001)
+}
+
 // The Aabb tree gets rendered down into an array of AabbUniform structs
 // It's placed at the module root to avoid importing items from siblings
 #[repr(C)]
@@ -62,4 +130,66 @@ impl BvhData {
 
         data
     }
-}
\ No newline at end of file
+
+    // The number of levels in the tree
+    // Traversal never holds more than this many nodes on its stack
+    pub fn depth(&self) -> usize {
+        fn depth_helper(data: &BvhData, idx: usize) -> usize {
+            let AabbUniform { fst, snd, .. } = data.uniforms[idx];
+
+            if fst == 0 && snd == 0 { return 1; }
+
+            1 + depth_helper(data, fst as usize)
+                .max(depth_helper(data, snd as usize))
+        }
+
+        if self.uniforms.is_empty() { 0 } else { depth_helper(self, 0) }
+    }
+
+    // Recomputes the bounds of every node from moved vertices, keeping the topology.
+    // `prims` must be in the order the tree was built from
+    pub fn refit(&mut self, prims: &[crate::geom::Prim], vertices: &[crate::geom::PrimVertex]) {
+        fn refit_helper(
+            data: &mut BvhData,
+            prims: &[crate::geom::Prim],
+            vertices: &[crate::geom::PrimVertex],
+            idx: usize,
+        ) -> aabb::Bounds {
+            let AabbUniform { fst, snd, item_idx, item_count, .. } = data.uniforms[idx];
+
+            let bounds = if fst == 0 && snd == 0 {
+                let items = item_idx as usize..(item_idx + item_count) as usize;
+
+                aabb::Bounds::new(data.indices[items].iter().map(|&i| prims[i as usize]), vertices)
+            } else {
+                refit_helper(data, prims, vertices, fst as usize)
+                    .union(&refit_helper(data, prims, vertices, snd as usize))
+            };
+
+            data.uniforms[idx].bounds = bounds;
+
+            bounds
+        }
+
+        if !self.uniforms.is_empty() {
+            refit_helper(self, prims, vertices, 0);
+        }
+    }
+
+    // Expected traversal cost under the surface area heuristic,
+    // relative to the root. Refitting trees under motion raises it
+    pub fn cost(&self) -> f32 {
+        let Some(root) = self.uniforms.first() else { return 0.; };
+
+        let area = root.bounds.surface_area().max(f32::MIN_POSITIVE);
+
+        self.uniforms
+            .iter()
+            .map(|uniform| match uniform {
+                AabbUniform { fst: 0, snd: 0, item_count, bounds, .. } => //
+                    bounds.surface_area() * *item_count as f32,
+                AabbUniform { bounds, .. } => bounds.surface_area(),
+            })
+            .sum::<f32>() / area
+    }
+}
@@ -0,0 +1,194 @@
+use super::wide;
+
+// The number of bits used for each quantised child coordinate
+#[derive(Clone, Copy)]
+#[derive(PartialEq, Eq)]
+#[derive(Default)]
+#[derive(Debug)]
+pub enum QuantPrecision {
+    Bits8,
+    #[default]
+    Bits16,
+}
+
+impl QuantPrecision {
+    pub const fn bits(self) -> u32 {
+        match self {
+            QuantPrecision::Bits8 => 8,
+            QuantPrecision::Bits16 => 16,
+        }
+    }
+
+    // The largest quantised offset
+    pub const fn levels(self) -> u32 {
+        (1 << self.bits()) - 1
+    }
+
+    pub const fn per_word(self) -> usize {
+        32 / self.bits() as usize
+    }
+
+    // Header (4), child references (4) and both quantised boxes
+    pub const fn words(self) -> usize {
+        8 + (QuantBvhData::COORDS).div_ceil(self.per_word())
+    }
+}
+
+// Binary nodes whose child boxes are stored as offsets from the node's own box.
+// Each node has a header holding its origin and a power-of-two scale per axis,
+// so decoding (origin + offset * scale) only rounds once
+#[derive(Clone)]
+#[derive(Debug)]
+pub struct QuantBvhData {
+    pub precision: QuantPrecision,
+    pub words: Vec<u32>,
+    pub indices: Vec<u32>,
+}
+
+// Decodes a single quantised coordinate
+fn decode(origin: f32, scale: f32, q: u32) -> f32 {
+    origin + q as f32 * scale
+}
+
+// Biased exponent of the smallest power of two that covers `min..max`
+fn exponent(min: f32, max: f32, levels: u32) -> u32 {
+    let mut biased = 1;
+
+    let extent = max - min;
+    if extent > 0. {
+        let estimate = (extent / levels as f32).log2().floor() as i32 + 127;
+
+        biased = estimate.clamp(1, 254) as u32;
+    }
+
+    while biased < 254 && decode(min, f32::from_bits(biased << 23), levels) < max {
+        biased += 1;
+    }
+
+    biased
+}
+
+impl QuantBvhData {
+    // Two children, each with a min and max corner
+    const COORDS: usize = 12;
+
+    // Quantises a collapsed tree with 2 children per node
+    pub fn new(data: &wide::WideBvhData, precision: QuantPrecision) -> Self {
+        assert_eq!(data.width, 2, "Quantised nodes are built from a binary tree");
+
+        let levels = precision.levels();
+
+        let mut words = vec![0; data.nodes() * precision.words()];
+
+        for (node, children) in data.children.chunks(2).enumerate() {
+            let words = &mut words[(node * precision.words())..((node + 1) * precision.words())];
+
+            let occupied = children
+                .iter()
+                .filter(|child| !child.is_empty());
+
+            // The node's own box is the union of its children
+            let mut min = [f32::MAX; 3];
+            let mut max = [f32::MIN; 3];
+            for child in occupied.clone() {
+                for i in 0..3 {
+                    min[i] = min[i].min(child.min[i]);
+                    max[i] = max[i].max(child.max[i]);
+                }
+            }
+
+            if occupied.count() == 0 {
+                min = [0.; 3];
+                max = [0.; 3];
+            }
+
+            let exponents = [0, 1, 2].map(|i| exponent(min[i], max[i], levels));
+
+            words[0..3].copy_from_slice(&min.map(f32::to_bits));
+            words[3] = exponents[0] | (exponents[1] << 8) | (exponents[2] << 16);
+
+            for (slot, child) in children.iter().enumerate() {
+                words[4 + slot * 2] = child.idx;
+                words[5 + slot * 2] = child.count;
+
+                let mut coords = [levels, levels, levels, 0, 0, 0];
+
+                // Round outward, so decoded boxes always contain the child
+                if !child.is_empty() {
+                    for i in 0..3 {
+                        let scale = f32::from_bits(exponents[i] << 23);
+
+                        let mut q_min = ((child.min[i] - min[i]) / scale)
+                            .floor()
+                            .clamp(0., levels as f32) as u32;
+
+                        while q_min > 0 && decode(min[i], scale, q_min) > child.min[i] {
+                            q_min -= 1;
+                        }
+
+                        let mut q_max = ((child.max[i] - min[i]) / scale)
+                            .ceil()
+                            .clamp(0., levels as f32) as u32;
+
+                        while q_max < levels && decode(min[i], scale, q_max) < child.max[i] {
+                            q_max += 1;
+                        }
+
+                        coords[i] = q_min;
+                        coords[i + 3] = q_max;
+                    }
+                }
+
+                for (i, q) in coords.into_iter().enumerate() {
+                    let k = slot * 6 + i;
+
+                    let shift = (k % precision.per_word()) as u32 * precision.bits();
+
+                    words[8 + k / precision.per_word()] |= q << shift;
+                }
+            }
+        }
+
+        Self {
+            precision,
+            words,
+            indices: data.indices.clone(),
+        }
+    }
+
+    pub fn nodes(&self) -> usize {
+        self.words.len() / self.precision.words()
+    }
+
+    // The decoded box of a child, as seen by the shader
+    pub fn bounds(&self, node: usize, slot: usize) -> ([f32; 3], [f32; 3]) {
+        let precision = self.precision;
+
+        let words = &self.words[(node * precision.words())..((node + 1) * precision.words())];
+
+        let coord = |k: usize| {
+            let word = words[8 + k / precision.per_word()];
+
+            (word >> ((k % precision.per_word()) as u32 * precision.bits())) & precision.levels()
+        };
+
+        let mut min = [0.; 3];
+        let mut max = [0.; 3];
+        for i in 0..3 {
+            let origin = f32::from_bits(words[i]);
+            let scale = f32::from_bits(((words[3] >> (i * 8)) & 0xFF) << 23);
+
+            min[i] = decode(origin, scale, coord(slot * 6 + i));
+            max[i] = decode(origin, scale, coord(slot * 6 + i + 3));
+        }
+
+        (min, max)
+    }
+
+    // The child reference (index & count) in the given slot
+    pub fn child(&self, node: usize, slot: usize) -> (u32, u32) {
+        let base = node * self.precision.words();
+
+        (self.words[base + 4 + slot * 2], self.words[base + 5 + slot * 2])
+    }
+}
@@ -0,0 +1,378 @@
+use once_cell::sync::OnceCell;
+
+use crate::{geom, scene};
+
+use super::aabb::{join, Aabb, Bounds, PARALLEL_ITEM_COUNT};
+
+// Candidate planes per axis are placed between bins
+const BINS: usize = 16;
+
+// Spatial splits are only tried when the children of the best object split
+// overlap by more than this fraction of the root's surface area
+const ALPHA: f32 = 1e-5;
+
+// Duplicated references can keep a node from shrinking,
+// so spatial splits are disabled past this depth
+const MAX_SPATIAL_DEPTH: usize = 32;
+
+// Spatial splits may add at most this many references per prim.
+// Heavily overlapping geometry (i.e. coplanar soups) would otherwise
+// duplicate references until the depth limit is reached.
+// NOTE: The budget is divided between subtrees, so they can be built in parallel
+const SPLIT_BUDGET: f32 = 1.;
+
+// A (possibly clipped) reference to a single prim
+#[derive(Clone, Copy)]
+struct Reference {
+    prim: usize,
+    bounds: Bounds,
+}
+
+impl Reference {
+    fn centroid(&self, axis: usize) -> f32 {
+        (self.bounds.min[axis] + self.bounds.max[axis]) * 0.5
+    }
+}
+
+// The lowest cost split found along any axis
+#[derive(Clone, Copy)]
+enum Split {
+    Object { axis: usize, min: f32, extent: f32, bin: usize, },
+    Spatial { axis: usize, pos: f32, },
+}
+
+struct Builder<'a> {
+    eps: f32,
+    prims: &'a [geom::Prim],
+    vertices: &'a [geom::PrimVertex],
+    target_item_count: usize,
+    root_area: f32,
+}
+
+// Finds the bounds of the part of a triangle between `lo` and `hi` along `axis`,
+// limited to the region the reference already covered.
+// Interpolated points are padded slightly so that rounding never loses hits
+fn clip(tri: [[f32; 3]; 3], axis: usize, lo: f32, hi: f32, within: &Bounds) -> Bounds {
+    let mut bounds = Bounds::empty();
+
+    let mut grow = |point: [f32; 3], pad: bool| {
+        let pad = |v: f32| if pad { v.abs().max(1.) * 4. * f32::EPSILON } else { 0. };
+
+        let min = point.map(|v| v - pad(v));
+        let max = point.map(|v| v + pad(v));
+
+        bounds = bounds.union(&Bounds::from_corners(min, max));
+    };
+
+    for i in 0..3 {
+        let a = tri[i];
+        let b = tri[(i + 1) % 3];
+
+        if a[axis] >= lo && a[axis] <= hi {
+            grow(a, false);
+        }
+
+        for plane in [lo, hi] {
+            let (da, db) = (a[axis] - plane, b[axis] - plane);
+
+            // The edge crosses the plane
+            if (da < 0. && db > 0.) || (da > 0. && db < 0.) {
+                let t = da / (da - db);
+
+                let mut point = [0, 1, 2].map(|j| a[j] + (b[j] - a[j]) * t);
+                point[axis] = plane;
+
+                grow(point, true);
+            }
+        }
+    }
+
+    // Padding must not leave the slab along the split axis
+    if !bounds.is_empty() {
+        bounds.min[axis] = bounds.min[axis].max(lo);
+        bounds.max[axis] = bounds.max[axis].min(hi);
+    }
+
+    bounds.intersection(within)
+}
+
+impl<'a> Builder<'a> {
+    fn tri(&self, prim: usize) -> [[f32; 3]; 3] {
+        self.prims[prim].indices.map(|idx| self.vertices[idx as usize].pos)
+    }
+
+    fn cost(left: &Bounds, left_count: usize, right: &Bounds, right_count: usize) -> f32 {
+        left.surface_area() * left_count as f32 + right.surface_area() * right_count as f32
+    }
+
+    // Binned SAH over reference centroids
+    // Returns the split, its cost and the overlap of the resulting children
+    fn object_split(&self, refs: &[Reference]) -> Option<(Split, f32, f32)> {
+        let mut best: Option<(Split, f32, f32)> = None;
+
+        for axis in 0..3 {
+            let (min, max) = refs
+                .iter()
+                .map(|r| r.centroid(axis))
+                .fold((f32::MAX, f32::MIN), |(lo, hi), c| (lo.min(c), hi.max(c)));
+
+            let extent = max - min;
+            if extent <= 0. { continue; }
+
+            let bin = |r: &Reference| {
+                (((r.centroid(axis) - min) / extent * BINS as f32) as usize).min(BINS - 1)
+            };
+
+            let mut bins = [(Bounds::empty(), 0usize); BINS];
+            for r in refs {
+                let (bounds, count) = &mut bins[bin(r)];
+
+                *bounds = bounds.union(&r.bounds);
+                *count += 1;
+            }
+
+            for split in 1..BINS {
+                let (left, left_count) = bins[..split]
+                    .iter()
+                    .fold((Bounds::empty(), 0), |(b, n), (bounds, count)| (b.union(bounds), n + count));
+
+                let (right, right_count) = bins[split..]
+                    .iter()
+                    .fold((Bounds::empty(), 0), |(b, n), (bounds, count)| (b.union(bounds), n + count));
+
+                if left_count == 0 || right_count == 0 { continue; }
+
+                let cost = Self::cost(&left, left_count, &right, right_count);
+
+                if best.is_none_or(|(_, best, _)| cost < best) {
+                    let overlap = left.intersection(&right).surface_area();
+
+                    best = Some((Split::Object { axis, min, extent, bin: split }, cost, overlap));
+                }
+            }
+        }
+
+        best
+    }
+
+    // Binned SAH over clipped references, each prim counts
+    // towards every side of a plane that it overlaps
+    fn spatial_split(&self, refs: &[Reference], bounds: &Bounds, budget: usize) -> Option<(Split, f32)> {
+        let mut best: Option<(Split, f32)> = None;
+
+        for axis in 0..3 {
+            let extent = bounds.max[axis] - bounds.min[axis];
+            if extent < self.eps * 0.5 { continue; }
+
+            let width = extent / BINS as f32;
+
+            let bin = |v: f32| (((v - bounds.min[axis]) / width) as usize).min(BINS - 1);
+
+            // Clipped bounds, entering and exiting references
+            let mut bins = [(Bounds::empty(), 0usize, 0usize); BINS];
+            for r in refs {
+                let (first, last) = (bin(r.bounds.min[axis]), bin(r.bounds.max[axis]));
+
+                for (idx, (bin_bounds, ..)) in bins.iter_mut().enumerate().take(last + 1).skip(first) {
+                    let lo = bounds.min[axis] + width * idx as f32;
+                    let hi = if idx == BINS - 1 { bounds.max[axis] } else { lo + width };
+
+                    let clipped = clip(self.tri(r.prim), axis, lo, hi, &r.bounds);
+
+                    *bin_bounds = bin_bounds.union(&clipped);
+                }
+
+                bins[first].1 += 1;
+                bins[last].2 += 1;
+            }
+
+            for split in 1..BINS {
+                let (left, left_count) = bins[..split]
+                    .iter()
+                    .fold((Bounds::empty(), 0), |(b, n), (bounds, entries, _)| (b.union(bounds), n + entries));
+
+                let (right, right_count) = bins[split..]
+                    .iter()
+                    .fold((Bounds::empty(), 0), |(b, n), (bounds, _, exits)| (b.union(bounds), n + exits));
+
+                // Splits that don't shrink either side make no progress
+                if left_count == 0 || right_count == 0 { continue; }
+                if left_count >= refs.len() && right_count >= refs.len() { continue; }
+
+                if left_count + right_count - refs.len() > budget { continue; }
+
+                let cost = Self::cost(&left, left_count, &right, right_count);
+
+                if best.is_none_or(|(_, best)| cost < best) {
+                    let pos = bounds.min[axis] + width * split as f32;
+
+                    best = Some((Split::Spatial { axis, pos }, cost));
+                }
+            }
+        }
+
+        best
+    }
+
+    fn partition(&self, refs: Vec<Reference>, split: Split) -> (Vec<Reference>, Vec<Reference>) {
+        let mut left = Vec::new();
+        let mut right = Vec::new();
+
+        match split {
+            Split::Object { axis, min, extent, bin } => {
+                for r in refs {
+                    let idx = (((r.centroid(axis) - min) / extent * BINS as f32) as usize).min(BINS - 1);
+
+                    if idx < bin { left.push(r); } else { right.push(r); }
+                }
+            },
+            Split::Spatial { axis, pos } => {
+                for r in refs {
+                    if r.bounds.max[axis] <= pos {
+                        left.push(r);
+                    } else if r.bounds.min[axis] >= pos {
+                        right.push(r);
+                    } else {
+                        // Straddling references are clipped into both children,
+                        // unless the triangle misses one side entirely
+                        let tri = self.tri(r.prim);
+
+                        for (side, lo, hi) in [(&mut left, f32::MIN, pos), (&mut right, pos, f32::MAX)] {
+                            let bounds = clip(tri, axis, lo, hi, &r.bounds);
+
+                            if !bounds.is_empty() {
+                                side.push(Reference { prim: r.prim, bounds });
+                            }
+                        }
+                    }
+                }
+            },
+        }
+
+        (left, right)
+    }
+
+    fn leaf(refs: &[Reference], bounds: Bounds) -> Aabb {
+        // A prim is never referenced twice by the same leaf
+        let mut items = refs.iter().map(|r| r.prim).collect::<Vec<_>>();
+            items.sort_unstable();
+            items.dedup();
+
+        Aabb {
+            fst: OnceCell::new(),
+            snd: OnceCell::new(),
+            bounds,
+            items,
+        }
+    }
+
+    // `budget` holds the references that spatial splits may still add to this subtree
+    fn build(&self, refs: Vec<Reference>, depth: usize, budget: usize) -> Aabb {
+        let bounds = refs
+            .iter()
+            .fold(Bounds::empty(), |bounds, r| bounds.union(&r.bounds));
+
+        if refs.len() <= self.target_item_count {
+            return Self::leaf(&refs, bounds);
+        }
+
+        let object = self.object_split(&refs);
+
+        // Only look for spatial splits when object splits overlap significantly
+        let spatial = match object {
+            Some((_, _, overlap)) if overlap / self.root_area <= ALPHA => None,
+            _ if depth >= MAX_SPATIAL_DEPTH => None,
+            _ => self.spatial_split(&refs, &bounds, budget),
+        };
+
+        let split = match (object, spatial) {
+            (Some((_, object_cost, _)), Some((spatial, spatial_cost))) //
+                if spatial_cost < object_cost => spatial,
+            (Some((object, ..)), _) => object,
+            (None, Some((spatial, _))) => spatial,
+            // Every reference shares a centroid and no plane separates them
+            (None, None) => return Self::leaf(&refs, bounds),
+        };
+
+        let (left, right) = self.partition(refs.clone(), split);
+
+        if left.is_empty() || right.is_empty() {
+            return Self::leaf(&refs, bounds);
+        }
+
+        // Charge the budget for the references that were actually added,
+        // then share what remains between the children by their size
+        let count = left.len() + right.len();
+
+        let budget = budget.saturating_sub(count.saturating_sub(refs.len()));
+
+        let budget_left = budget * left.len() / count;
+        let budget_right = budget - budget_left;
+
+        let (fst, snd) = if count >= PARALLEL_ITEM_COUNT {
+            join(
+                || self.build(left, depth + 1, budget_left),
+                || self.build(right, depth + 1, budget_right),
+            )
+        } else {
+            (
+                self.build(left, depth + 1, budget_left),
+                self.build(right, depth + 1, budget_right),
+            )
+        };
+
+        Aabb {
+            fst: OnceCell::with_value(Box::new(fst)),
+            snd: OnceCell::with_value(Box::new(snd)),
+            bounds,
+            items: Vec::new(),
+        }
+    }
+}
+
+impl Aabb {
+    // Builds a spatial-split BVH (SBVH)
+    // Prims may be referenced by more than one leaf,
+    // so `BvhData.indices` can be longer than the prim list
+    pub fn from_scene_sbvh(
+        eps: f32,
+        scene: &scene::Scene,
+        target_item_count: usize,
+    ) -> Self {
+        let scene::Scene::Active {
+            prims,
+            vertices, ..
+        } = scene else {
+            return Self::from_scene_unloaded();
+        };
+
+        let refs = prims
+            .iter()
+            .enumerate()
+            .map(|(prim, geom::Prim { indices, .. })| {
+                let [a, b, c] = indices.map(|idx| vertices[idx as usize].pos);
+
+                let min = [0, 1, 2].map(|i| a[i].min(b[i]).min(c[i]));
+                let max = [0, 1, 2].map(|i| a[i].max(b[i]).max(c[i]));
+
+                Reference { prim, bounds: Bounds::from_corners(min, max) }
+            })
+            .collect::<Vec<_>>();
+
+        let root_area = refs
+            .iter()
+            .fold(Bounds::empty(), |bounds, r| bounds.union(&r.bounds))
+            .surface_area()
+            .max(f32::MIN_POSITIVE);
+
+        let builder = Builder {
+            eps,
+            prims,
+            vertices,
+            target_item_count: target_item_count.max(1),
+            root_area,
+        };
+
+        builder.build(refs, 0, (prims.len() as f32 * SPLIT_BUDGET) as usize)
+    }
+}
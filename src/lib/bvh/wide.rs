@@ -0,0 +1,219 @@
+use std::collections;
+
+use super::aabb;
+
+// Costs of visiting a node and testing a prim under the surface area heuristic,
+// matching `BvhData::cost`
+const COST_NODE: f32 = 1.;
+const COST_TRI: f32 = 1.;
+
+// The SAH cost of each collapsed subtree, keyed by its binary node
+type Costs = collections::HashMap<*const aabb::Aabb, f32>;
+
+// A single slot of a wide node
+// Internal children have a count of 0 and point at another node,
+// leaves reference a range of `WideBvhData::indices`
+#[repr(C)]
+#[derive(Clone, Copy)]
+#[derive(bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Debug)]
+pub struct WideChild {
+    pub min: [f32; 3],
+    pub idx: u32,
+    pub max: [f32; 3],
+    pub count: u32,
+}
+
+impl WideChild {
+    // Inverted bounds never collide with a ray
+    // An index & count of 0 marks the slot as empty (the root is never a child)
+    const EMPTY: Self = Self {
+        min: [f32::MAX; 3],
+        idx: 0,
+        max: [-f32::MAX; 3],
+        count: 0,
+    };
+
+    pub fn is_empty(&self) -> bool {
+        self.idx == 0 && self.count == 0
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        self.count > 0
+    }
+}
+
+// Each node occupies `width` consecutive children, the root is node 0
+#[derive(Clone)]
+#[derive(Debug)]
+pub struct WideBvhData {
+    pub width: usize,
+    pub children: Vec<WideChild>,
+    pub indices: Vec<u32>,
+}
+
+fn split(node: &aabb::Aabb) -> Option<[&aabb::Aabb; 2]> {
+    match (node.fst.get(), node.snd.get()) {
+        (Some(fst), Some(snd)) => Some([fst, snd]),
+        _ => None,
+    }
+}
+
+impl WideBvhData {
+    // Collapses a binary tree into one with `width` children per node
+    pub fn new(aabb: &aabb::Aabb, width: usize) -> Self {
+        assert!(width >= 2, "Wide BVH nodes need at least 2 children");
+
+        let mut data = Self {
+            width,
+            children: Vec::new(),
+            indices: Vec::new(),
+        };
+
+        let mut costs = Costs::new();
+
+        // A root without children still needs a node to hold it
+        let children = match split(aabb) {
+            Some(_) => data.collapse(aabb, &mut costs),
+            None => vec![aabb],
+        };
+
+        data.push(&children, &mut costs);
+        data
+    }
+
+    // The SAH cost of the wide subtree rooted at `node` (not relative to the root)
+    fn subtree_cost(&self, node: &aabb::Aabb, costs: &mut Costs) -> f32 {
+        let area = node.bounds.surface_area();
+
+        if split(node).is_none() {
+            return area * node.items.len() as f32 * COST_TRI;
+        }
+
+        if let Some(&cost) = costs.get(&(node as *const _)) {
+            return cost;
+        }
+
+        let cost = area * COST_NODE + self
+            .collapse(node, costs)
+            .into_iter()
+            .map(|child| self.subtree_cost(child, costs))
+            .sum::<f32>();
+
+        costs.insert(node as *const _, cost);
+        cost
+    }
+
+    // The binary descendants that become the children of a wide node.
+    // Opens the internal child whose grandchildren lower the SAH cost the most,
+    // until the node is full (or no child would lower it)
+    fn collapse<'a>(&self, node: &'a aabb::Aabb, costs: &mut Costs) -> Vec<&'a aabb::Aabb> {
+        let mut children = Vec::from(split(node).unwrap());
+
+        while children.len() < self.width {
+            let best = children
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, child)| {
+                    let [fst, snd] = split(child)?;
+
+                    // Opening a child drops its node, but its grandchildren
+                    // can no longer be collapsed into it
+                    let saved = self.subtree_cost(child, costs) 
+                        - self.subtree_cost(fst, costs) 
+                        - self.subtree_cost(snd, costs);
+
+                    (saved > 0.).then_some((idx, saved))
+                })
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(idx, _)| idx);
+
+            let Some(idx) = best else { break; };
+
+            let [fst, snd] = split(children[idx]).unwrap();
+
+            children[idx] = fst;
+            children.push(snd);
+        }
+
+        children
+    }
+
+    // Appends a node with the given children, returns its index
+    fn push(&mut self, children: &[&aabb::Aabb], costs: &mut Costs) -> u32 {
+        let node = self.children.len() / self.width;
+
+        self.children.extend(std::iter::repeat_n(WideChild::EMPTY, self.width));
+
+        for (slot, child) in children.iter().enumerate() {
+            let aabb::Bounds { min, max, .. } = child.bounds;
+
+            let (idx, count) = match split(child) {
+                Some(_) => {
+                    let grandchildren = self.collapse(child, costs);
+
+                    (self.push(&grandchildren, costs), 0)
+                },
+                None => {
+                    let idx = self.indices.len() as u32;
+
+                    self.indices.extend(child.items.iter().map(|&i| i as u32));
+
+                    (idx, child.items.len() as u32)
+                },
+            };
+
+            self.children[node * self.width + slot] = WideChild { min, idx, max, count };
+        }
+
+        node as u32
+    }
+
+    // Expected traversal cost under the surface area heuristic,
+    // relative to the root (see `BvhData::cost`)
+    pub fn cost(&self) -> f32 {
+        let area = |child: &WideChild| {
+            aabb::Bounds::from_corners(child.min, child.max).surface_area()
+        };
+
+        let root = self.children
+            .iter()
+            .take(self.width)
+            .filter(|child| !child.is_empty())
+            .fold(aabb::Bounds::empty(), |bounds, child| {
+                bounds.union(&aabb::Bounds::from_corners(child.min, child.max))
+            })
+            .surface_area();
+
+        let children = self.children
+            .iter()
+            .filter(|child| !child.is_empty())
+            .map(|child| match child.count {
+                0 => area(child) * COST_NODE,
+                count => area(child) * count as f32 * COST_TRI,
+            })
+            .sum::<f32>();
+
+        (root * COST_NODE + children) / root.max(f32::MIN_POSITIVE)
+    }
+
+    pub fn nodes(&self) -> usize {
+        self.children.len() / self.width
+    }
+
+    // The number of wide nodes on the longest path from the root
+    pub fn depth(&self) -> usize {
+        fn depth_helper(data: &WideBvhData, node: usize) -> usize {
+            let children = &data.children[(node * data.width)..((node + 1) * data.width)];
+
+            1 + children
+                .iter()
+                .filter(|child| !child.is_empty() && !child.is_leaf())
+                .map(|child| depth_helper(data, child.idx as usize))
+                .max()
+                .unwrap_or(0)
+        }
+
+        if self.children.is_empty() { 0 } else { depth_helper(self, 0) }
+    }
+}
@@ -0,0 +1,501 @@
+use std::sync;
+
+use winit::dpi;
+
+use crate::scene;
+
+// Native captures are written to this directory
+#[cfg(not(target_arch = "wasm32"))]
+pub const DIR: &str = "captures";
+
+// A copy of the compute texture that hasn't been read back yet
+struct CapturePending {
+    kind: CaptureKind,
+    buffer: wgpu::Buffer,
+    size: dpi::PhysicalSize<u32>,
+    bytes_per_row: u32,
+    mapped: bool,
+    completed: sync::Arc<sync::atomic::AtomicBool>,
+}
+
+#[derive(Clone, Copy)]
+#[derive(PartialEq, Eq)]
+enum CaptureKind {
+    Screenshot,
+    // Frames are only recorded on native
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    Frame,
+}
+
+// Tightly-packed RGBA pixels, the top row comes first
+#[derive(Clone)]
+#[derive(Debug)]
+pub struct CaptureImage {
+    pub size: dpi::PhysicalSize<u32>,
+    pub pixels: Vec<u8>,
+}
+
+impl CapturePending {
+    fn new(
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+        kind: CaptureKind,
+    ) -> Self {
+        let size = dpi::PhysicalSize::new(texture.width(), texture.height());
+
+        // Rows of the destination buffer must be aligned
+        let bytes_per_row = (4 * size.width)
+            .next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: (bytes_per_row * size.height) as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(bytes_per_row),
+                    rows_per_image: Some(size.height),
+                },
+            },
+            texture.size(),
+        );
+
+        Self {
+            kind,
+            buffer,
+            size,
+            bytes_per_row,
+            mapped: false,
+            completed: sync::Arc::new(sync::atomic::AtomicBool::new(false)),
+        }
+    }
+}
+
+impl CaptureImage {
+    fn from_pending(pending: &CapturePending) -> Self {
+        let CapturePending {
+            buffer,
+            size,
+            bytes_per_row, ..
+        } = pending;
+
+        let data = buffer.slice(..).get_mapped_range();
+
+        let row = 4 * size.width as usize;
+
+        // The texture's first row is displayed at the bottom of the screen
+        let mut pixels = Vec::with_capacity(row * size.height as usize);
+        for chunk in data.chunks_exact(*bytes_per_row as usize).rev() {
+            pixels.extend_from_slice(&chunk[..row]);
+        }
+
+        Self { size: *size, pixels }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(self, path: &std::path::Path) -> anyhow::Result<()> {
+        use resvg::tiny_skia;
+
+        let Self {
+            size: dpi::PhysicalSize { width, height },
+            pixels,
+        } = self;
+
+        // The output is opaque, so it doesn't need to be premultiplied
+        let pixmap = tiny_skia::IntSize::from_wh(width, height)
+            .and_then(|size| tiny_skia::Pixmap::from_vec(pixels, size))
+            .ok_or(anyhow::anyhow!("Captured image had invalid dimensions"))?;
+
+        pixmap.save_png(path)?;
+
+        Ok(())
+    }
+
+    // Browsers can't write to disk, so the image is downloaded instead
+    #[cfg(target_arch = "wasm32")]
+    fn save(self, name: &str) -> anyhow::Result<()> {
+        use wasm_bindgen::JsCast as _;
+
+        use crate::web::err::WebError;
+
+        let Self {
+            size: dpi::PhysicalSize { width, height },
+            pixels,
+        } = self;
+
+        let doc = web_sys::window()
+            .and_then(|dom| dom.document())
+            .ok_or(WebError::new("obtain document"))?;
+
+        let canvas = doc.create_element("canvas")
+            .map_err(|_| WebError::new("construct canvas"))?
+            .dyn_into::<web_sys::HtmlCanvasElement>()
+            .map_err(|_| WebError::new("construct canvas"))?;
+
+        canvas.set_width(width);
+        canvas.set_height(height);
+
+        let context = canvas.get_context("2d")
+            .ok()
+            .flatten()
+            .and_then(|context| {
+                context.dyn_into::<web_sys::CanvasRenderingContext2d>().ok()
+            })
+            .ok_or(WebError::new("obtain canvas context"))?;
+
+        let data = web_sys::ImageData::new_with_u8_clamped_array_and_sh(
+            wasm_bindgen::Clamped(&pixels), width, height
+        ).map_err(|_| WebError::new("construct image data"))?;
+
+        context.put_image_data(&data, 0., 0.)
+            .map_err(|_| WebError::new("draw captured image"))?;
+
+        let url = canvas.to_data_url()
+            .map_err(|_| WebError::new("encode captured image"))?;
+
+        let anchor = doc.create_element("a")
+            .map_err(|_| WebError::new("construct anchor"))?
+            .dyn_into::<web_sys::HtmlAnchorElement>()
+            .map_err(|_| WebError::new("construct anchor"))?;
+
+        anchor.set_href(&url);
+        anchor.set_download(name);
+        anchor.click();
+
+        Ok(())
+    }
+}
+
+// Sidecar entry for a single recorded frame
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(serde::Serialize)]
+struct CaptureFrame {
+    file: String,
+    camera: Option<scene::CameraUniform>,
+    // Milliseconds since recording began
+    time: f64,
+    // Milliseconds since the previous recorded frame
+    frame_time: f64,
+}
+
+// Writes every frame (and its sidecar entry) on a separate thread
+#[cfg(not(target_arch = "wasm32"))]
+struct Recorder {
+    start: chrono::DateTime<chrono::Local>,
+    prev: chrono::DateTime<chrono::Local>,
+    frames: usize,
+    // Camera state and timing for frames that are still being read back
+    queued: std::collections::VecDeque<(Option<scene::CameraUniform>, f64, f64)>,
+    sender: Option<sync::mpsc::Sender<(CaptureImage, CaptureFrame)>>,
+    handle: Option<std::thread::JoinHandle<anyhow::Result<()>>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Recorder {
+    fn new() -> anyhow::Result<Self> {
+        use std::{fs, path};
+
+        let now = chrono::Local::now();
+
+        let out = path::Path::new(DIR)
+            .join(format!("record-{}", now.format("%Y%m%d-%H%M%S")));
+
+        fs::create_dir_all(&out)?;
+
+        log::info!("Recording frames to {}", out.display());
+
+        let (sender, receiver) = //
+            sync::mpsc::channel::<(CaptureImage, CaptureFrame)>();
+
+        let handle = std::thread::spawn(move || {
+            let mut frames = Vec::new();
+
+            // Runs until the recording is stopped
+            while let Ok((image, frame)) = receiver.recv() {
+                image.save(&out.join(&frame.file))?;
+
+                frames.push(frame);
+            }
+
+            fs::write(out.join("frames.json"), serde_json::to_string_pretty(&frames)?)?;
+
+            Ok(())
+        });
+
+        Ok(Self {
+            start: now,
+            prev: now,
+            frames: 0,
+            queued: std::collections::VecDeque::new(),
+            sender: Some(sender),
+            handle: Some(handle),
+        })
+    }
+
+    // Called when a frame's texture copy is encoded
+    fn queue(&mut self, camera: Option<scene::CameraUniform>) {
+        let now = chrono::Local::now();
+
+        let millis = |duration: chrono::Duration| duration
+            .num_microseconds()
+            .map(|micros| 0.001 * micros as f64)
+            .unwrap_or(duration.num_milliseconds() as f64);
+
+        let time = millis(now.signed_duration_since(self.start));
+        let frame_time = millis(now.signed_duration_since(self.prev));
+
+        self.prev = now;
+        self.queued.push_back((camera, time, frame_time));
+    }
+
+    // Called once the frame has been read back
+    fn push(&mut self, image: CaptureImage) {
+        let Some((camera, time, frame_time)) = self.queued.pop_front() else {
+            return;
+        };
+
+        let frame = CaptureFrame {
+            file: format!("frame-{:05}.png", self.frames),
+            camera,
+            time,
+            frame_time,
+        };
+
+        self.frames += 1;
+
+        if let Some(sender) = &self.sender {
+            let _ = sender.send((image, frame));
+        }
+    }
+
+    // Blocks until all frames have been written
+    fn stop(&mut self) {
+        let _ = self.sender.take();
+
+        if let Some(handle) = self.handle.take() {
+            match handle.join() {
+                Ok(Ok(())) => log::info!("Recorded {} frames", self.frames),
+                Ok(Err(e)) => log::error!("Failed to record frames: {e}"),
+                Err(_) => log::error!("Failed to record frames"),
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for Recorder {
+    fn drop(&mut self) { self.stop(); }
+}
+
+// Copies the compute output into mappable buffers and saves it as PNG
+#[derive(Default)]
+pub struct Capture {
+    pending: Vec<CapturePending>,
+    // The last camera written by `State::update_camera_buffer`
+    camera: Option<scene::CameraUniform>,
+    #[cfg(not(target_arch = "wasm32"))]
+    recorder: Option<Recorder>,
+}
+
+impl std::fmt::Debug for Capture {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Capture")
+            .field("pending", &self.pending.len())
+            .finish()
+    }
+}
+
+impl Capture {
+    pub fn set_camera(&mut self, camera: scene::CameraUniform) {
+        let _ = self.camera.insert(camera);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn toggle_recording(&mut self) -> anyhow::Result<()> {
+        // Dropping the recorder finishes writing its frames
+        if self.recorder.take().is_none() {
+            let _ = self.recorder.insert(Recorder::new()?);
+        }
+
+        Ok(())
+    }
+
+    // Encode a copy of the texture as it currently stands
+    pub fn screenshot(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture: &wgpu::Texture,
+    ) {
+        let mut encoder = device.create_command_encoder({
+            &wgpu::CommandEncoderDescriptor::default()
+        });
+
+        self.copy(device, &mut encoder, texture, CaptureKind::Screenshot);
+
+        queue.submit(Some(encoder.finish()));
+
+        self.post();
+    }
+
+    // Encode a copy of the texture after a compute pass if recording
+    #[allow(unused_variables)]
+    pub fn frame(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+    ) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.queue(self.camera);
+
+            self.copy(device, encoder, texture, CaptureKind::Frame);
+        }
+    }
+
+    fn copy(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+        kind: CaptureKind,
+    ) {
+        self.pending.push(CapturePending::new(device, encoder, texture, kind));
+    }
+
+    // Copy the texture and wait for it to be read back
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture: &wgpu::Texture,
+    ) -> anyhow::Result<CaptureImage> {
+        let mut encoder = device.create_command_encoder({
+            &wgpu::CommandEncoderDescriptor::default()
+        });
+
+        let pending = CapturePending::new(
+            device, 
+            &mut encoder, 
+            texture, 
+            CaptureKind::Screenshot
+        );
+
+        queue.submit(Some(encoder.finish()));
+
+        let (sender, receiver) = sync::mpsc::channel();
+
+        pending.buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+
+        device.poll(wgpu::Maintain::Wait);
+
+        receiver.recv()??;
+
+        let image = CaptureImage::from_pending(&pending);
+
+        pending.buffer.unmap();
+        pending.buffer.destroy();
+
+        Ok(image)
+    }
+
+    // Map all copies once they have been submitted
+    pub fn post(&mut self) {
+        for pending in self.pending.iter_mut().filter(|pending| !pending.mapped) {
+            let completed = pending.completed.clone();
+
+            pending.buffer.slice(..).map_async(wgpu::MapMode::Read, move |_| {
+                completed.store(true, sync::atomic::Ordering::Release);
+            });
+
+            pending.mapped = true;
+        }
+    }
+
+    // Save every capture that has finished copying
+    pub fn poll(&mut self, device: &wgpu::Device) {
+        if self.pending.is_empty() { return; }
+
+        // Map callbacks aren't fired until the device is polled
+        device.poll(wgpu::Maintain::Poll);
+
+        // Frames must be handled in order, so stop at the first incomplete copy
+        while let Some(pending) = self.pending.first() {
+            if !pending.completed.load(sync::atomic::Ordering::Acquire) {
+                break;
+            }
+
+            let pending = self.pending.remove(0);
+
+            let image = CaptureImage::from_pending(&pending);
+
+            pending.buffer.unmap();
+            pending.buffer.destroy();
+
+            match pending.kind {
+                CaptureKind::Screenshot => Self::save(image),
+                #[cfg(not(target_arch = "wasm32"))]
+                CaptureKind::Frame => {
+                    if let Some(recorder) = self.recorder.as_mut() {
+                        recorder.push(image);
+                    }
+                },
+                // Frames are only recorded natively
+                #[cfg(target_arch = "wasm32")]
+                CaptureKind::Frame => {},
+            }
+        }
+    }
+
+    fn save(image: CaptureImage) {
+        let name = format!(
+            "screenshot-{}.png",
+            chrono::Local::now().format("%Y%m%d-%H%M%S%.3f")
+        );
+
+        cfg_if::cfg_if! {
+            if #[cfg(target_arch = "wasm32")] {
+                if let Err(e) = image.save(&name) {
+                    log::error!("Failed to save screenshot: {e}");
+                }
+            } else {
+                use std::{fs, path};
+
+                // Encoding is slow enough to stall the event loop
+                std::thread::spawn(move || {
+                    let path = path::Path::new(DIR).join(name);
+
+                    let result = fs::create_dir_all(DIR)
+                        .map_err(anyhow::Error::from)
+                        .and_then(|_| image.save(&path));
+
+                    match result {
+                        Ok(_) => log::info!("Saved screenshot to {}", path.display()),
+                        Err(e) => log::error!("Failed to save screenshot: {e}"),
+                    }
+                });
+            }
+        }
+    }
+}
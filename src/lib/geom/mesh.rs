@@ -0,0 +1,523 @@
+use std::{cmp, collections, mem};
+
+use crate::geom::{self, V3Ops as _};
+
+// Determines how vertex normals are generated for a mesh
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub enum MeshShading {
+    // Normals are averaged across adjacent faces (angle-weighted)
+    Smooth,
+    // Every face gets its own vertices with the face normal
+    Flat,
+}
+
+// All processing options applied when a mesh is added to a scene
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub struct MeshConfig {
+    // Vertices closer than this distance are merged
+    pub weld: Option<f32>,
+    // Adjacent faces only share a normal if the angle
+    // between them (in degrees) doesn't exceed this threshold
+    pub crease: Option<f32>,
+    pub shading: MeshShading,
+    // The number of triangles to decimate the mesh down to
+    pub target: Option<usize>,
+}
+
+impl MeshConfig {
+    pub const fn new() -> Self {
+        Self {
+            weld: None,
+            crease: None,
+            shading: MeshShading::Smooth,
+            target: None,
+        }
+    }
+}
+
+impl Default for MeshConfig {
+    fn default() -> Self { Self::new() }
+}
+
+// An indexed triangle mesh that hasn't been added to a scene yet
+#[derive(Clone)]
+#[derive(Debug)]
+pub struct Mesh {
+    pub positions: Vec<[f32; 3]>,
+    pub faces: Vec<[usize; 3]>,
+    // Normals provided by the source file (for each corner of each face)
+    pub normals: Vec<[Option<[f32; 3]>; 3]>,
+}
+
+impl Mesh {
+    // Faces whose area is smaller than this fraction of
+    // the parallelogram spanned by their edges are rejected
+    const DEGENERATE: f32 = 0.000001;
+
+    // Multiplier applied to the constraint planes along open edges,
+    // this keeps decimation from eating into mesh boundaries
+    const BOUNDARY_WEIGHT: f64 = 1000.;
+
+    pub fn from_obj(obj: &wavefront::Obj) -> Self {
+        let mut faces = vec![];
+        let mut normals = vec![];
+
+        for [a, b, c] in obj.triangles() {
+            faces.push([
+                a.position_index(),
+                b.position_index(),
+                c.position_index(),
+            ]);
+
+            normals.push([a.normal(), b.normal(), c.normal()]);
+        }
+
+        Self {
+            positions: obj.positions().to_vec(),
+            faces,
+            normals,
+        }
+    }
+
+    // Applies welding, culling and decimation (in that order)
+    pub fn process(&mut self, config: MeshConfig) {
+        if let Some(eps) = config.weld {
+            self.weld(eps);
+        }
+
+        self.cull();
+
+        if let Some(target) = config.target {
+            self.decimate(target);
+        }
+    }
+
+    // Merge all vertices that lie within `eps` of each other
+    pub fn weld(&mut self, eps: f32) {
+        if eps <= 0. { return; }
+
+        let cell = |pos: [f32; 3]| -> [i64; 3] {
+            pos.map(|c| (c / eps).floor() as i64)
+        };
+
+        // Each grid cell holds the welded vertices that fall inside it
+        let mut grid = collections::HashMap::<[i64; 3], Vec<usize>>::new();
+
+        let mut welded: Vec<[f32; 3]> = Vec::new();
+        let mut remap = Vec::with_capacity(self.positions.len());
+
+        for &pos in self.positions.iter() {
+            let [x, y, z] = cell(pos);
+
+            // Welded vertices can only be found in the neighboring cells
+            let mut found = None;
+            'search: for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        let Some(candidates) = grid
+                            .get(&[x + dx, y + dy, z + dz]) else { continue; };
+
+                        for &idx in candidates.iter() {
+                            if welded[idx].sub(pos).mag() <= eps {
+                                found = Some(idx); break 'search;
+                            }
+                        }
+                    }
+                }
+            }
+
+            remap.push(found.unwrap_or_else(|| {
+                welded.push(pos);
+
+                grid.entry([x, y, z]).or_default().push(welded.len() - 1);
+
+                welded.len() - 1
+            }));
+        }
+
+        for face in self.faces.iter_mut() {
+            *face = face.map(|idx| remap[idx]);
+        }
+
+        self.positions = welded;
+    }
+
+    fn degenerate(&self, face: [usize; 3]) -> bool {
+        let [a, b, c] = face;
+
+        if a == b || b == c || c == a { return true; }
+
+        let ab = self.positions[b].sub(self.positions[a]);
+        let ac = self.positions[c].sub(self.positions[a]);
+
+        let area = ab.cross(ac).mag();
+
+        // NOTE: NaN areas are rejected as well
+        area.is_nan() || area <= Self::DEGENERATE * ab.mag() * ac.mag()
+    }
+
+    // Removes all degenerate faces, returns the number of faces removed
+    pub fn cull(&mut self) -> usize {
+        let count = self.faces.len();
+
+        let (faces, normals) = self.faces
+            .iter()
+            .zip(self.normals.iter())
+            .filter(|(&face, _)| !self.degenerate(face))
+            .map(|(&face, &normals)| (face, normals))
+            .unzip();
+
+        self.faces = faces;
+        self.normals = normals;
+
+        count - self.faces.len()
+    }
+
+    // Quadric error decimation (Garland & Heckbert)
+    // Edges are collapsed until `target` triangles remain
+    pub fn decimate(&mut self, target: usize) {
+        if self.faces.len() <= target { return; }
+
+        let mut positions = self.positions
+            .iter()
+            .map(|pos| pos.map(f64::from))
+            .collect::<Vec<_>>();
+
+        let mut faces = self.faces.clone();
+
+        fn plane([a, b, c]: [[f64; 3]; 3]) -> [f64; 3] {
+            b.sub(a).cross(c.sub(a))
+        }
+
+        let mut quadrics = vec![Quadric::default(); positions.len()];
+        let mut incident = vec![Vec::new(); positions.len()];
+
+        // Count the faces on each edge to find the boundaries
+        // NOTE: Ordered so that decimation is deterministic
+        let mut edges = collections::BTreeMap::<(usize, usize), Vec<usize>>::new();
+
+        for (idx, &face) in faces.iter().enumerate() {
+            let normal = plane(face.map(|idx| positions[idx])).normalize();
+
+            let q = Quadric::new(normal, positions[face[0]]);
+
+            for k in 0..3 {
+                let (a, b) = (face[k], face[(k + 1) % 3]);
+
+                quadrics[a].add(&q);
+                incident[a].push(idx);
+
+                edges.entry((a.min(b), a.max(b))).or_default().push(idx);
+            }
+        }
+
+        for (&(a, b), adjacent) in edges.iter() {
+            if let [face] = adjacent[..] {
+                let edge = positions[b].sub(positions[a]);
+
+                // A plane through the edge perpendicular to its face
+                let normal = edge
+                    .cross(plane(faces[face].map(|idx| positions[idx])).normalize())
+                    .normalize();
+
+                let mut q = Quadric::new(normal, positions[a]);
+                    q.scale(Self::BOUNDARY_WEIGHT * edge.dot(edge));
+
+                quadrics[a].add(&q);
+                quadrics[b].add(&q);
+            }
+        }
+
+        // Bumped whenever a vertex moves, this invalidates stale candidates
+        let mut stamps = vec![0_u32; positions.len()];
+
+        let mut alive = vec![true; faces.len()];
+        let mut count = faces.len();
+
+        fn candidate(
+            a: usize,
+            b: usize,
+            quadrics: &[Quadric],
+            positions: &[[f64; 3]],
+            stamps: &[u32],
+        ) -> cmp::Reverse<Collapse> {
+            let mut q = quadrics[a]; q.add(&quadrics[b]);
+
+            let pos = q.optimal().unwrap_or_else(|| {
+                let mid = positions[a].add(positions[b]).scale(0.5);
+
+                [positions[a], positions[b], mid]
+                    .into_iter()
+                    .min_by(|x, y| q.eval(*x).total_cmp(&q.eval(*y)))
+                    .unwrap()
+            });
+
+            cmp::Reverse(Collapse {
+                cost: q.eval(pos).max(0.),
+                a,
+                b,
+                stamps: [stamps[a], stamps[b]],
+                pos,
+            })
+        }
+
+        let mut heap = edges
+            .keys()
+            .map(|&(a, b)| candidate(a, b, &quadrics, &positions, &stamps))
+            .collect::<collections::BinaryHeap<_>>();
+
+        while count > target {
+            let Some(cmp::Reverse(Collapse {
+                a,
+                b,
+                stamps: [sa, sb],
+                pos, .. 
+            })) = heap.pop() else { break; };
+
+            if stamps[a] != sa || stamps[b] != sb { continue; }
+
+            // Reject collapses that would flip or flatten a neighboring face
+            let flips = incident[a].iter().chain(incident[b].iter())
+                .filter(|&&idx| alive[idx])
+                .filter(|&&idx| !(faces[idx].contains(&a) && faces[idx].contains(&b)))
+                .any(|&idx| {
+                    let before = plane(faces[idx].map(|v| positions[v]));
+                    let after = plane(faces[idx].map(|v| {
+                        if v == a || v == b { pos } else { positions[v] }
+                    }));
+
+                    before.dot(after) <= 0.
+                });
+
+            if flips { continue; }
+
+            positions[a] = pos;
+
+            let q = quadrics[b]; quadrics[a].add(&q);
+
+            for idx in mem::take(&mut incident[b]) {
+                if !alive[idx] { continue; }
+
+                if faces[idx].contains(&a) {
+                    alive[idx] = false; count -= 1;
+                } else {
+                    for vertex in faces[idx].iter_mut() {
+                        if *vertex == b { *vertex = a; }
+                    }
+
+                    incident[a].push(idx);
+                }
+            }
+
+            incident[a].retain(|&idx| alive[idx]);
+            incident[a].dedup();
+
+            stamps[a] += 1;
+            stamps[b] += 1;
+
+            // Queue up new candidates for every edge around `a`
+            let mut neighbors = incident[a]
+                .iter()
+                .flat_map(|&idx| faces[idx])
+                .filter(|&idx| idx != a)
+                .collect::<Vec<_>>();
+
+            neighbors.sort_unstable();
+            neighbors.dedup();
+
+            for c in neighbors {
+                heap.push(candidate(a, c, &quadrics, &positions, &stamps));
+            }
+        }
+
+        self.positions = positions
+            .into_iter()
+            .map(|pos| pos.map(|c| c as f32))
+            .collect();
+
+        self.faces = faces
+            .into_iter()
+            .zip(alive)
+            .filter_map(|(face, alive)| alive.then_some(face))
+            .collect();
+
+        // The source normals no longer describe the surface
+        self.normals = vec![[None; 3]; self.faces.len()];
+
+        self.cull();
+    }
+
+    // Generate the scene vertices and triangle indices
+    pub fn assemble(
+        &self,
+        config: MeshConfig
+    ) -> (Vec<geom::PrimVertex>, Vec<[u32; 3]>) {
+        let face_normals = self.faces
+            .iter()
+            .map(|face| {
+                let [a, b, c] = face.map(|idx| self.positions[idx]);
+
+                b.sub(a).cross(c.sub(a)).normalize()
+            }).collect::<Vec<_>>();
+
+        // Every (face, corner) pair that touches a given position
+        let mut incident = vec![Vec::new(); self.positions.len()];
+        for (idx, face) in self.faces.iter().enumerate() {
+            for (corner, &vertex) in face.iter().enumerate() {
+                incident[vertex].push((idx, corner));
+            }
+        }
+
+        // The angle-weighted normal each corner contributes to its vertex
+        let contribution = |idx: usize, corner: usize| -> [f32; 3] {
+            self.normals[idx][corner].unwrap_or_else(|| {
+                let face = self.faces[idx];
+
+                let p = self.positions[face[corner]];
+                let p1 = self.positions[face[(corner + 1) % 3]];
+                let p2 = self.positions[face[(corner + 2) % 3]];
+
+                face_normals[idx].scale(p.angle(p1, p2))
+            })
+        };
+
+        let crease = config.crease.map(|deg| deg.to_radians().cos());
+
+        // Corners that share a position and a normal share a vertex
+        let mut lookup = collections::HashMap::<(usize, [u32; 3]), u32>::new();
+
+        let mut vertices = Vec::new();
+        let mut indices = Vec::with_capacity(self.faces.len());
+
+        for (idx, face) in self.faces.iter().enumerate() {
+            let mut tri = [0; 3];
+
+            for (corner, &vertex) in face.iter().enumerate() {
+                let normal = match config.shading {
+                    MeshShading::Flat => face_normals[idx],
+                    MeshShading::Smooth => incident[vertex]
+                        .iter()
+                        .filter(|(other, _)| crease.map(|crease| {
+                            face_normals[idx].dot(face_normals[*other]) >= crease
+                        }).unwrap_or(true))
+                        .map(|&(other, corner)| contribution(other, corner))
+                        .fold([0.; 3], |n, c| n.add(c))
+                        .normalize(),
+                };
+
+                tri[corner] = *lookup
+                    .entry((vertex, normal.map(f32::to_bits)))
+                    .or_insert_with(|| {
+                        vertices.push({
+                            geom::PrimVertex::new(self.positions[vertex], normal)
+                        });
+
+                        (vertices.len() - 1) as u32
+                    });
+            }
+
+            indices.push(tri);
+        }
+
+        (vertices, indices)
+    }
+}
+
+// Symmetric 4x4 error quadric, only the upper triangle is stored
+#[derive(Clone, Copy)]
+#[derive(Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    // The quadric of the plane with the given normal through `at`
+    fn new(normal: [f64; 3], at: [f64; 3]) -> Self {
+        let [a, b, c] = normal;
+
+        let d = -normal.dot(at);
+
+        Self([
+            a * a, a * b, a * c, a * d,
+                   b * b, b * c, b * d,
+                          c * c, c * d,
+                                 d * d,
+        ])
+    }
+
+    fn add(&mut self, other: &Self) {
+        for (a, b) in self.0.iter_mut().zip(other.0) { *a += b; }
+    }
+
+    fn scale(&mut self, s: f64) {
+        for a in self.0.iter_mut() { *a *= s; }
+    }
+
+    fn eval(&self, [x, y, z]: [f64; 3]) -> f64 {
+        let [a2, ab, ac, ad, b2, bc, bd, c2, cd, d2] = self.0;
+
+        a2 * x * x + 2. * ab * x * y + 2. * ac * x * z + 2. * ad * x
+            + b2 * y * y + 2. * bc * y * z + 2. * bd * y
+            + c2 * z * z + 2. * cd * z
+            + d2
+    }
+
+    // The position that minimizes the error (if the system is solvable)
+    fn optimal(&self) -> Option<[f64; 3]> {
+        let [a2, ab, ac, ad, b2, bc, bd, c2, cd, _] = self.0;
+
+        let det = |m: [[f64; 3]; 3]| {
+            m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+                - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+                + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+        };
+
+        let m = [[a2, ab, ac], [ab, b2, bc], [ac, bc, c2]];
+        let r = [-ad, -bd, -cd];
+
+        let d = det(m);
+
+        if d.abs() < 0.000000000001 { return None; }
+
+        // Cramer's rule
+        let mut out = [0.; 3];
+        for (col, out) in out.iter_mut().enumerate() {
+            let mut temp = m;
+            for row in 0..3 { temp[row][col] = r[row]; }
+
+            *out = det(temp) / d;
+        }
+
+        Some(out)
+    }
+}
+
+struct Collapse {
+    cost: f64,
+    a: usize,
+    b: usize,
+    stamps: [u32; 2],
+    pos: [f64; 3],
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.cost
+            .total_cmp(&other.cost)
+            .then((self.a, self.b).cmp(&(other.a, other.b)))
+    }
+}
@@ -1,4 +1,5 @@
 pub mod light;
+pub mod mesh;
 
 mod v3; pub use v3::V3Ops;
 
@@ -11,8 +11,8 @@ impl super::IntrsHandler for BlankIntrs {
     
     fn vars<'a>(
         &self,
-        _scene: &mut scene::Scene, device: &wgpu::Device,
-    ) -> (super::IntrsPack<'a>, super::IntrsStats) {
+        _scene: &scene::Scene, device: &wgpu::Device,
+    ) -> anyhow::Result<(super::IntrsPack<'a>, super::IntrsStats)> {
         let layout = device.create_bind_group_layout(
             &wgpu::BindGroupLayoutDescriptor {
                 label: None,
@@ -32,14 +32,16 @@ impl super::IntrsHandler for BlankIntrs {
             vars: Vec::with_capacity(0),
             group,
             layout,
+            prims: None,
         };
 
         let stats = super::IntrsStats { 
             name: "Blank",
             size: 0,
+            build: None,
         };
 
-        (pack, stats)
+        Ok((pack, stats))
     }
 
     fn logic(&self) -> &'static str {
@@ -9,31 +9,38 @@ use crate::bvh;
 
 // This stores all configuration options 
 // for construction of the BVH and its intersection logic
+#[derive(Clone)]
+#[derive(Default)]
 pub enum BvhConfig {
     Bytes(Vec<u8>),
     Runtime { eps: f32, },
+    // Constructs the BVH with a strategy other than object splits
+    Builder { eps: f32, builder: bvh::BvhBuilder, },
+    #[default]
     Default,
 }
 
-impl Default for BvhConfig {
-    fn default() -> Self { Self::Default }
-}
-
 pub struct BvhIntrs {
     pub eps: f32,
+    pub builder: bvh::BvhBuilder,
 
     // These members are private, 
     // binaries should access them through BvhConfig
     data: unsync::OnceCell<bvh::BvhData>,
-    nodes: unsync::OnceCell<usize>,
+    // Construction time (MS), empty if the BVH was precomputed
+    build: unsync::OnceCell<f32>,
+    // Capacity of the traversal stack
+    stack: unsync::OnceCell<usize>,
 }
 
 impl Default for BvhIntrs {
     fn default() -> Self {
         Self { 
             eps: 0.02, 
+            builder: bvh::BvhBuilder::Object,
             data: unsync::OnceCell::new(),
-            nodes: unsync::OnceCell::new(),
+            build: unsync::OnceCell::new(),
+            stack: unsync::OnceCell::new(),
         }
     }
 }
@@ -49,16 +56,18 @@ impl BvhIntrs {
 impl super::IntrsHandler for BvhIntrs {
     type Config = BvhConfig;
 
+    const KIND: Option<super::IntrsKind> = Some(super::IntrsKind::Bvh);
+
     fn new(config: Self::Config) -> anyhow::Result<Self> {
         let intrs = match config {
             BvhConfig::Bytes(bytes) => {
                 let data = serde_json::from_slice::<bvh::BvhData>(&bytes)?;
 
-                let nodes = data.uniforms.len();
+                let stack = data.depth();
 
                 Self {
                     data: unsync::OnceCell::with_value(data),
-                    nodes: unsync::OnceCell::with_value(nodes),
+                    stack: unsync::OnceCell::with_value(stack),
                     ..Default::default()
                 }
             },
@@ -66,6 +75,11 @@ impl super::IntrsHandler for BvhIntrs {
                 eps,
                 ..Default::default()
             },
+            BvhConfig::Builder { eps, builder } => Self {
+                eps,
+                builder,
+                ..Default::default()
+            },
             BvhConfig::Default => Self::default(),
         };
 
@@ -74,14 +88,18 @@ impl super::IntrsHandler for BvhIntrs {
 
     fn vars<'a>(
         &self,
-        scene: &mut crate::scene::Scene, 
+        scene: &crate::scene::Scene, 
         device: &wgpu::Device
-    ) -> (super::IntrsPack<'a>, super::IntrsStats) {
+    ) -> anyhow::Result<(super::IntrsPack<'a>, super::IntrsStats)> {
         // Build the BVH if we haven't already
         let data = self.data.get_or_init(|| {
-            let aabb = bvh::Aabb::from_scene(self.eps, scene, 2);
+            let (data, build) = bvh::timed(|| {
+                bvh::BvhData::new(&self.builder.build(self.eps, scene, 2))
+            });
 
-            bvh::BvhData::new(&aabb)
+            let _ = self.build.set(build);
+
+            data
         });
 
         let bvh::BvhData {
@@ -89,8 +107,8 @@ impl super::IntrsHandler for BvhIntrs {
             indices, ..
         } = data;
 
-        // Set the node count if we haven't already
-        self.nodes.get_or_init(|| uniforms.len());
+        // Size the stack if we haven't already
+        self.stack.get_or_init(|| data.depth());
 
         let aabb_uniforms = device.create_buffer_init(
             &wgpu::util::BufferInitDescriptor {
@@ -100,14 +118,7 @@ impl super::IntrsHandler for BvhIntrs {
             }
         );
 
-        if let crate::scene::Scene::Active { prims, .. } = scene {
-            let ordered = indices
-                .iter()
-                .map(|&idx| prims[idx as usize])
-                .collect::<Vec<_>>();
-
-            let _ = mem::replace(prims, ordered);
-        }
+        let prims = super::ordered(scene, indices);
 
         let layout = device.create_bind_group_layout(
             &wgpu::BindGroupLayoutDescriptor {
@@ -155,14 +166,20 @@ impl super::IntrsHandler for BvhIntrs {
             ],
             group,
             layout,
+            prims,
         };
 
         let stats = super::IntrsStats {
-            name: "BVH",
+            name: match self.builder {
+                bvh::BvhBuilder::Object => "BVH",
+                bvh::BvhBuilder::Sbvh => "BVH (SBVH)",
+                bvh::BvhBuilder::Lbvh => "BVH (LBVH)",
+            },
             size: mem::size_of::<bvh::AabbUniform>() * uniforms.len(),
+            build: self.build.get().copied(),
         };
 
-        (pack, stats)
+        Ok((pack, stats))
     }
 
     fn logic(&self) -> &'static str {
@@ -172,19 +189,27 @@ impl super::IntrsHandler for BvhIntrs {
 
         // IntrsHandler::logic is always called after IntrsHandler::vars,
         // so the diverging case is truly unreachable
-        let Some(nodes) = self.nodes.get().copied() else { 
+        let Some(stack) = self.stack.get().copied() else { 
             unreachable!();
         };
 
         // Perform the replacement
         let mut logic = String::from(LOGIC); logic.insert_str(
             LOGIC.find(DECL).unwrap() + DECL.len() - 1, 
-            format!(": array<u32, {nodes}>",).as_str()
+            format!(": array<u32, {stack}>",).as_str()
         );
         
         // We have to return a static string, so we leak it
         Box::leak(logic.into_boxed_str())
     }
+
+    fn bvh(&self) -> Option<&bvh::BvhData> {
+        self.data.get()
+    }
+
+    fn refit_var(&self) -> Option<&'static str> {
+        Some("aabb_uniforms")
+    }
 }
 
 // The intersection logic
@@ -202,92 +227,12 @@ const LOGIC: &str = "\
         bounds: Bounds,
     }
 
-    fn intrs_tri(r: Ray, s: Prim) -> Intrs {
-        let e1: vec3<f32> = vertices[s.b].pos - vertices[s.a].pos;
-        let e2: vec3<f32> = vertices[s.c].pos - vertices[s.a].pos;
-
-        let p: vec3<f32> = cross(r.dir, e2);
-        let t: vec3<f32> = r.origin - vertices[s.a].pos;
-        let q: vec3<f32> = cross(t, e1);
-
-        let det = dot(e1, p);
-
-        var u: f32 = 0.0;
-        var v: f32 = 0.0;
-        if(det > config.eps) {
-            u = dot(t, p);
-            if(u < 0.0 || u > det) { return intrs_empty(); }
-
-            v = dot(r.dir, q);
-            if(v < 0.0 || u + v > det) { return intrs_empty(); }
-        } else if(det < -1.0 * config.eps) {
-            u = dot(t, p);
-            if(u > 0.0 || u < det) { return intrs_empty(); }
-
-            v = dot(r.dir, q);
-            if(v > 0.0 || u + v < det) { return intrs_empty(); }
-        } else {
-            return intrs_empty();
-        }
-
-        let w: f32 = dot(e2, q) / det;
-        
-        if(w > config.t_max || w < config.t_min) {
-            return intrs_empty();
-        } else {
-            return Intrs(s, w);
-        }
-    }
-
-    const INF_POS: f32 = 0x1.p+38f;
-    const INF_NEG: f32 = -1.0 * INF_POS;
-
-    // Wobble for the intersection test below
-    const EPS: f32 = 0.000002;
-
-    fn collides(bb: Aabb, ray: Ray) -> bool {
-        var t0 = (bb.bounds.min.x - EPS - ray.origin.x) / ray.dir.x;
-        var t1 = (bb.bounds.max.x + EPS - ray.origin.x) / ray.dir.x;
-
-        var t_min = min(t0, t1);
-        var t_max = max(t0, t1);
-        
-        t0 = (bb.bounds.min.y - EPS - ray.origin.y) / ray.dir.y;
-        t1 = (bb.bounds.max.y + EPS - ray.origin.y) / ray.dir.y;
-
-        t_min = max(t_min, min(min(t0, t1), INF_NEG));
-        t_max = min(t_max, max(max(t0, t1), INF_POS));
-        
-        t0 = (bb.bounds.min.z - EPS - ray.origin.z) / ray.dir.z;
-        t1 = (bb.bounds.max.z + EPS - ray.origin.z) / ray.dir.z;
-
-        t_min = max(t_min, min(min(t0, t1), INF_NEG));
-        t_max = min(t_max, max(max(t0, t1), INF_POS));
-
-        return (t_min < t_max);
-    }
-
-    // TODO: The vector-based collision method is faster,
-    // but does not yet incorporate the INF_POS/_NEG testing.
-    // Implement the edge case, then switch to this algorithm
-    fn collides_wiche(bb: Aabb, ray: Ray) -> bool {
-        let t0s = (bb.bounds.min - ray.origin) / ray.dir;
-        let t1s = (bb.bounds.max - ray.origin) / ray.dir;
-
-        let t_mins = min(t0s, t1s);
-        let t_maxs = max(t0s, t1s);
-
-        let t_min = max(config.t_min, max(t_mins.x, max(t_mins.y, t_mins.z)));
-        let t_max = min(config.t_max, min(t_maxs.x, min(t_maxs.y, t_maxs.z)));
-
-        return (t_min < t_max);
-    }
-
     fn intrs_bvh(bb: Aabb, ray: Ray, excl: Prim) -> Intrs {
         var intrs: Intrs = intrs_empty();
 
         for(var i: u32 = bb.item_idx; i < (bb.item_idx + bb.item_count); i = i + 1u) {
-            let prim: Prim = primitives[i];
+            // The first primitive is the 'null' prim
+            let prim: Prim = primitives[i + 1u];
 
             let temp: Intrs = intrs_tri(ray, prim);
 
@@ -330,7 +275,9 @@ const LOGIC: &str = "\
             let bb_idx = pop(&stack_idx, &stack_empty);
             let bb = aabb_uniforms[bb_idx];
 
-            if(collides(bb, r)) {
+            count_node();
+
+            if(collides(bb.bounds.min, bb.bounds.max, r)) {
                 if(bb.item_count > 0u) {
                     let temp = intrs_bvh(bb, r, excl);
 
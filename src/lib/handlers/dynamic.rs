@@ -0,0 +1,113 @@
+use crate::{bvh, scene};
+
+use super::IntrsHandler;
+
+// The object-safe half of `IntrsHandler`,
+// implemented for every handler so it can be boxed
+pub trait DynIntrsHandler {
+    fn vars<'a>(
+        &self,
+        scene: &scene::Scene,
+        device: &wgpu::Device,
+    ) -> anyhow::Result<(super::IntrsPack<'a>, super::IntrsStats)>;
+
+    fn logic(&self) -> &'static str;
+
+    fn bvh(&self) -> Option<&bvh::BvhData>;
+
+    fn refit_var(&self) -> Option<&'static str>;
+}
+
+impl<H: IntrsHandler> DynIntrsHandler for H {
+    fn vars<'a>(
+        &self,
+        scene: &scene::Scene,
+        device: &wgpu::Device,
+    ) -> anyhow::Result<(super::IntrsPack<'a>, super::IntrsStats)> {
+        IntrsHandler::vars(self, scene, device)
+    }
+
+    fn logic(&self) -> &'static str { IntrsHandler::logic(self) }
+
+    fn bvh(&self) -> Option<&bvh::BvhData> { IntrsHandler::bvh(self) }
+
+    fn refit_var(&self) -> Option<&'static str> { IntrsHandler::refit_var(self) }
+}
+
+// The handlers that can be selected (and swapped) at runtime,
+// each is built with its default config
+#[derive(Clone, Copy)]
+#[derive(PartialEq, Eq)]
+#[derive(Default)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum IntrsKind {
+    Naive,
+    #[default]
+    Bvh,
+    BvhRf,
+    BvhThreaded,
+    BvhWide,
+    BvhQuant,
+}
+
+impl IntrsKind {
+    pub const ALL: [Self; 6] = [
+        Self::Naive,
+        Self::Bvh,
+        Self::BvhRf,
+        Self::BvhThreaded,
+        Self::BvhWide,
+        Self::BvhQuant,
+    ];
+
+    // Cycles through every kind, wrapping back to the first
+    pub fn next(self) -> Self {
+        let idx = Self::ALL
+            .iter()
+            .position(|&kind| kind == self)
+            .unwrap_or(0);
+
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    pub fn build(self) -> anyhow::Result<Box<dyn DynIntrsHandler>> {
+        Ok(match self {
+            Self::Naive => Box::new(super::BasicIntrs::new(())?),
+            Self::Bvh => Box::new(super::BvhIntrs::new(Default::default())?),
+            Self::BvhRf => Box::new(super::RfBvhIntrs::new(Default::default())?),
+            Self::BvhThreaded => Box::new(super::ThreadedBvhIntrs::new(Default::default())?),
+            Self::BvhWide => Box::new(super::WideBvhIntrs::new(Default::default())?),
+            Self::BvhQuant => Box::new(super::QuantBvhIntrs::new(Default::default())?),
+        })
+    }
+}
+
+// A boxed handler, chosen by `IntrsKind` instead of at compile time.
+// Passing it to `State::load` swaps handlers while keeping the scene
+pub struct DynIntrs {
+    inner: Box<dyn DynIntrsHandler>,
+}
+
+impl IntrsHandler for DynIntrs {
+    type Config = IntrsKind;
+
+    fn new(config: Self::Config) -> anyhow::Result<Self> {
+        Ok(Self { inner: config.build()? })
+    }
+
+    fn vars<'a>(
+        &self,
+        scene: &scene::Scene,
+        device: &wgpu::Device,
+    ) -> anyhow::Result<(super::IntrsPack<'a>, super::IntrsStats)> {
+        self.inner.vars(scene, device)
+    }
+
+    fn logic(&self) -> &'static str { self.inner.logic() }
+
+    fn bvh(&self) -> Option<&bvh::BvhData> { self.inner.bvh() }
+
+    fn refit_var(&self) -> Option<&'static str> { self.inner.refit_var() }
+}
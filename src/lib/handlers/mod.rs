@@ -7,11 +7,23 @@ pub use bvh::{BvhIntrs, BvhConfig};
 mod rf;
 pub use rf::{RfBvhIntrs, RfBvhConfig};
 
+mod threaded;
+pub use threaded::ThreadedBvhIntrs;
+
+mod wide;
+pub use wide::{WideBvhIntrs, WideBvhConfig};
+
+mod quant;
+pub use quant::{QuantBvhIntrs, QuantBvhConfig};
+
+mod dynamic;
+pub use dynamic::{DynIntrs, DynIntrsHandler, IntrsKind};
+
 mod blank;
 // NOTE: Dummy intersection handler used for benchmarking
 pub use blank::BlankIntrs;
 
-use crate::scene;
+use crate::{geom, scene};
 
 #[derive(Debug)]
 pub struct IntrsVar<'a> {
@@ -32,6 +44,9 @@ pub struct IntrsPack<'a> {
     pub vars: Vec<IntrsVar<'a>>,
     pub group: wgpu::BindGroup,
     pub layout: wgpu::BindGroupLayout,
+    // The prims in the order the logic indexes them (without the 'null' prim)
+    // Empty when the logic uses the scene's own order
+    pub prims: Option<Vec<geom::Prim>>,
 }
 
 impl<'a> IntrsPack<'a> {
@@ -47,21 +62,45 @@ impl<'a> IntrsPack<'a> {
 pub struct IntrsStats {
     pub name: &'static str,
     pub size: usize,
+    // Time spent constructing the hierarchy (MS)
+    // Empty for handlers without one, or when it was precomputed
+    pub build: Option<f32>,
 }
 
 pub trait IntrsHandler {
-    type Config: Default;
+    type Config: Default + Clone;
+
+    // The kind that builds this handler at runtime (see `DynIntrs`), if there is one
+    const KIND: Option<IntrsKind> = None;
 
     fn new(config: Self::Config) -> anyhow::Result<Self> 
         where Self: Sized;
 
     // Builds all the requisite buffers and groups
+    // Fails if the scene can't be represented by the handler
     fn vars<'a>(
         &self,
-        scene: &mut scene::Scene, 
+        scene: &scene::Scene, 
         device: &wgpu::Device,
-    ) -> (IntrsPack<'a>, IntrsStats);
+    ) -> anyhow::Result<(IntrsPack<'a>, IntrsStats)>;
 
     // Contains all of the intersection logic
     fn logic(&self) -> &'static str;
-}
\ No newline at end of file
+
+    // The hierarchy traversed by the intersection logic (if there is one)
+    // Like `logic`, this is only valid after `vars` has been called
+    fn bvh(&self) -> Option<&crate::bvh::BvhData> { None }
+
+    // The var holding the nodes of `bvh` (in order), if they can be refit
+    // when vertices move. Each node must end with its `bvh::Bounds`,
+    // following 16 bytes of links like `bvh::AabbUniform`
+    fn refit_var(&self) -> Option<&'static str> { None }
+}
+
+// Gathers the scene's prims in the order of a hierarchy's leaves,
+// prims referenced by several leaves (spatial splits) are repeated
+fn ordered(scene: &scene::Scene, indices: &[u32]) -> Option<Vec<geom::Prim>> {
+    let scene::Scene::Active { prims, .. } = scene else { return None; };
+
+    Some(indices.iter().map(|&idx| prims[idx as usize]).collect())
+}
@@ -0,0 +1,307 @@
+use std::mem;
+
+// Needed for `device.create_buffer_init`
+use wgpu::util::DeviceExt as _;
+
+use once_cell::unsync;
+
+use crate::bvh;
+
+#[derive(Clone)]
+#[derive(Default)]
+pub enum QuantBvhConfig {
+    Runtime { eps: f32, precision: bvh::QuantPrecision, },
+    #[default]
+    Default,
+}
+
+// Generalises the f16 packing of RfBvhIntrs,
+// child boxes are quantised relative to their parent
+pub struct QuantBvhIntrs {
+    pub eps: f32,
+    pub precision: bvh::QuantPrecision,
+
+    // The binary tree is kept around for the overlay
+    data: unsync::OnceCell<(bvh::BvhData, bvh::QuantBvhData)>,
+    // Construction time (MS), including quantisation
+    build: unsync::OnceCell<f32>,
+    // Capacity of the traversal stack
+    stack: unsync::OnceCell<usize>,
+}
+
+impl Default for QuantBvhIntrs {
+    fn default() -> Self {
+        Self {
+            eps: 0.02,
+            precision: bvh::QuantPrecision::default(),
+            data: unsync::OnceCell::new(),
+            build: unsync::OnceCell::new(),
+            stack: unsync::OnceCell::new(),
+        }
+    }
+}
+
+impl QuantBvhIntrs {
+    // When reloading scenes, we may want to write into our previous buffers
+    const COPY_USAGES: wgpu::BufferUsages = {
+        wgpu::BufferUsages::COPY_SRC //
+            .union(wgpu::BufferUsages::COPY_DST) //
+    };
+}
+
+impl super::IntrsHandler for QuantBvhIntrs {
+    type Config = QuantBvhConfig;
+
+    const KIND: Option<super::IntrsKind> = Some(super::IntrsKind::BvhQuant);
+
+    fn new(config: Self::Config) -> anyhow::Result<Self> {
+        let intrs = match config {
+            QuantBvhConfig::Runtime { eps, precision } => Self {
+                eps,
+                precision,
+                ..Default::default()
+            },
+            QuantBvhConfig::Default => Self::default(),
+        };
+
+        Ok(intrs)
+    }
+
+    fn vars<'a>(
+        &self,
+        scene: &crate::scene::Scene,
+        device: &wgpu::Device
+    ) -> anyhow::Result<(super::IntrsPack<'a>, super::IntrsStats)> {
+        // Build and quantise the BVH if we haven't already
+        let (_, quant) = self.data.get_or_init(|| {
+            let (aabb, build) = bvh::timed(|| bvh::Aabb::from_scene(self.eps, scene, 2));
+
+            let ((wide, quant), quantise) = bvh::timed(|| {
+                let wide = bvh::WideBvhData::new(&aabb, 2);
+                let quant = bvh::QuantBvhData::new(&wide, self.precision);
+
+                (wide, quant)
+            });
+
+            let _ = self.build.set(build + quantise);
+
+            // The stack never holds more than one node per level
+            self.stack.get_or_init(|| wide.depth() + 1);
+
+            (bvh::BvhData::new(&aabb), quant)
+        });
+
+        let quant_nodes = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&quant.words),
+                usage: wgpu::BufferUsages::STORAGE | Self::COPY_USAGES,
+            }
+        );
+
+        // Leaves reference contiguous ranges of prims
+        let prims = super::ordered(scene, &quant.indices);
+
+        let layout = device.create_bind_group_layout(
+            &wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        count: None,
+                        ty: wgpu::BindingType::Buffer {
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                            ty: wgpu::BufferBindingType::Storage {
+                                read_only: true
+                            },
+                        },
+                    },
+                ]
+            }
+        );
+
+        let group = device.create_bind_group(
+            &wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: quant_nodes.as_entire_binding(),
+                    },
+                ],
+            }
+        );
+
+        let pack = super::IntrsPack {
+            vars: vec![
+                super::IntrsVar {
+                    var_name: "quant_nodes",
+                    var_ty: "array<u32>",
+                    buffer: quant_nodes,
+                    buffer_ty: wgpu::BufferBindingType::Storage {
+                        read_only: true,
+                    },
+                },
+            ],
+            group,
+            layout,
+            prims,
+        };
+
+        let stats = super::IntrsStats {
+            name: match self.precision {
+                bvh::QuantPrecision::Bits8 => "Quantised BVH (8-bit)",
+                bvh::QuantPrecision::Bits16 => "Quantised BVH (16-bit)",
+            },
+            size: mem::size_of::<u32>() * quant.words.len(),
+            build: self.build.get().copied(),
+        };
+
+        Ok((pack, stats))
+    }
+
+    fn logic(&self) -> &'static str {
+        // IntrsHandler::logic is always called after IntrsHandler::vars,
+        // so the diverging case is truly unreachable
+        let Some(stack) = self.stack.get().copied() else {
+            unreachable!();
+        };
+
+        let precision = self.precision;
+
+        // In the shader code below, these lines are incomplete
+        let decls = [
+            ("const BITS: u32;", precision.bits() as usize),
+            ("const MASK: u32;", precision.levels() as usize),
+            ("const PER_WORD: u32;", precision.per_word()),
+            ("const NODE_WORDS: u32;", precision.words()),
+        ];
+
+        // Perform the replacements
+        let mut logic = LOGIC.replace(
+            "var<private> quant_stack;",
+            &format!("var<private> quant_stack: array<u32, {stack}>;")
+        );
+
+        for (decl, value) in decls {
+            logic = logic.replace(decl, &format!("{} = {value}u;", &decl[..(decl.len() - 1)]));
+        }
+
+        // We have to return a static string, so we leak it
+        Box::leak(logic.into_boxed_str())
+    }
+
+    fn bvh(&self) -> Option<&bvh::BvhData> {
+        self.data.get().map(|(data, _)| data)
+    }
+}
+
+// The intersection logic
+const LOGIC: &str = "\
+    // NOTE: These values are specified by QuantBvhIntrs::logic
+    const BITS: u32;
+    const MASK: u32;
+    const PER_WORD: u32;
+    const NODE_WORDS: u32;
+
+    fn intrs_leaf(idx: u32, count: u32, ray: Ray, excl: Prim) -> Intrs {
+        var intrs: Intrs = intrs_empty();
+
+        for(var i: u32 = idx; i < (idx + count); i = i + 1u) {
+            // The first primitive is the 'null' prim
+            let prim: Prim = primitives[i + 1u];
+
+            let temp: Intrs = intrs_tri(ray, prim);
+
+            if(temp.t < intrs.t) {
+                intrs = temp;
+            }
+        }
+
+        return intrs;
+    }
+
+    // Unpacks the k-th quantised coordinate of a node
+    fn coord(base: u32, k: u32) -> f32 {
+        let word = quant_nodes[base + 8u + k / PER_WORD];
+
+        return f32((word >> ((k % PER_WORD) * BITS)) & MASK);
+    }
+
+    // Scales are stored as biased exponents, so they decode exactly
+    fn scale(exponents: u32, axis: u32) -> f32 {
+        return bitcast<f32>(((exponents >> (axis * 8u)) & 0xFFu) << 23u);
+    }
+
+    // NOTE: The type is specified by QuantBvhIntrs::logic
+    var<private> quant_stack;
+
+    fn intrs(r: Ray, excl: Prim) -> Intrs {
+        var intrs = intrs_empty();
+
+        var stack_idx = 1u;
+        quant_stack[0] = 0u;
+
+        while(stack_idx > 0u) {
+            stack_idx = stack_idx - 1u;
+
+            let base = quant_stack[stack_idx] * NODE_WORDS;
+
+            count_node();
+
+            let origin = vec3<f32>(
+                bitcast<f32>(quant_nodes[base]),
+                bitcast<f32>(quant_nodes[base + 1u]),
+                bitcast<f32>(quant_nodes[base + 2u]),
+            );
+
+            let exponents = quant_nodes[base + 3u];
+            let scales = vec3<f32>(
+                scale(exponents, 0u),
+                scale(exponents, 1u),
+                scale(exponents, 2u),
+            );
+
+            for(var slot: u32 = 0u; slot < 2u; slot = slot + 1u) {
+                let idx = quant_nodes[base + 4u + slot * 2u];
+                let count = quant_nodes[base + 5u + slot * 2u];
+
+                // Empty slots only appear after the occupied ones
+                if(idx == 0u && count == 0u) { break; }
+
+                let k = slot * 6u;
+
+                let lo = origin + vec3<f32>(
+                    coord(base, k),
+                    coord(base, k + 1u),
+                    coord(base, k + 2u),
+                ) * scales;
+
+                let hi = origin + vec3<f32>(
+                    coord(base, k + 3u),
+                    coord(base, k + 4u),
+                    coord(base, k + 5u),
+                ) * scales;
+
+                if(!collides(lo, hi, r)) { continue; }
+
+                if(count > 0u) {
+                    let temp = intrs_leaf(idx, count, r, excl);
+
+                    if(temp.t < intrs.t) {
+                        intrs = temp;
+                    }
+                } else {
+                    quant_stack[stack_idx] = idx;
+
+                    stack_idx = stack_idx + 1u;
+                }
+            }
+        }
+
+        return intrs;
+    }\
+";
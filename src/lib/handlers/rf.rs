@@ -13,25 +13,110 @@ struct RfAabbUniform {
     tag: u32,
 }
 
+#[derive(Clone)]
+#[derive(Default)]
 pub enum RfBvhConfig {
     Eps(f32),
+    Runtime { eps: f32, leaf_size: usize, },
+    #[default]
     Default,
 }
 
-impl Default for RfBvhConfig {
-    fn default() -> Self { Self::Default }
+// The reduced-footprint layout used on the GPU
+// Narrow nodes pack child and prim indices into 16 bits,
+// the wide layout is used when a scene exceeds those limits
+#[derive(Clone, Copy)]
+#[derive(PartialEq, Eq)]
+#[derive(Debug)]
+enum RfFormat {
+    Narrow,
+    Wide,
+}
+
+impl RfFormat {
+    // The number of prims referenced by a single leaf record
+    const fn slots(self) -> usize {
+        match self {
+            RfFormat::Narrow => 8,
+            RfFormat::Wide => 4,
+        }
+    }
+
+    // The number of records following a leaf with `count` prims
+    const fn records(self, count: usize) -> usize {
+        match self {
+            RfFormat::Narrow => 1,
+            RfFormat::Wide => count.div_ceil(self.slots()),
+        }
+    }
+
+    // Narrow internal nodes store `fst` in the upper half of the tag,
+    // so its top bit (the leaf flag) has to remain clear
+    const fn max_nodes(self) -> usize {
+        match self {
+            RfFormat::Narrow => 1 << 15,
+            RfFormat::Wide => 1 << 31,
+        }
+    }
+
+    // Index 0 is reserved for empty slots
+    const fn max_prims(self) -> usize {
+        match self {
+            RfFormat::Narrow => u16::MAX as usize,
+            RfFormat::Wide => u32::MAX as usize,
+        }
+    }
+
+    // Pick the narrowest layout that can hold the tree
+    fn select(data: &bvh::BvhData, prims: usize) -> anyhow::Result<Self> {
+        let leaves = data.uniforms
+            .iter()
+            .filter(|uniform| uniform.fst == 0 && uniform.snd == 0);
+
+        let leaf_size = leaves.clone()
+            .map(|uniform| uniform.item_count as usize)
+            .max()
+            .unwrap_or(0);
+
+        let fits = |format: RfFormat| {
+            let nodes = data.uniforms.len() + leaves.clone()
+                .map(|uniform| format.records(uniform.item_count as usize))
+                .sum::<usize>();
+
+            nodes <= format.max_nodes() &&
+            prims < format.max_prims() &&
+            (format == RfFormat::Wide || leaf_size <= format.slots())
+        };
+
+        match [RfFormat::Narrow, RfFormat::Wide].into_iter().find(|&format| fits(format)) {
+            Some(format) => Ok(format),
+            None => anyhow::bail!("\
+                Scene exceeds the limits of RfBvhIntrs \
+                ({} nodes, {} prims)", data.uniforms.len(), prims),
+        }
+    }
 }
 
 pub struct RfBvhIntrs {
     eps: f32,
-    nodes: unsync::OnceCell<usize>,
+    leaf_size: usize,
+    data: unsync::OnceCell<bvh::BvhData>,
+    // Construction time (MS)
+    build: unsync::OnceCell<f32>,
+    format: unsync::OnceCell<RfFormat>,
+    // Capacity of the traversal stack
+    stack: unsync::OnceCell<usize>,
 }
 
 impl Default for RfBvhIntrs {
     fn default() -> Self {
         Self { 
             eps: 0.02, 
-            nodes: unsync::OnceCell::new(),
+            leaf_size: 4,
+            data: unsync::OnceCell::new(),
+            build: unsync::OnceCell::new(),
+            format: unsync::OnceCell::new(),
+            stack: unsync::OnceCell::new(),
         }
     }
 }
@@ -47,115 +132,56 @@ impl RfBvhIntrs {
 impl super::IntrsHandler for RfBvhIntrs {
     type Config = RfBvhConfig;
 
+    const KIND: Option<super::IntrsKind> = Some(super::IntrsKind::BvhRf);
+
     fn new(config: Self::Config) -> anyhow::Result<Self> 
         where Self: Sized {
 
         Ok(match config {
             RfBvhConfig::Eps(eps) => Self { eps, ..Default::default() },
+            RfBvhConfig::Runtime { eps, leaf_size } => {
+                if leaf_size == 0 {
+                    anyhow::bail!("RfBvhIntrs leaf size must be at least 1");
+                }
+
+                Self { eps, leaf_size, ..Default::default() }
+            },
             RfBvhConfig::Default => Self::default(),
         })
     }
 
     fn vars<'a>(
         &self,
-        scene: &mut crate::scene::Scene, 
+        scene: &crate::scene::Scene, 
         device: &wgpu::Device,
-    ) -> (super::IntrsPack<'a>, super::IntrsStats) {
-        let aabb = bvh::Aabb::from_scene(self.eps, scene, 4);
-
-        let data = bvh::BvhData::new(&aabb);
-
-        let bvh::BvhData {
-            uniforms,
-            indices, ..
-        } = data;
-
-        // Set the node count if we haven't already
-        self.nodes.get_or_init(|| uniforms.len());
+    ) -> anyhow::Result<(super::IntrsPack<'a>, super::IntrsStats)> {
+        // The full-width tree is kept around for `IntrsHandler::bvh`
+        let data = self.data.get_or_init(|| {
+            let (data, build) = bvh::timed(|| {
+                bvh::BvhData::new(&bvh::Aabb::from_scene(self.eps, scene, self.leaf_size))
+            });
 
-        let mut uniforms_rf = Vec::with_capacity(uniforms.len());
+            let _ = self.build.set(build);
 
-        for uniform in uniforms.iter() {
-            let bvh::AabbUniform {
-                fst,
-                snd,
-                item_idx,
-                item_count,
-                bounds: bvh::Bounds { min, max, .. },
-            } = *uniform;
+            data
+        });
 
-            fn pack(a: f32, b: f32) -> u32 {
-                let a = half::f16::from_f32(a);
-                let b = half::f16::from_f32(b);
+        let prims = match scene {
+            crate::scene::Scene::Active { prims, .. } => prims.len(),
+            crate::scene::Scene::Unloaded => 0,
+        };
 
-                bytemuck::cast_slice::<half::f16, u32>(&[a, b])[0]
-            }
+        let format = RfFormat::select(data, prims)?;
 
-            // If it is a leaf
-            if fst == 0 && snd == 0 {
-                uniforms_rf.push(RfAabbUniform {
-                    bounds: [
-                        pack(min[0], max[0]),
-                        pack(min[1], max[1]),
-                        pack(min[2], max[2]),
-                    ],
-                    tag: 1 << 31,
-                });
-
-                let item_idx = item_idx as usize;
-                let item_count = item_count as usize;
-
-                let mut items = indices[item_idx..(item_idx + item_count)]
-                    .iter()
-                    .map(|&idx| idx as u16)
-                    .collect::<Vec<_>>();
-
-                items.extend(std::iter::repeat(0).take(8 - items.len()));
-
-                uniforms_rf.push({
-                    bytemuck::cast_slice::<u16, RfAabbUniform>(&items)[0]
-                });
-            } else { // Internal node
-                uniforms_rf.push(RfAabbUniform {
-                    bounds: [
-                        pack(min[0], max[0]),
-                        pack(min[1], max[1]),
-                        pack(min[2], max[2]),
-                    ],
-                    tag: ((fst) << 16) | ((snd) & 0xFFFF),
-                });
-            };
+        // The format is fixed once the shader has been generated
+        if *self.format.get_or_init(|| format) != format {
+            anyhow::bail!("RfBvhIntrs can't change format after initialization");
         }
 
-        for RfAabbUniform { tag, .. } in uniforms_rf.iter_mut() {
-            if (*tag >> 31) & 1 == 0 {
-                let [
-                    mut fst, 
-                    mut snd
-                ] = bytemuck::cast::<u32, [u16; 2]>(*tag);
-
-                let mut idx = 0;
-                let mut offset = 0;
-                while idx < fst as usize {
-                    if uniforms[idx].item_count > 0 {
-                        offset += 1;
-                    }
-    
-                    idx += 1;
-                } fst += offset;
+        // Size the stack if we haven't already
+        self.stack.get_or_init(|| data.depth());
 
-                idx = 0; offset = 0;
-                while idx < snd as usize {
-                    if uniforms[idx].item_count > 0 {
-                        offset += 1;
-                    }
-    
-                    idx += 1;
-                } snd += offset;
-
-                *tag = bytemuck::cast::<[u16; 2], u32>([fst, snd]);
-            }
-        }
+        let uniforms_rf = build(data, format);
 
         let aabb_uniforms = device.create_buffer_init(
             &wgpu::util::BufferInitDescriptor {
@@ -211,14 +237,19 @@ impl super::IntrsHandler for RfBvhIntrs {
             ],
             group,
             layout,
+            prims: None,
         };
 
         let stats = super::IntrsStats {
-            name: "RF-BVH",
+            name: match format {
+                RfFormat::Narrow => "RF-BVH",
+                RfFormat::Wide => "RF-BVH (32-bit)",
+            },
             size: mem::size_of::<RfAabbUniform>() * uniforms_rf.len(),
+            build: self.build.get().copied(),
         };
 
-        (pack, stats)
+        Ok((pack, stats))
     }
 
     fn logic(&self) -> &'static str {
@@ -228,19 +259,143 @@ impl super::IntrsHandler for RfBvhIntrs {
 
         // IntrsHandler::logic is always called after IntrsHandler::vars,
         // so the diverging case is truly unreachable
-        let Some(nodes) = self.nodes.get().copied() else { 
+        let (Some(stack), Some(format)) = (
+            self.stack.get().copied(),
+            self.format.get().copied(),
+        ) else { 
             unreachable!();
         };
 
+        let mut logic = String::from(LOGIC);
+        logic.push_str(match format {
+            RfFormat::Narrow => LOGIC_NARROW,
+            RfFormat::Wide => LOGIC_WIDE,
+        });
+
         // Perform the replacement
-        let mut logic = String::from(LOGIC); logic.insert_str(
-            LOGIC.find(DECL).unwrap() + DECL.len() - 1, 
-            format!(": array<u32, {nodes}>",).as_str()
+        logic.insert_str(
+            logic.find(DECL).unwrap() + DECL.len() - 1, 
+            format!(": array<u32, {stack}>",).as_str()
         );
         
         // We have to return a static string, so we leak it
         Box::leak(logic.into_boxed_str())
     }
+
+    fn bvh(&self) -> Option<&bvh::BvhData> {
+        self.data.get()
+    }
+}
+
+// Rounds outwards, so the packed bounds always contain the originals
+fn pack_bounds(min: f32, max: f32) -> u32 {
+    use half::f16;
+
+    // The adjacent representable values (towards -inf & +inf)
+    fn next_down(h: f16) -> f16 {
+        match h.to_bits() {
+            0x0000 | 0x8000 => f16::from_bits(0x8001),
+            bits if h.is_sign_negative() => f16::from_bits(bits + 1),
+            bits => f16::from_bits(bits - 1),
+        }
+    }
+
+    fn next_up(h: f16) -> f16 {
+        -next_down(-h)
+    }
+
+    let mut a = f16::from_f32(min);
+    if a.to_f32() > min { a = next_down(a); }
+
+    let mut b = f16::from_f32(max);
+    if b.to_f32() < max { b = next_up(b); }
+
+    bytemuck::cast_slice::<f16, u32>(&[a, b])[0]
+}
+
+// Lowers the tree into the given format.
+// Each leaf node is followed by records holding its prim indices
+fn build(data: &bvh::BvhData, format: RfFormat) -> Vec<RfAabbUniform> {
+    let bvh::BvhData { uniforms, indices, .. } = data;
+
+    let leaf = |uniform: &bvh::AabbUniform| uniform.fst == 0 && uniform.snd == 0;
+
+    // Where each node of the tree lands once the records are inserted
+    let mut offsets = Vec::with_capacity(uniforms.len());
+    let mut offset = 0;
+    for uniform in uniforms.iter() {
+        offsets.push(offset as u32);
+
+        offset += 1;
+        if leaf(uniform) {
+            offset += format.records(uniform.item_count as usize);
+        }
+    }
+
+    let mut uniforms_rf = Vec::with_capacity(offset);
+
+    for uniform in uniforms.iter() {
+        let bvh::AabbUniform {
+            fst,
+            snd,
+            item_idx,
+            item_count,
+            bounds: bvh::Bounds { min, max, .. },
+        } = *uniform;
+
+        let bounds = [
+            pack_bounds(min[0], max[0]),
+            pack_bounds(min[1], max[1]),
+            pack_bounds(min[2], max[2]),
+        ];
+
+        let item_idx = item_idx as usize;
+        let item_count = item_count as usize;
+
+        // Offset past the 'null' prim, so 0 marks an empty slot
+        let items = indices[item_idx..(item_idx + item_count)]
+            .iter()
+            .map(|&idx| idx + 1);
+
+        match (format, leaf(uniform)) {
+            (RfFormat::Narrow, true) => {
+                uniforms_rf.push(RfAabbUniform { bounds, tag: 1 << 31 });
+
+                let mut record = [0u16; 8];
+                for (slot, idx) in record.iter_mut().zip(items) {
+                    *slot = idx as u16;
+                }
+
+                uniforms_rf.push(bytemuck::cast(record));
+            },
+            (RfFormat::Narrow, false) => {
+                let fst = offsets[fst as usize];
+                let snd = offsets[snd as usize];
+
+                uniforms_rf.push(RfAabbUniform { bounds, tag: (fst << 16) | snd });
+            },
+            (RfFormat::Wide, true) => {
+                let records = format.records(item_count);
+
+                uniforms_rf.push(RfAabbUniform { bounds, tag: (1 << 31) | records as u32 });
+
+                let items = items.collect::<Vec<_>>();
+                for chunk in items.chunks(format.slots()) {
+                    let mut record = [0u32; 4];
+                    record[..chunk.len()].copy_from_slice(chunk);
+
+                    uniforms_rf.push(bytemuck::cast(record));
+                }
+            },
+            // The first child always directly follows its parent,
+            // so only the second has to be stored
+            (RfFormat::Wide, false) => {
+                uniforms_rf.push(RfAabbUniform { bounds, tag: offsets[snd as usize] });
+            },
+        }
+    }
+
+    uniforms_rf
 }
 
 #[allow(dead_code)]
@@ -290,8 +445,8 @@ fn debug_aabb(data: &bvh::BvhData) {
 }
 
 #[allow(dead_code)]
-fn debug_rf_aabb(bbs: &[RfAabbUniform]) {
-    fn debug_rf_aabb_inner(bbs: &[RfAabbUniform], curr: usize, indent: usize) {
+fn debug_rf_aabb(bbs: &[RfAabbUniform], format: RfFormat) {
+    fn debug_rf_aabb_inner(bbs: &[RfAabbUniform], format: RfFormat, curr: usize, indent: usize) {
         let RfAabbUniform {
             bounds,
             tag, ..
@@ -302,7 +457,11 @@ fn debug_rf_aabb(bbs: &[RfAabbUniform]) {
         let [z_min, z_max] = bytemuck::cast::<u32, [half::f16; 2]>(bounds[2]);
     
         if (tag >> 31) & 1 == 0 {
-            let [fst, snd] = bytemuck::cast::<u32, [u16; 2]>(tag);
+            // Wide nodes are followed by their first child
+            let [fst, snd] = match format {
+                RfFormat::Narrow => bytemuck::cast::<u32, [u16; 2]>(tag).map(usize::from),
+                RfFormat::Wide => [curr + 1, tag as usize],
+            };
     
             println!(
                 "{} Node [{:.3}, {:.3}, {:.3}] [{:.3}, {:.3}, {:.3}]", 
@@ -311,20 +470,25 @@ fn debug_rf_aabb(bbs: &[RfAabbUniform]) {
                 x_max, y_max, z_max,
             );
     
-            debug_rf_aabb_inner(bbs, fst as usize, indent + 1);
-            debug_rf_aabb_inner(bbs, snd as usize, indent + 1);
+            debug_rf_aabb_inner(bbs, format, fst, indent + 1);
+            debug_rf_aabb_inner(bbs, format, snd, indent + 1);
         } else {
-            let RfAabbUniform { 
-                bounds, 
-                tag, ..
-            } = bbs[curr + 1];
-    
+            let records = match format {
+                RfFormat::Narrow => 1,
+                RfFormat::Wide => (tag & 0x7FFFFFFF) as usize,
+            };
+
             let mut indices = vec![];
-            indices.extend_from_slice(&bytemuck::cast::<u32, [u16; 2]>(bounds[0]));
-            indices.extend_from_slice(&bytemuck::cast::<u32, [u16; 2]>(bounds[1]));
-            indices.extend_from_slice(&bytemuck::cast::<u32, [u16; 2]>(bounds[2]));
-            indices.extend_from_slice(&bytemuck::cast::<u32, [u16; 2]>(tag));
+            for RfAabbUniform { bounds, tag, .. } in &bbs[(curr + 1)..(curr + 1 + records)] {
+                for slot in bounds.iter().chain([tag]) {
+                    match format {
+                        RfFormat::Narrow => indices.extend(bytemuck::cast::<u32, [u16; 2]>(*slot).map(u32::from)),
+                        RfFormat::Wide => indices.push(*slot),
+                    }
+                }
+            }
     
+            // Indices are offset by the 'null' prim, so empty slots are 0
             let indices = indices
                 .into_iter()
                 .filter(|&x| x != 0)
@@ -340,7 +504,7 @@ fn debug_rf_aabb(bbs: &[RfAabbUniform]) {
         }
     }
 
-    debug_rf_aabb_inner(bbs, 0, 0);
+    debug_rf_aabb_inner(bbs, format, 0, 0);
 }
 
 const LOGIC: &str = "\
@@ -354,76 +518,12 @@ const LOGIC: &str = "\
         tag: u32
     }
 
-    fn intrs_tri(r: Ray, s: Prim) -> Intrs {
-        let e1: vec3<f32> = vertices[s.b].pos - vertices[s.a].pos;
-        let e2: vec3<f32> = vertices[s.c].pos - vertices[s.a].pos;
-
-        let p: vec3<f32> = cross(r.dir, e2);
-        let t: vec3<f32> = r.origin - vertices[s.a].pos;
-        let q: vec3<f32> = cross(t, e1);
-
-        let det = dot(e1, p);
-
-        var u: f32 = 0.0;
-        var v: f32 = 0.0;
-        if(det > config.eps) {
-            u = dot(t, p);
-            if(u < 0.0 || u > det) { return intrs_empty(); }
-
-            v = dot(r.dir, q);
-            if(v < 0.0 || u + v > det) { return intrs_empty(); }
-        } else if(det < -1.0 * config.eps) {
-            u = dot(t, p);
-            if(u > 0.0 || u < det) { return intrs_empty(); }
-
-            v = dot(r.dir, q);
-            if(v > 0.0 || u + v < det) { return intrs_empty(); }
-        } else {
-            return intrs_empty();
-        }
-
-        let w: f32 = dot(e2, q) / det;
-        
-        if(w > config.t_max || w < config.t_min) {
-            return intrs_empty();
-        } else {
-            return Intrs(s, w);
-        }
-    }
-
-    const INF_POS: f32 = 0x1.p+38f;
-    const INF_NEG: f32 = -1.0 * INF_POS;
-
-    // Wobble for the intersection test below
-    const EPS: f32 = 0.000002;
-
-    fn collides(bb: Aabb, ray: Ray) -> bool {
+    fn unpack_bounds(bb: Aabb) -> Bounds {
         let a: vec2<f32> = unpack2x16float(bb.bounds.x);
         let b: vec2<f32> = unpack2x16float(bb.bounds.y);
         let c: vec2<f32> = unpack2x16float(bb.bounds.z);
 
-        let minima: vec3<f32> = vec3<f32>(a.x, b.x, c.x);
-        let maxima: vec3<f32> = vec3<f32>(a.y, b.y, c.y);
-        
-        var t0 = (minima.x - EPS - ray.origin.x) / ray.dir.x;
-        var t1 = (maxima.x + EPS - ray.origin.x) / ray.dir.x;
-
-        var t_min = min(t0, t1);
-        var t_max = max(t0, t1);
-        
-        t0 = (minima.y - EPS - ray.origin.y) / ray.dir.y;
-        t1 = (maxima.y + EPS - ray.origin.y) / ray.dir.y;
-
-        t_min = max(t_min, min(min(t0, t1), INF_NEG));
-        t_max = min(t_max, max(max(t0, t1), INF_POS));
-        
-        t0 = (minima.z - EPS - ray.origin.z) / ray.dir.z;
-        t1 = (maxima.z + EPS - ray.origin.z) / ray.dir.z;
-
-        t_min = max(t_min, min(min(t0, t1), INF_NEG));
-        t_max = min(t_max, max(max(t0, t1), INF_POS));
-
-        return (t_min < t_max);
+        return Bounds(vec3<f32>(a.x, b.x, c.x), vec3<f32>(a.y, b.y, c.y));
     }
 
     fn intrs_bvh_helper(idx: u32, ray: Ray, curr: Intrs) -> Intrs {
@@ -440,28 +540,7 @@ const LOGIC: &str = "\
         return curr;
     }
 
-    fn intrs_bvh(bb: Aabb, ray: Ray, excl: Prim) -> Intrs {
-        var intrs: Intrs = intrs_empty();
-
-        var t = bb.bounds.x & 0xFFFF;
-        intrs = intrs_bvh_helper(t, ray, intrs);
-        t = (bb.bounds.x >> 16) & 0xFFFF;
-        intrs = intrs_bvh_helper(t, ray, intrs);
-
-        t = bb.bounds.y & 0xFFFF;
-        intrs = intrs_bvh_helper(t, ray, intrs);
-        t = (bb.bounds.y >> 16) & 0xFFFF;
-        intrs = intrs_bvh_helper(t, ray, intrs);
-
-        t = bb.bounds.z & 0xFFFF;
-        intrs = intrs_bvh_helper(t, ray, intrs);
-        t = (bb.bounds.z >> 16) & 0xFFFF;
-        intrs = intrs_bvh_helper(t, ray, intrs);
-
-        return intrs;
-    }
-
-    // NOTE: The type is specified by BvhIntrs::logic
+    // NOTE: The type is specified by RfBvhIntrs::logic
     var<private> aabb_stack;
 
     fn pop(idx: ptr<function, u32>, empty: ptr<function, bool>) -> u32 {
@@ -479,6 +558,21 @@ const LOGIC: &str = "\
 
         *idx = *idx + 1u;
     }
+";
+
+// Traversal of the 16-bit layout
+const LOGIC_NARROW: &str = "\
+    fn intrs_bvh(bb: Aabb, ray: Ray, excl: Prim) -> Intrs {
+        var intrs: Intrs = intrs_empty();
+
+        let slots = vec4<u32>(bb.bounds, bb.tag);
+        for(var i = 0; i < 4; i = i + 1) {
+            intrs = intrs_bvh_helper(slots[i] & 0xFFFF, ray, intrs);
+            intrs = intrs_bvh_helper((slots[i] >> 16) & 0xFFFF, ray, intrs);
+        }
+
+        return intrs;
+    }
 
     fn intrs(r: Ray, excl: Prim) -> Intrs {
         var stack_idx = 0u;
@@ -492,7 +586,11 @@ const LOGIC: &str = "\
             let bb_idx = pop(&stack_idx, &stack_empty);
             let bb = aabb_uniforms[bb_idx];
 
-            if(collides(bb, r)) {
+            count_node();
+
+            let bounds = unpack_bounds(bb);
+
+            if(collides(bounds.min, bounds.max, r)) {
                 if((bb.tag >> 31 & 1) == 1u) {
                     let temp = intrs_bvh(aabb_uniforms[bb_idx + 1u], r, excl);
 
@@ -513,4 +611,58 @@ const LOGIC: &str = "\
 
         return intrs;
     }\
-";
\ No newline at end of file
+";
+
+// Traversal of the 32-bit layout
+const LOGIC_WIDE: &str = "\
+    // Each leaf is followed by `count` records of four prim indices
+    fn intrs_bvh(idx: u32, count: u32, ray: Ray, excl: Prim) -> Intrs {
+        var intrs: Intrs = intrs_empty();
+
+        for(var i = idx; i < idx + count; i = i + 1u) {
+            let bb = aabb_uniforms[i];
+
+            let slots = vec4<u32>(bb.bounds, bb.tag);
+            for(var j = 0; j < 4; j = j + 1) {
+                intrs = intrs_bvh_helper(slots[j], ray, intrs);
+            }
+        }
+
+        return intrs;
+    }
+
+    fn intrs(r: Ray, excl: Prim) -> Intrs {
+        var stack_idx = 0u;
+        var stack_empty = false;
+
+        push(&stack_idx, 0u);
+
+        var intrs = intrs_empty();
+
+        while(!stack_empty) {
+            let bb_idx = pop(&stack_idx, &stack_empty);
+            let bb = aabb_uniforms[bb_idx];
+
+            count_node();
+
+            let bounds = unpack_bounds(bb);
+
+            if(collides(bounds.min, bounds.max, r)) {
+                if((bb.tag >> 31 & 1) == 1u) {
+                    let temp = intrs_bvh(bb_idx + 1u, bb.tag & 0x7FFFFFFF, r, excl);
+
+                    if(temp.t < intrs.t) {
+                        intrs = temp;
+                    }
+                } else {
+                    push(&stack_idx, bb_idx + 1u);
+                    push(&stack_idx, bb.tag);
+
+                    stack_empty = false;
+                }
+            }
+        }
+
+        return intrs;
+    }\
+";
@@ -0,0 +1,286 @@
+use std::mem;
+
+// Needed for `device.create_buffer_init`
+use wgpu::util::DeviceExt as _;
+
+use once_cell::unsync;
+
+use crate::bvh;
+
+// Each node links to the node visited after a hit and after a miss,
+// so the tree can be traversed without a stack
+#[repr(C)]
+#[derive(Clone, Copy)]
+#[derive(bytemuck::Pod, bytemuck::Zeroable)]
+struct ThreadedAabbUniform {
+    hit: u32,
+    miss: u32,
+    item_idx: u32,
+    item_count: u32,
+    bounds: bvh::Bounds,
+}
+
+pub struct ThreadedBvhIntrs {
+    pub eps: f32,
+    pub builder: bvh::BvhBuilder,
+    data: unsync::OnceCell<bvh::BvhData>,
+    // Construction time (MS), empty if the BVH was precomputed
+    build: unsync::OnceCell<f32>,
+}
+
+impl Default for ThreadedBvhIntrs {
+    fn default() -> Self {
+        Self {
+            eps: 0.02,
+            builder: bvh::BvhBuilder::Object,
+            data: unsync::OnceCell::new(),
+            build: unsync::OnceCell::new(),
+        }
+    }
+}
+
+impl ThreadedBvhIntrs {
+    // When reloading scenes, we may want to write into our previous buffers
+    const COPY_USAGES: wgpu::BufferUsages = {
+        wgpu::BufferUsages::COPY_SRC //
+            .union(wgpu::BufferUsages::COPY_DST) //
+    };
+}
+
+// Nodes are flattened depth-first, so the first child always follows
+// its parent and a subtree ends where the next sibling begins.
+// Links equal to the node count end traversal
+fn thread(data: &bvh::BvhData) -> Vec<ThreadedAabbUniform> {
+    fn thread_inner(
+        data: &bvh::BvhData,
+        threaded: &mut [ThreadedAabbUniform],
+        curr: usize,
+        miss: u32,
+    ) {
+        let bvh::AabbUniform { fst, snd, .. } = data.uniforms[curr];
+
+        threaded[curr].miss = miss;
+
+        if fst == 0 && snd == 0 {
+            threaded[curr].hit = miss;
+        } else {
+            threaded[curr].hit = fst;
+
+            thread_inner(data, threaded, fst as usize, snd);
+            thread_inner(data, threaded, snd as usize, miss);
+        }
+    }
+
+    let mut threaded = data.uniforms
+        .iter()
+        .map(|uniform| ThreadedAabbUniform {
+            hit: 0,
+            miss: 0,
+            item_idx: uniform.item_idx,
+            item_count: uniform.item_count,
+            bounds: uniform.bounds,
+        })
+        .collect::<Vec<_>>();
+
+    if !threaded.is_empty() {
+        thread_inner(data, &mut threaded, 0, data.uniforms.len() as u32);
+    }
+
+    threaded
+}
+
+// Both handlers are built from the same `BvhData`,
+// so precomputed BVH files work with either of them
+impl super::IntrsHandler for ThreadedBvhIntrs {
+    type Config = super::BvhConfig;
+
+    const KIND: Option<super::IntrsKind> = Some(super::IntrsKind::BvhThreaded);
+
+    fn new(config: Self::Config) -> anyhow::Result<Self> {
+        let intrs = match config {
+            super::BvhConfig::Bytes(bytes) => {
+                let data = serde_json::from_slice::<bvh::BvhData>(&bytes)?;
+
+                Self {
+                    data: unsync::OnceCell::with_value(data),
+                    ..Default::default()
+                }
+            },
+            super::BvhConfig::Runtime { eps } => Self {
+                eps,
+                ..Default::default()
+            },
+            super::BvhConfig::Builder { eps, builder } => Self {
+                eps,
+                builder,
+                ..Default::default()
+            },
+            super::BvhConfig::Default => Self::default(),
+        };
+
+        Ok(intrs)
+    }
+
+    fn vars<'a>(
+        &self,
+        scene: &crate::scene::Scene,
+        device: &wgpu::Device
+    ) -> anyhow::Result<(super::IntrsPack<'a>, super::IntrsStats)> {
+        // Build the BVH if we haven't already
+        let data = self.data.get_or_init(|| {
+            let (data, build) = bvh::timed(|| {
+                bvh::BvhData::new(&self.builder.build(self.eps, scene, 2))
+            });
+
+            let _ = self.build.set(build);
+
+            data
+        });
+
+        let uniforms = thread(data);
+
+        let aabb_uniforms = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&uniforms),
+                usage: wgpu::BufferUsages::STORAGE | Self::COPY_USAGES,
+            }
+        );
+
+        // Leaves reference contiguous ranges of prims
+        let prims = super::ordered(scene, &data.indices);
+
+        let layout = device.create_bind_group_layout(
+            &wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        count: None,
+                        ty: wgpu::BindingType::Buffer {
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                            ty: wgpu::BufferBindingType::Storage {
+                                read_only: true
+                            },
+                        },
+                    },
+                ]
+            }
+        );
+
+        let group = device.create_bind_group(
+            &wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: aabb_uniforms.as_entire_binding(),
+                    },
+                ],
+            }
+        );
+
+        let pack = super::IntrsPack {
+            vars: vec![
+                super::IntrsVar {
+                    var_name: "aabb_uniforms",
+                    var_ty: "array<Aabb>",
+                    buffer: aabb_uniforms,
+                    buffer_ty: wgpu::BufferBindingType::Storage {
+                        read_only: true,
+                    },
+                },
+            ],
+            group,
+            layout,
+            prims,
+        };
+
+        let stats = super::IntrsStats {
+            name: match self.builder {
+                bvh::BvhBuilder::Object => "Threaded BVH",
+                bvh::BvhBuilder::Sbvh => "Threaded BVH (SBVH)",
+                bvh::BvhBuilder::Lbvh => "Threaded BVH (LBVH)",
+            },
+            size: mem::size_of::<ThreadedAabbUniform>() * uniforms.len(),
+            build: self.build.get().copied(),
+        };
+
+        Ok((pack, stats))
+    }
+
+    fn logic(&self) -> &'static str { LOGIC }
+
+    fn bvh(&self) -> Option<&bvh::BvhData> {
+        self.data.get()
+    }
+
+    fn refit_var(&self) -> Option<&'static str> {
+        Some("aabb_uniforms")
+    }
+}
+
+// The intersection logic
+const LOGIC: &str = "\
+    struct Bounds {
+        min: vec3<f32>,
+        max: vec3<f32>,
+    }
+
+    struct Aabb {
+        hit: u32,
+        miss: u32,
+        item_idx: u32,
+        item_count: u32,
+        bounds: Bounds,
+    }
+
+    fn intrs_bvh(bb: Aabb, ray: Ray, excl: Prim) -> Intrs {
+        var intrs: Intrs = intrs_empty();
+
+        for(var i: u32 = bb.item_idx; i < (bb.item_idx + bb.item_count); i = i + 1u) {
+            // The first primitive is the 'null' prim
+            let prim: Prim = primitives[i + 1u];
+
+            let temp: Intrs = intrs_tri(ray, prim);
+
+            if(temp.t < intrs.t) {
+                intrs = temp;
+            }
+        }
+
+        return intrs;
+    }
+
+    fn intrs(r: Ray, excl: Prim) -> Intrs {
+        let end = arrayLength(&aabb_uniforms);
+
+        var intrs = intrs_empty();
+
+        var idx = 0u;
+        while(idx < end) {
+            let bb = aabb_uniforms[idx];
+
+            count_node();
+
+            if(collides(bb.bounds.min, bb.bounds.max, r)) {
+                if(bb.item_count > 0u) {
+                    let temp = intrs_bvh(bb, r, excl);
+
+                    if(temp.t < intrs.t) {
+                        intrs = temp;
+                    }
+                }
+
+                idx = bb.hit;
+            } else {
+                idx = bb.miss;
+            }
+        }
+
+        return intrs;
+    }\
+";
@@ -0,0 +1,256 @@
+use std::mem;
+
+// Needed for `device.create_buffer_init`
+use wgpu::util::DeviceExt as _;
+
+use once_cell::unsync;
+
+use crate::bvh;
+
+#[derive(Clone)]
+#[derive(Default)]
+pub enum WideBvhConfig {
+    Runtime { eps: f32, width: usize, },
+    #[default]
+    Default,
+}
+
+pub struct WideBvhIntrs {
+    pub eps: f32,
+    // The number of children per node (4 or 8)
+    pub width: usize,
+
+    // The binary tree is kept around for the overlay
+    data: unsync::OnceCell<(bvh::BvhData, bvh::WideBvhData)>,
+    // Construction time (MS), including the collapse
+    build: unsync::OnceCell<f32>,
+    // Capacity of the traversal stack
+    stack: unsync::OnceCell<usize>,
+}
+
+impl Default for WideBvhIntrs {
+    fn default() -> Self {
+        Self {
+            eps: 0.02,
+            width: 4,
+            data: unsync::OnceCell::new(),
+            build: unsync::OnceCell::new(),
+            stack: unsync::OnceCell::new(),
+        }
+    }
+}
+
+impl WideBvhIntrs {
+    // When reloading scenes, we may want to write into our previous buffers
+    const COPY_USAGES: wgpu::BufferUsages = {
+        wgpu::BufferUsages::COPY_SRC //
+            .union(wgpu::BufferUsages::COPY_DST) //
+    };
+}
+
+impl super::IntrsHandler for WideBvhIntrs {
+    type Config = WideBvhConfig;
+
+    const KIND: Option<super::IntrsKind> = Some(super::IntrsKind::BvhWide);
+
+    fn new(config: Self::Config) -> anyhow::Result<Self> {
+        let intrs = match config {
+            WideBvhConfig::Runtime { eps, width } => {
+                if !matches!(width, 4 | 8) {
+                    anyhow::bail!("WideBvhIntrs requires a width of either 4 or 8");
+                }
+
+                Self { eps, width, ..Default::default() }
+            },
+            WideBvhConfig::Default => Self::default(),
+        };
+
+        Ok(intrs)
+    }
+
+    fn vars<'a>(
+        &self,
+        scene: &crate::scene::Scene,
+        device: &wgpu::Device
+    ) -> anyhow::Result<(super::IntrsPack<'a>, super::IntrsStats)> {
+        // Build and collapse the BVH if we haven't already
+        let (_, wide) = self.data.get_or_init(|| {
+            let (aabb, build) = bvh::timed(|| bvh::Aabb::from_scene(self.eps, scene, 2));
+            let (wide, collapse) = bvh::timed(|| bvh::WideBvhData::new(&aabb, self.width));
+
+            let _ = self.build.set(build + collapse);
+
+            (bvh::BvhData::new(&aabb), wide)
+        });
+
+        // Each popped node can push all but one of its children
+        self.stack.get_or_init(|| wide.depth() * (self.width - 1) + 1);
+
+        let wide_nodes = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&wide.children),
+                usage: wgpu::BufferUsages::STORAGE | Self::COPY_USAGES,
+            }
+        );
+
+        // Leaves reference contiguous ranges of prims
+        let prims = super::ordered(scene, &wide.indices);
+
+        let layout = device.create_bind_group_layout(
+            &wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        count: None,
+                        ty: wgpu::BindingType::Buffer {
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                            ty: wgpu::BufferBindingType::Storage {
+                                read_only: true
+                            },
+                        },
+                    },
+                ]
+            }
+        );
+
+        let group = device.create_bind_group(
+            &wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wide_nodes.as_entire_binding(),
+                    },
+                ],
+            }
+        );
+
+        let pack = super::IntrsPack {
+            vars: vec![
+                super::IntrsVar {
+                    var_name: "wide_nodes",
+                    var_ty: "array<WideChild>",
+                    buffer: wide_nodes,
+                    buffer_ty: wgpu::BufferBindingType::Storage {
+                        read_only: true,
+                    },
+                },
+            ],
+            group,
+            layout,
+            prims,
+        };
+
+        let stats = super::IntrsStats {
+            name: if self.width == 8 { "BVH8" } else { "BVH4" },
+            size: mem::size_of::<bvh::WideChild>() * wide.children.len(),
+            build: self.build.get().copied(),
+        };
+
+        Ok((pack, stats))
+    }
+
+    fn logic(&self) -> &'static str {
+        // In the shader code below, these lines are incomplete
+        const DECL_WIDTH: &str = "const WIDTH: u32;";
+        const DECL_STACK: &str = "var<private> wide_stack;";
+
+        // IntrsHandler::logic is always called after IntrsHandler::vars,
+        // so the diverging case is truly unreachable
+        let Some(stack) = self.stack.get().copied() else {
+            unreachable!();
+        };
+
+        let width = self.width;
+
+        // Perform the replacements
+        let logic = LOGIC
+            .replace(DECL_WIDTH, &format!("const WIDTH: u32 = {width}u;"))
+            .replace(DECL_STACK, &format!("var<private> wide_stack: array<u32, {stack}>;"));
+
+        // We have to return a static string, so we leak it
+        Box::leak(logic.into_boxed_str())
+    }
+
+    fn bvh(&self) -> Option<&bvh::BvhData> {
+        self.data.get().map(|(data, _)| data)
+    }
+}
+
+// The intersection logic
+const LOGIC: &str = "\
+    // Children of node `n` occupy `wide_nodes[n * WIDTH..(n + 1) * WIDTH]`
+    struct WideChild {
+        min: vec3<f32>,
+        idx: u32,
+        max: vec3<f32>,
+        count: u32,
+    }
+
+    // NOTE: The value is specified by WideBvhIntrs::logic
+    const WIDTH: u32;
+
+    fn intrs_leaf(child: WideChild, ray: Ray, excl: Prim) -> Intrs {
+        var intrs: Intrs = intrs_empty();
+
+        for(var i: u32 = child.idx; i < (child.idx + child.count); i = i + 1u) {
+            // The first primitive is the 'null' prim
+            let prim: Prim = primitives[i + 1u];
+
+            let temp: Intrs = intrs_tri(ray, prim);
+
+            if(temp.t < intrs.t) {
+                intrs = temp;
+            }
+        }
+
+        return intrs;
+    }
+
+    // NOTE: The type is specified by WideBvhIntrs::logic
+    var<private> wide_stack;
+
+    fn intrs(r: Ray, excl: Prim) -> Intrs {
+        var intrs = intrs_empty();
+
+        var stack_idx = 1u;
+        wide_stack[0] = 0u;
+
+        while(stack_idx > 0u) {
+            stack_idx = stack_idx - 1u;
+
+            let node = wide_stack[stack_idx];
+
+            count_node();
+
+            // Every child box of the node is tested before descending
+            for(var slot: u32 = 0u; slot < WIDTH; slot = slot + 1u) {
+                let child = wide_nodes[node * WIDTH + slot];
+
+                // Empty slots only appear after the occupied ones
+                if(child.idx == 0u && child.count == 0u) { break; }
+
+                if(!collides(child.min, child.max, r)) { continue; }
+
+                if(child.count > 0u) {
+                    let temp = intrs_leaf(child, r, excl);
+
+                    if(temp.t < intrs.t) {
+                        intrs = temp;
+                    }
+                } else {
+                    wide_stack[stack_idx] = child.idx;
+
+                    stack_idx = stack_idx + 1u;
+                }
+            }
+        }
+
+        return intrs;
+    }\
+";
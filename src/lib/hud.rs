@@ -0,0 +1,103 @@
+use winit::dpi;
+
+use crate::{handlers, text, timer};
+
+// Everything the HUD reports, gathered from the state on each frame
+#[derive(Clone, Copy)]
+pub struct HudInfo<'a> {
+    pub timer: &'a timer::PassTimer,
+    pub size: dpi::PhysicalSize<u32>,
+    pub wg: u32,
+    pub stats: handlers::IntrsStats,
+    pub prims: usize,
+}
+
+// Frame statistics drawn in the bottom-left corner of the window
+#[derive(Debug)]
+pub struct Hud {
+    enabled: bool,
+    prev: Option<chrono::DateTime<chrono::Local>>,
+    // The smoothed time between presented frames (MS)
+    frame_ms: Option<f32>,
+}
+
+impl Hud {
+    // Weight given to each new frame
+    const SMOOTHING: f32 = 0.1;
+
+    // Frames further apart than this restart the average (MS)
+    const RESET: f32 = 1000.;
+
+    pub const fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            prev: None,
+            frame_ms: None,
+        }
+    }
+
+    pub fn update(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    // Called once per presented frame, even while hidden
+    pub fn frame(&mut self) {
+        let now = chrono::Local::now();
+
+        if let Some(prev) = self.prev.replace(now) {
+            let elapsed = now - prev;
+            let sample = elapsed.num_microseconds().unwrap_or(i64::MAX) as f32 / 1000.;
+
+            self.frame_ms = Some(match self.frame_ms {
+                Some(frame_ms) if sample < Self::RESET => //
+                    frame_ms + (sample - frame_ms) * Self::SMOOTHING,
+                _ => sample,
+            });
+        }
+    }
+
+    fn lines(&self, info: HudInfo) -> [String; 5] {
+        let HudInfo {
+            timer,
+            size: dpi::PhysicalSize { width, height },
+            wg,
+            stats: handlers::IntrsStats { name, size, .. },
+            prims,
+        } = info;
+
+        let fps = match self.frame_ms {
+            Some(frame_ms) if frame_ms > 0. => format!("FPS {:.1}", 1000. / frame_ms),
+            _ => String::from("FPS --"),
+        };
+
+        // Durations without timestamp queries include queueing, so they are marked
+        let compute = match timer.ms() {
+            Some(ms) if timer.precise() => format!("COMPUTE {ms:.2} MS"),
+            Some(ms) => format!("COMPUTE ~{ms:.2} MS"),
+            None => String::from("COMPUTE -- MS"),
+        };
+
+        [
+            fps,
+            compute,
+            format!("{width}X{height} WG {wg}X{wg}"),
+            format!("{name} {size} BYTES"),
+            format!("PRIMS {prims}"),
+        ]
+    }
+
+    // Queues each line of the HUD, stacked up from the bottom of the window
+    pub fn push(&self, text: &mut text::Text, info: HudInfo, window_size: dpi::PhysicalSize<u32>) {
+        if !self.enabled { return; }
+
+        let lines = self.lines(info);
+
+        let line = text::Text::line_height();
+
+        let top = window_size.height as f32 - 8. - line * lines.len() as f32;
+
+        for (idx, content) in lines.iter().enumerate() {
+            text.push(content, [8., top + line * idx as f32], [1.; 4]);
+        }
+    }
+}
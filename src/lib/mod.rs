@@ -1,4 +1,10 @@
 mod pipelines;
+mod overlay;
+mod refit;
+mod timer;
+mod text;
+mod hud;
+mod capture;
 mod vertex;
 mod state;
 mod shaders;
@@ -8,12 +14,16 @@ pub mod scene;
 pub mod geom;
 pub mod handlers;
 pub mod bvh;
+pub mod session;
 
 #[cfg(target_arch = "wasm32")]
 mod web;
 
+pub use capture::CaptureImage;
+pub use state::{CompareConfig, CompareMode};
+
 #[cfg(target_arch = "wasm32")]
-pub use web::{update_config, update_scene, update_viewport};
+pub use web::{capture, edit_scene, update_compare, update_config, update_handler, update_scene, update_viewport};
 
 use std::sync;
 
@@ -105,11 +115,45 @@ impl Resolution {
     }
 }
 
+// Selects what the compute shader writes to the output texture
+// NOTE: The discriminants must match the DEBUG_* constants in `compute.wgsl`
+#[repr(u32)]
+#[derive(Clone, Copy)]
+#[derive(PartialEq, Eq)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(bytemuck::NoUninit)]
+#[derive(Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DebugMode {
+    Shaded = 0,
+    Normals = 1,
+    Depth = 2,
+    Prim = 3,
+    Material = 4,
+    Barycentrics = 5,
+    Heatmap = 6,
+}
+
+impl DebugMode {
+    // Cycles through each mode in order
+    pub const fn next(self) -> Self {
+        match self {
+            DebugMode::Shaded => DebugMode::Normals,
+            DebugMode::Normals => DebugMode::Depth,
+            DebugMode::Depth => DebugMode::Prim,
+            DebugMode::Prim => DebugMode::Material,
+            DebugMode::Material => DebugMode::Barycentrics,
+            DebugMode::Barycentrics => DebugMode::Heatmap,
+            DebugMode::Heatmap => DebugMode::Shaded,
+        }
+    }
+}
+
 // These config options will be passed to the compute shader
 #[repr(C)]
 #[derive(Clone, Copy)]
-#[derive(serde::Deserialize)]
-#[derive(bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(bytemuck::NoUninit)]
 #[derive(Debug)]
 #[serde(default)]
 pub struct ComputeConfig {
@@ -119,6 +163,7 @@ pub struct ComputeConfig {
     pub bounces: u32,
     pub eps: f32,
     pub ambience: f32,
+    pub debug: DebugMode,
 }
 
 impl ComputeConfig {
@@ -130,6 +175,7 @@ impl ComputeConfig {
             bounces: 4,
             eps: 0.0000001,
             ambience: 0.1,
+            debug: DebugMode::Shaded,
         }
     }
 }
@@ -138,6 +184,35 @@ impl Default for ComputeConfig {
     fn default() -> Self { Self::new() }
 }
 
+// Controls the wireframe overlay of BVH node bounds
+#[derive(Clone, Copy)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Debug)]
+#[serde(default)]
+pub struct OverlayConfig {
+    pub enabled: bool,
+    // Only nodes within this (inclusive) range of depths are drawn
+    pub depth_min: u32,
+    pub depth_max: u32,
+    // Only draw leaf nodes
+    pub leaves: bool,
+}
+
+impl OverlayConfig {
+    const fn new() -> Self {
+        Self {
+            enabled: false,
+            depth_min: 0,
+            depth_max: u32::MAX,
+            leaves: false,
+        }
+    }
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self { Self::new() }
+}
+
 // Config declaration
 #[derive(Clone, Copy)]
 #[derive(serde::Deserialize)]
@@ -145,8 +220,11 @@ impl Default for ComputeConfig {
 #[serde(default)]
 pub struct Config {
     pub compute: ComputeConfig,
+    pub overlay: OverlayConfig,
     pub resolution: Resolution,
     pub fps: u32,
+    // Draws frame statistics over the image
+    pub hud: bool,
 }
 
 impl Default for Config {
@@ -159,8 +237,10 @@ impl Config {
     const fn new() -> Self {
         Self {
             compute: ComputeConfig::new(),
+            overlay: OverlayConfig::new(),
             resolution: Resolution::new(),
             fps: 60,
+            hud: false,
         }
     }
 }
@@ -169,15 +249,167 @@ impl Config {
 pub async fn run_native<H, S>(
     mut config: Config, 
     mut config_handler: H::Config,
-    mut scene: scene::Scene
+    mut config_scheduler: S::Config,
+    mut scene: scene::Scene,
+    session: Option<session::SessionMode>,
 ) -> Result<(), Failed> 
     where H: handlers::IntrsHandler, S: timing::Scheduler {
 
     unsafe {
-        run_internal::<H, S>(&mut config, config_handler, &mut scene).await
+        run_internal::<H, S>(
+            &mut config, 
+            config_handler, 
+            config_scheduler, 
+            &mut scene,
+            session
+        ).await
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+type HeadlessState = state::State<timing::DefaultScheduler>;
+
+// The setup shared by each of the headless renderers below.
+// Returns the first config with the state, or None if there are no configs
+// NOTE: Only the first config's resolution is used
+#[cfg(not(target_arch = "wasm32"))]
+async fn headless<H>(
+    configs: &[Config],
+    config_handler: H::Config,
+    scene: &mut scene::Scene,
+) -> anyhow::Result<Option<(Config, HeadlessState)>> 
+    where H: handlers::IntrsHandler {
+
+    let Some(config) = configs.first().copied() else {
+        return Ok(None);
+    };
+
+    let size = match config.resolution {
+        Resolution::Sized(size) => size,
+        Resolution::Fixed { size, .. } => size,
+        Resolution::Dynamic(_) => {
+            anyhow::bail!("Headless rendering requires a fixed resolution");
+        },
+    };
+
+    // The first pass draws the animation's current frame
+    scene.animate();
+
+    let state = HeadlessState::headless::<H>(
+        config, config_handler, (), scene, size).await?;
+
+    Ok(Some((config, state)))
+}
+
+// Renders the current state once for each config
+#[cfg(not(target_arch = "wasm32"))]
+fn snapshots(state: &mut HeadlessState, configs: &[Config]) -> anyhow::Result<Vec<CaptureImage>> {
+    configs
+        .iter()
+        .map(|config| {
+            state.update_config(config.compute);
+
+            state.snapshot(*config)
+        })
+        .collect()
+}
+
+// Renders a single frame for each config without opening a window
+// NOTE: Only the first config's resolution is used, as with the renderers below
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn run_headless<H>(
+    configs: &[Config],
+    config_handler: H::Config,
+    mut scene: scene::Scene,
+) -> anyhow::Result<Vec<CaptureImage>> 
+    where H: handlers::IntrsHandler {
+
+    let Some((_, mut state)) = headless::<H>(configs, config_handler, &mut scene).await? else {
+        return Ok(Vec::new());
+    };
+
+    snapshots(&mut state, configs)
+}
+
+// Renders each config once per frame of vertices, without opening a window.
+// The handler's hierarchy is built once, then refit for every frame
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn run_headless_animated<H>(
+    configs: &[Config],
+    config_handler: H::Config,
+    mut scene: scene::Scene,
+    frames: &[Vec<geom::PrimVertex>],
+) -> anyhow::Result<Vec<Vec<CaptureImage>>> 
+    where H: handlers::IntrsHandler {
+
+    let Some((_, mut state)) = headless::<H>(configs, config_handler, &mut scene).await? else {
+        return Ok(Vec::new());
+    };
+
+    frames
+        .iter()
+        .map(|vertices| {
+            state.update_vertices(vertices)?;
+
+            snapshots(&mut state, configs)
+        })
+        .collect()
+}
+
+// Renders each config once after every edit, without opening a window.
+// Edits patch the state in place rather than reloading the scene
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn run_headless_edited<H>(
+    configs: &[Config],
+    config_handler: H::Config,
+    mut scene: scene::Scene,
+    edits: Vec<scene::SceneEdit>,
+) -> anyhow::Result<Vec<Vec<CaptureImage>>> 
+    where H: handlers::IntrsHandler {
+
+    let Some((config, mut state)) = headless::<H>(configs, config_handler.clone(), &mut scene).await? else {
+        return Ok(Vec::new());
+    };
+
+    edits
+        .into_iter()
+        .map(|edit| {
+            state.edit::<H>(config, config_handler.clone(), &mut scene, edit)?;
+
+            snapshots(&mut state, configs)
+        })
+        .collect()
+}
+
+// Renders each config with two handlers, without opening a window.
+// Each primary image is paired with the compared handler's image
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn run_headless_compared<H>(
+    configs: &[Config],
+    config_handler: H::Config,
+    config_compare: CompareConfig,
+    mut scene: scene::Scene,
+) -> anyhow::Result<Vec<(CaptureImage, CaptureImage)>> 
+    where H: handlers::IntrsHandler {
+
+    let Some((config, mut state)) = headless::<H>(configs, config_handler, &mut scene).await? else {
+        return Ok(Vec::new());
+    };
+
+    state.compare(config, &scene, Some(config_compare))?;
+
+    configs
+        .iter()
+        .map(|config| {
+            state.update_config(config.compute);
+
+            let image = state.snapshot(*config)?;
+
+            Ok((image, state.snapshot_compared()?))
+        })
+        .collect()
+}
+
 #[cfg(target_arch = "wasm32")]
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen::prelude::wasm_bindgen)]
 pub async fn run_wasm() -> Result<(), Failed> {
@@ -185,25 +417,91 @@ pub async fn run_wasm() -> Result<(), Failed> {
         #[allow(static_mut_refs)]
         let web::WebState {
             config,
-            scene, ..
+            scene, 
+            handler, ..
         } = &mut web::WEB_STATE;
 
         // We don't take benchmarks on WASM
         type WebScheduler = timing::DefaultScheduler;
 
-        // TODO: I'm going to keep web::WebHandler == BasicIntrs
-        // until optimizations are complete
-        run_internal::<web::WebHandler, WebScheduler>
-            (config, <web::WebHandler as handlers::IntrsHandler>::Config::default(), scene).await
+        // The handler can be swapped from the page through `update_handler`
+        run_internal::<web::WebHandler, WebScheduler>(
+            config, 
+            *handler, 
+            (),
+            scene,
+            None
+        ).await
 
             
     }
 }
 
+// Swaps in the handler of the given kind, keeping the scene & camera
+// NOTE: It is built with its default config, not the one it was launched with
+#[cfg(not(target_arch = "wasm32"))]
+fn swap_handler<S: timing::Scheduler>(
+    state: &mut state::State<S>,
+    config: Config,
+    scene: &scene::Scene,
+    kind: handlers::IntrsKind,
+) -> bool {
+    // Loading restarts the scheduler, which would mix handlers into one benchmark
+    if state.continuous() {
+        log::warn!("Handlers can't be swapped while benchmarking");
+
+        return false;
+    }
+
+    match state.load::<handlers::DynIntrs>(config, kind, scene) {
+        Ok(_) => {
+            log::info!("Handler: {:?} (default config)", kind);
+
+            true
+        },
+        Err(e) => {
+            log::error!("Failed to swap handlers: {e}");
+
+            false
+        },
+    }
+}
+
+// Draws the given handler next to the primary one (or stops comparing)
+#[cfg(not(target_arch = "wasm32"))]
+fn compare_handlers<S: timing::Scheduler>(
+    state: &mut state::State<S>,
+    config: Config,
+    scene: &scene::Scene,
+    compare: Option<CompareConfig>,
+) -> bool {
+    // Benchmarks only measure a single handler
+    if state.continuous() {
+        log::warn!("Handlers can't be compared while benchmarking");
+
+        return false;
+    }
+
+    match state.compare(config, scene, compare) {
+        Ok(_) => {
+            log::info!("Comparison: {:?}", compare);
+
+            true
+        },
+        Err(e) => {
+            log::error!("Failed to compare handlers: {e}");
+
+            false
+        },
+    }
+}
+
 async unsafe fn run_internal<H, S>(
     config: &mut Config,
     config_handler: H::Config,
-    scene: &mut scene::Scene
+    config_scheduler: S::Config,
+    scene: &mut scene::Scene,
+    session: Option<session::SessionMode>,
 ) -> Result<(), Failed> 
     where H: handlers::IntrsHandler, S: timing::Scheduler {
 
@@ -233,12 +531,19 @@ async unsafe fn run_internal<H, S>(
     // This needs to be shared with State
     let window = sync::Arc::new(window);
 
+    // Replays overwrite the scene's camera before it is packed
+    let mut session = session::Session::new(session, config, scene);
+
+    // The first pass draws the animation's current frame,
+    // even when the scene wasn't deserialized
+    scene.animate();
+
     // Initialize the state (bail on failure)
     let mut state = {
         let window = window.clone();
 
         BAIL(state::State::<S>::new::<H>(
-            *config, config_handler, scene, window).await)?
+            *config, config_handler, config_scheduler, scene, window).await)?
     };
 
     // Keeps track of resize actions. 
@@ -257,6 +562,18 @@ async unsafe fn run_internal<H, S>(
     // Indicates whether the camera has changed
     let mut update_required_camera = false;
 
+    // Indicates that the config has been changed by a hotkey
+    let mut update_required_config = false;
+
+    // The handler cycled from with H (native only)
+    // Handlers without a kind (i.e. `BlankIntrs`) start the cycle over
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut handler_kind: Option<handlers::IntrsKind> = H::KIND;
+
+    // The handler drawn next to the primary one, if any (native only)
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut compare: Option<CompareConfig> = None;
+
     // Enter the event loop
     BAIL(event_loop.run(|event, target| {
         // We are only updating config options live on the web
@@ -278,10 +595,16 @@ async unsafe fn run_internal<H, S>(
 
                 let handled = match scene {
                     scene::Scene::Unloaded => false,
+                    // Live camera input is ignored during replays
+                    scene::Scene::Active { .. } if session.replaying() => false,
                     scene::Scene::Active { camera_controller, .. } => //
                         camera_controller.handle_event(&event),
                 };
 
+                if handled {
+                    session.record_input(&event);
+                }
+
                 if handled {
                     cfg_if::cfg_if! {
                         if #[cfg(target_arch = "wasm32")] {
@@ -299,6 +622,142 @@ async unsafe fn run_internal<H, S>(
                                 logical_key: keyboard::Key::Named(keyboard::NamedKey::Escape), ..
                             }, ..
                         } => target.exit(),
+                        // Cycle through the debug visualisation modes
+                        // On the web, this is handled through `update_config`
+                        #[cfg(not(target_arch = "wasm32"))]
+                        event::WindowEvent::KeyboardInput {
+                            event: event::KeyEvent {
+                                state: event::ElementState::Pressed,
+                                logical_key: keyboard::Key::Named(keyboard::NamedKey::Tab), 
+                                repeat: false, ..
+                            }, ..
+                        } => {
+                            config.compute.debug = config.compute.debug.next();
+
+                            log::info!("Debug mode: {:?}", config.compute.debug);
+
+                            state.update_config(config.compute);
+
+                            session.record(session::SessionEvent::Compute(config.compute));
+
+                            update_required_config = true;
+                        },
+                        // Toggle the BVH overlay (B) and restrict it to leaves (L)
+                        #[cfg(not(target_arch = "wasm32"))]
+                        event::WindowEvent::KeyboardInput {
+                            event: event::KeyEvent {
+                                state: event::ElementState::Pressed,
+                                logical_key: keyboard::Key::Character(ref key), 
+                                repeat: false, ..
+                            }, ..
+                        } if matches!(key.as_str(), "b" | "l") => {
+                            match key.as_str() {
+                                "b" => config.overlay.enabled ^= true,
+                                _ => config.overlay.leaves ^= true,
+                            }
+
+                            state.update_overlay(config.overlay);
+
+                            session.record(session::SessionEvent::Overlay(config.overlay));
+
+                            window.request_redraw();
+                        },
+                        // Toggle the performance HUD (I)
+                        // On the web, this is handled through `update_config`
+                        #[cfg(not(target_arch = "wasm32"))]
+                        event::WindowEvent::KeyboardInput {
+                            event: event::KeyEvent {
+                                state: event::ElementState::Pressed,
+                                logical_key: keyboard::Key::Character(ref key), 
+                                repeat: false, ..
+                            }, ..
+                        } if key.as_str() == "i" => {
+                            config.hud ^= true;
+
+                            state.update_hud(config.hud);
+
+                            session.record(session::SessionEvent::Hud(config.hud));
+
+                            window.request_redraw();
+                        },
+                        // Swap to the next intersection handler (H), keeping the scene & camera
+                        // On the web, this is handled through `update_handler`
+                        #[cfg(not(target_arch = "wasm32"))]
+                        event::WindowEvent::KeyboardInput {
+                            event: event::KeyEvent {
+                                state: event::ElementState::Pressed,
+                                logical_key: keyboard::Key::Character(ref key), 
+                                repeat: false, ..
+                            }, ..
+                        } if key.as_str() == "h" => {
+                            let kind = handler_kind
+                                .map_or(handlers::IntrsKind::Naive, handlers::IntrsKind::next);
+
+                            if swap_handler(&mut state, *config, scene, kind) {
+                                handler_kind = Some(kind);
+
+                                session.record(session::SessionEvent::Handler(kind));
+
+                                update_required_config = true;
+                            }
+                        },
+                        // Cycle the compared handler (C) or toggle between split & diff (V)
+                        // On the web, this is handled through `update_compare`
+                        #[cfg(not(target_arch = "wasm32"))]
+                        event::WindowEvent::KeyboardInput {
+                            event: event::KeyEvent {
+                                state: event::ElementState::Pressed,
+                                logical_key: keyboard::Key::Character(ref key), 
+                                repeat: false, ..
+                            }, ..
+                        } if matches!(key.as_str(), "c" | "v") => {
+                            // The last kind wraps back around to no comparison
+                            let next = match (key.as_str(), compare) {
+                                ("c", None) => Some(CompareConfig {
+                                    handler: handlers::IntrsKind::ALL[0],
+                                    mode: CompareMode::default(),
+                                }),
+                                ("c", Some(CompareConfig { handler, mode })) => {
+                                    let handler = handler.next();
+
+                                    (handler != handlers::IntrsKind::ALL[0])
+                                        .then_some(CompareConfig { handler, mode })
+                                },
+                                (_, current) => current.map(|current| CompareConfig {
+                                    mode: current.mode.next(), ..current
+                                }),
+                            };
+
+                            if compare_handlers(&mut state, *config, scene, next) {
+                                compare = next;
+
+                                session.record(session::SessionEvent::Compare(next));
+
+                                update_required_config = true;
+                            }
+                        },
+                        // Save a screenshot (P) or toggle frame recording (R)
+                        // On the web, screenshots are requested through `capture`
+                        #[cfg(not(target_arch = "wasm32"))]
+                        event::WindowEvent::KeyboardInput {
+                            event: event::KeyEvent {
+                                state: event::ElementState::Pressed,
+                                logical_key: keyboard::Key::Character(ref key), 
+                                repeat: false, ..
+                            }, ..
+                        } if matches!(key.as_str(), "p" | "r") => {
+                            match key.as_str() {
+                                "p" => state.screenshot(),
+                                _ => match state.toggle_recording() {
+                                    Ok(recording) => {
+                                        if !recording {
+                                            log::info!("Stopped recording");
+                                        }
+                                    },
+                                    Err(e) => log::error!("Failed to start recording: {e}"),
+                                },
+                            }
+                        },
                         event::WindowEvent::Resized(physical_size) //
                             if resize_dim != Some(physical_size) => {
                             // Update the size and the time the event occurred
@@ -308,17 +767,17 @@ async unsafe fn run_internal<H, S>(
                         },
                         event::WindowEvent::RedrawRequested => {
                             match state.render() {
-                                Ok(_) => { /*  */ },
+                                Ok(_) => {},
                                 Err(wgpu::SurfaceError::Lost | 
                                     wgpu::SurfaceError::Outdated
                                 ) => state.resize(*config, window.inner_size()),
                                 Err(e) => failure = BAIL(Err(e)),
                             }
                         },
-                        _ => { /*  */ },
+                        _ => {},
                     }
                 }},
-            _ => { /*  */ },
+            _ => {},
         }
 
         // Take a snapshot of the current Instant
@@ -339,24 +798,98 @@ async unsafe fn run_internal<H, S>(
             temp = frame_duration; prev_frame_duration = 0.;
         }
 
+        // Replayed events are applied in the order they were recorded
+        let replayed = session.poll();
+
         // Update the camera
         // NOTE: Camera updates are tied to FPS
         if let scene::Scene::Active { 
             camera, 
             camera_controller, .. 
         } = scene {
-            if camera_controller.update(camera, temp as f32) {
+            for event in replayed.iter() {
+                match *event {
+                    session::SessionEvent::Key { ref key, pressed } => {
+                        camera_controller.handle_key(key, pressed);
+                    },
+                    session::SessionEvent::Update { dt } => {
+                        if camera_controller.update(camera, dt) {
+                            state.update_camera_buffer(*camera);
+
+                            update_required_camera = true;
+                        }
+                    },
+                    session::SessionEvent::Advance => camera_controller.advance(),
+                    session::SessionEvent::Compute(compute) => {
+                        config.compute = compute;
+
+                        state.update_config(compute);
+
+                        update_required_config = true;
+                    },
+                    session::SessionEvent::Overlay(overlay) => {
+                        config.overlay = overlay;
+
+                        state.update_overlay(overlay);
+
+                        window.request_redraw();
+                    },
+                    session::SessionEvent::Hud(hud) => {
+                        config.hud = hud;
+
+                        state.update_hud(hud);
+
+                        window.request_redraw();
+                    },
+                    // These need the whole scene, so they're replayed below
+                    session::SessionEvent::Handler(_) | 
+                    session::SessionEvent::Compare(_) => {},
+                }
+            }
+
+            if !session.replaying() && camera_controller.update(camera, temp as f32) {
+                session.record(session::SessionEvent::Update { dt: temp as f32 });
+
                 state.update_camera_buffer(*camera);
 
                 update_required_camera = true;
             }
         }
 
+        // Handler swaps & comparisons are replayed once the camera is up to date
+        // Like their hotkeys, both are refused while benchmarking
+        #[cfg(not(target_arch = "wasm32"))]
+        for event in replayed {
+            match event {
+                session::SessionEvent::Handler(kind) //
+                    if swap_handler(&mut state, *config, scene, kind) => {
+                    handler_kind = Some(kind);
+
+                    update_required_config = true;
+                },
+                session::SessionEvent::Compare(next) //
+                    if compare_handlers(&mut state, *config, scene, next) => {
+                    compare = next;
+
+                    update_required_config = true;
+                },
+                // Camera & config events were replayed above, failed swaps are logged
+                _ => {},
+            }
+        }
+
         prev_frame_instant = frame_instant;
         prev_frame_duration += temp;
 
-        #[allow(unused_mut)] // Indicates that its time for the next frame
-        let mut update_required_framerate = false;
+        // Indicates that its time for the next frame
+        // Schedulers may request that every frame is rendered
+        #[allow(unused_mut)]
+        let mut update_required_framerate = state.continuous();
+
+        // Camera paths and animations request a compute pass for each of their frames
+        if let scene::Scene::Active { camera_controller, animation, .. } = scene {
+            update_required_framerate |= camera_controller.playing() || animation.playing();
+        }
 
         #[cfg(not(target_arch = "wasm32"))] {
             // Calculate time since last resize event
@@ -381,7 +914,7 @@ async unsafe fn run_internal<H, S>(
             }
         }
 
-        if !(update_required_camera || update_required_framerate) {
+        if !(update_required_camera || update_required_framerate || update_required_config) {
             // If no update is required, discard the frame
             if prev_frame_duration > frame_duration {
                 prev_frame_duration -= frame_duration;
@@ -398,7 +931,54 @@ async unsafe fn run_internal<H, S>(
             // This is platform-specific
             // Queue::on_submitted_work_done is not available on WASM
             // So we implement compute pass completion checking with map_async
-            state.update(*config);
+            let dispatched = state.update(*config);
+
+            // Paths are tied to compute passes rather than frame time,
+            // so the next view is applied before another pass can be dispatched
+            if let scene::Scene::Active { 
+                camera, 
+                camera_controller, .. 
+            } = scene {
+                if dispatched && camera_controller.playing() && !session.replaying() {
+                    camera_controller.advance();
+
+                    session.record(session::SessionEvent::Advance);
+
+                    if camera_controller.update(camera, 0.) {
+                        session.record(session::SessionEvent::Update { dt: 0. });
+
+                        state.update_camera_buffer(*camera);
+                    }
+                }
+            }
+
+            // Animations also advance once per compute pass
+            if let scene::Scene::Active { 
+                lights, 
+                materials, 
+                animation, .. 
+            } = scene {
+                if dispatched && animation.playing() {
+                    animation.advance();
+
+                    let scene::AnimationChanges {
+                        lights: update_lights,
+                        materials: update_materials,
+                    } = animation.apply(lights, materials);
+
+                    if update_lights {
+                        if let Err(e) = state.update_lights(lights) {
+                            failure = BAIL(Err(e));
+                        }
+                    }
+
+                    if update_materials {
+                        if let Err(e) = state.update_materials(materials) {
+                            failure = BAIL(Err(e));
+                        }
+                    }
+                }
+            }
 
             if !requested {
                 // Anytime we update, we need to request a redraw
@@ -411,14 +991,27 @@ async unsafe fn run_internal<H, S>(
             // We need to state that we've handled the camera update
             // Since it resides outside of the event loop
             update_required_camera = false;
+            update_required_config = false;
 
             // Decrement the frame
             prev_frame_duration -= frame_duration;
         }
 
+        // Save any screenshots or frames that have been read back
+        state.poll_captures();
+
         // If we've ran into an error, start the process of exiting
         if failure.is_err() { target.exit(); }
+
+        // The scheduler has nothing left to do (i.e. benchmark complete)
+        if state.finished() { target.exit(); }
     }))?;
 
+    // Recorded sessions are only written once the window has closed
+    BAIL(session.finish())?;
+
+    // Benchmarks may still be writing their results
+    BAIL(state.finish())?;
+
     failure
 }
\ No newline at end of file
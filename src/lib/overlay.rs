@@ -0,0 +1,273 @@
+use std::mem;
+
+use wgpu::util::DeviceExt as _;
+
+use crate::bvh;
+
+// A single BVH node, drawn as an instance of a wireframe box
+#[repr(C)]
+#[derive(bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Clone, Copy)]
+struct OverlayBox {
+    min: [f32; 3],
+    depth: u32,
+    max: [f32; 3],
+    leaf: u32,
+}
+
+impl OverlayBox {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
+        0 => Float32x3,
+        1 => Uint32,
+        2 => Float32x3,
+        3 => Uint32
+    ];
+
+    fn description<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+
+    // Walk the tree from the root to find the depth of each node
+    fn from_data(data: &bvh::BvhData) -> Vec<Self> {
+        let bvh::BvhData { uniforms, .. } = data;
+
+        let mut boxes = Vec::with_capacity(uniforms.len());
+
+        if uniforms.is_empty() { return boxes; }
+
+        let mut stack = vec![(0, 0)];
+        while let Some((idx, depth)) = stack.pop() {
+            let bvh::AabbUniform {
+                fst,
+                snd,
+                item_count,
+                bounds: bvh::Bounds { min, max, .. }, ..
+            } = uniforms[idx as usize];
+
+            let leaf = item_count > 0;
+
+            boxes.push(Self { min, depth, max, leaf: leaf as u32 });
+
+            if !leaf {
+                stack.push((fst, depth + 1));
+                stack.push((snd, depth + 1));
+            }
+        }
+
+        boxes
+    }
+}
+
+// Mirrors `Overlay` in `overlay.wgsl`
+#[repr(C)]
+#[derive(bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Clone, Copy)]
+struct OverlayUniform {
+    depth_min: u32,
+    depth_max: u32,
+    leaves: u32,
+    _p0: u32,
+}
+
+impl From<crate::OverlayConfig> for OverlayUniform {
+    fn from(value: crate::OverlayConfig) -> Self {
+        let crate::OverlayConfig {
+            depth_min,
+            depth_max,
+            leaves, ..
+        } = value;
+
+        Self {
+            depth_min,
+            depth_max,
+            leaves: leaves as u32,
+            _p0: 0,
+        }
+    }
+}
+
+// Wireframe boxes of the BVH's nodes, drawn over the ray-traced image
+#[derive(Debug)]
+pub struct Overlay {
+    enabled: bool,
+    count: u32,
+    boxes: wgpu::Buffer,
+    buffer: wgpu::Buffer,
+    group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl Overlay {
+    // Each box is drawn as 12 lines
+    const VERTICES: u32 = 24;
+
+    pub fn new(
+        device: &wgpu::Device,
+        tex_format: wgpu::TextureFormat,
+        module: &wgpu::ShaderModule,
+        camera_buffer: &wgpu::Buffer,
+        data: &bvh::BvhData,
+        config: crate::OverlayConfig,
+    ) -> Self {
+        let boxes = OverlayBox::from_data(data);
+
+        let count = boxes.len() as u32;
+
+        let boxes = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&boxes),
+                usage: wgpu::BufferUsages::VERTEX,
+            }
+        );
+
+        let buffer = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&[OverlayUniform::from(config)]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            }
+        );
+
+        let layout = device.create_bind_group_layout(
+            &wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        count: None,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        count: None,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                    },
+                ],
+            }
+        );
+
+        let group = device.create_bind_group(
+            &wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: camera_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: buffer.as_entire_binding(),
+                    },
+                ],
+            }
+        );
+
+        let pipeline_layout = device.create_pipeline_layout(
+            &wgpu::PipelineLayoutDescriptor {
+                label: None,
+                push_constant_ranges: &[],
+                bind_group_layouts: &[&layout],
+            }
+        );
+
+        // The SRGB texture view isn't available on web
+        cfg_if::cfg_if! {
+            if #[cfg(target_arch = "wasm32")] {
+                let fragment_format = tex_format;
+            } else {
+                let fragment_format = tex_format.add_srgb_suffix();
+            }
+        }
+
+        let pipeline = device.create_render_pipeline(
+            &wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module,
+                    entry_point: "vs_main",
+                    buffers: &[OverlayBox::description()],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: fragment_format,
+                        blend: Some(wgpu::BlendState::REPLACE),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::LineList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: None,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+            }
+        );
+
+        Self {
+            enabled: config.enabled,
+            count,
+            boxes,
+            buffer,
+            group,
+            pipeline,
+        }
+    }
+
+    pub fn update(&mut self, queue: &wgpu::Queue, config: crate::OverlayConfig) {
+        self.enabled = config.enabled;
+
+        queue.write_buffer(
+            &self.buffer,
+            0,
+            bytemuck::cast_slice(&[OverlayUniform::from(config)])
+        );
+    }
+
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        let Self {
+            enabled,
+            count,
+            boxes,
+            group,
+            pipeline, ..
+        } = self;
+
+        if !*enabled { return; }
+
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, group, &[]);
+        render_pass.set_vertex_buffer(0, boxes.slice(..));
+        render_pass.draw(0..Self::VERTICES, 0..*count);
+    }
+
+    // Only called when reloading scenes
+    pub fn destroy(&self) {
+        self.boxes.destroy();
+        self.buffer.destroy();
+    }
+}
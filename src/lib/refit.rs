@@ -0,0 +1,231 @@
+use std::mem;
+
+use wgpu::util::DeviceExt as _;
+
+use crate::bvh;
+
+// Mirrors `Level` in `refit.wgsl`
+#[repr(C)]
+#[derive(bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Clone, Copy)]
+struct RefitLevel {
+    start: u32,
+    count: u32,
+}
+
+// Refits the bounds of a handler's BVH nodes in place,
+// keeping the topology it was built with
+#[derive(Debug)]
+pub struct Refit {
+    // The number of nodes in each level, deepest first
+    levels: Vec<u32>,
+    // Distance between consecutive `RefitLevel`s (dynamic offsets are aligned)
+    stride: u32,
+    topology: wgpu::Buffer,
+    order: wgpu::Buffer,
+    buffer: wgpu::Buffer,
+    group: wgpu::BindGroup,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl Refit {
+    // Matches `@workgroup_size` in `refit.wgsl`
+    const WORKGROUP_SIZE: u32 = 64;
+
+    // `nodes` must hold the elements of `data.uniforms` (in order),
+    // each ending with its `bvh::Bounds` like `bvh::AabbUniform`
+    pub fn new(
+        device: &wgpu::Device,
+        module: &wgpu::ShaderModule,
+        data: &bvh::BvhData,
+        nodes: &wgpu::Buffer,
+        prims: &wgpu::Buffer,
+        vertices: &wgpu::Buffer,
+    ) -> Self {
+        // Group the nodes by their depth
+        let mut levels: Vec<Vec<u32>> = Vec::new();
+
+        let mut stack = vec![(0, 0)];
+        while let Some((idx, depth)) = stack.pop() {
+            if levels.len() <= depth {
+                levels.resize_with(depth + 1, Vec::new);
+            }
+
+            levels[depth].push(idx);
+
+            let bvh::AabbUniform { fst, snd, .. } = data.uniforms[idx as usize];
+
+            if fst != 0 || snd != 0 {
+                stack.push((fst, depth + 1));
+                stack.push((snd, depth + 1));
+            }
+        }
+
+        // Children are always one level deeper than their parent
+        levels.reverse();
+
+        let stride = device.limits().min_uniform_buffer_offset_alignment;
+
+        let mut contents = vec![0u8; stride as usize * levels.len()];
+
+        let mut start = 0;
+        for (level, nodes) in levels.iter().enumerate() {
+            let uniform = RefitLevel { start, count: nodes.len() as u32 };
+
+            let offset = level * stride as usize;
+
+            contents[offset..(offset + mem::size_of::<RefitLevel>())]
+                .copy_from_slice(bytemuck::bytes_of(&uniform));
+
+            start += nodes.len() as u32;
+        }
+
+        let buffer = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: &contents,
+                usage: wgpu::BufferUsages::UNIFORM,
+            }
+        );
+
+        let topology = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&data.uniforms),
+                usage: wgpu::BufferUsages::STORAGE,
+            }
+        );
+
+        let order = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&levels.concat()),
+                usage: wgpu::BufferUsages::STORAGE,
+            }
+        );
+
+        let storage = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            count: None,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+        };
+
+        let layout = device.create_bind_group_layout(
+            &wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[
+                    storage(0, false),
+                    storage(1, true),
+                    storage(2, true),
+                    storage(3, true),
+                    storage(4, true),
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 5,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        count: None,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: true,
+                            min_binding_size: wgpu::BufferSize::new({
+                                mem::size_of::<RefitLevel>() as u64
+                            }),
+                        },
+                    },
+                ],
+            }
+        );
+
+        let group = device.create_bind_group(
+            &wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: nodes.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: topology.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: order.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: prims.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 4,
+                        resource: vertices.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 5,
+                        resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                            buffer: &buffer,
+                            offset: 0,
+                            size: wgpu::BufferSize::new({
+                                mem::size_of::<RefitLevel>() as u64
+                            }),
+                        }),
+                    },
+                ],
+            }
+        );
+
+        let pipeline_layout = device.create_pipeline_layout(
+            &wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[&layout],
+                push_constant_ranges: &[],
+            }
+        );
+
+        let pipeline = device.create_compute_pipeline(
+            &wgpu::ComputePipelineDescriptor {
+                label: None,
+                layout: Some(&pipeline_layout),
+                module,
+                entry_point: "main_cs",
+            }
+        );
+
+        Self {
+            levels: levels.iter().map(|nodes| nodes.len() as u32).collect(),
+            stride,
+            topology,
+            order,
+            buffer,
+            group,
+            pipeline,
+        }
+    }
+
+    // Each level is its own dispatch, so it sees the writes of the previous one
+    pub fn encode(&self, encoder: &mut wgpu::CommandEncoder) {
+        let mut compute_pass = encoder.begin_compute_pass(&{
+            wgpu::ComputePassDescriptor::default()
+        });
+
+        compute_pass.set_pipeline(&self.pipeline);
+
+        for (level, &count) in self.levels.iter().enumerate() {
+            compute_pass.set_bind_group(0, &self.group, &[level as u32 * self.stride]);
+
+            compute_pass.dispatch_workgroups(count.div_ceil(Self::WORKGROUP_SIZE), 1, 1);
+        }
+    }
+
+    // Only called when reloading scenes
+    pub fn destroy(&self) {
+        self.topology.destroy();
+        self.order.destroy();
+        self.buffer.destroy();
+    }
+}
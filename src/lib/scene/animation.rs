@@ -0,0 +1,221 @@
+use crate::geom;
+use crate::geom::light as light;
+
+use super::path::lerp;
+
+#[derive(Clone, Copy)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Debug)]
+pub struct AnimationKeyframe<T> {
+    // Seconds since the start of the animation
+    pub time: f32,
+    pub value: T,
+}
+
+// Each track drives a single property of a light or material.
+// Values are interpolated linearly between keyframes
+// TODO: Object transforms, once scenes support instancing
+#[derive(Clone)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Debug)]
+#[serde(tag = "target", rename_all = "snake_case")]
+pub enum AnimationTrack {
+    LightPos {
+        light: usize,
+        keyframes: Vec<AnimationKeyframe<[f32; 3]>>,
+    },
+    LightStrength {
+        light: usize,
+        keyframes: Vec<AnimationKeyframe<f32>>,
+    },
+    MaterialColor {
+        material: usize,
+        keyframes: Vec<AnimationKeyframe<[f32; 3]>>,
+    },
+}
+
+// The buffers that have to be uploaded after a frame is applied
+#[derive(Clone, Copy)]
+#[derive(Default)]
+#[derive(Debug)]
+pub struct AnimationChanges {
+    pub lights: bool,
+    pub materials: bool,
+}
+
+// Keyframed lights and materials, played alongside the camera.
+// Like camera paths, playback advances by one frame per compute pass
+#[derive(Clone)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Debug)]
+pub struct Animation {
+    #[serde(default)]
+    pub tracks: Vec<AnimationTrack>,
+    // The number of frames played per second of animation time
+    #[serde(default = "Animation::default_rate")]
+    pub rate: f32,
+    // Restart from the first frame once every track is complete
+    #[serde(default)]
+    pub looping: bool,
+    #[serde(skip)]
+    frame: u32,
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Self {
+            tracks: Vec::new(),
+            rate: Self::default_rate(),
+            looping: false,
+            frame: 0,
+        }
+    }
+}
+
+// Samples a track at the given time, holding the end values
+fn sample<T: Copy>(
+    keyframes: &[AnimationKeyframe<T>],
+    time: f32,
+    interpolate: impl Fn(T, T, f32) -> T,
+) -> Option<T> {
+    let idx = keyframes.partition_point(|keyframe| keyframe.time <= time);
+
+    let k1 = keyframes.get(idx.saturating_sub(1))?;
+    let k2 = keyframes.get(idx).unwrap_or(k1);
+
+    let span = k2.time - k1.time;
+    let t = if span > 0. {
+        ((time - k1.time) / span).clamp(0., 1.)
+    } else {
+        0.
+    };
+
+    Some(interpolate(k1.value, k2.value, t))
+}
+
+impl Animation {
+    const fn default_rate() -> f32 { 60. }
+
+    // Tracks must be sorted and only target lights & materials that exist
+    pub fn validate(&self, lights: usize, materials: usize) -> anyhow::Result<()> {
+        if !(self.rate.is_finite() && self.rate > 0.) {
+            anyhow::bail!("Animation rate must be positive");
+        }
+
+        for track in self.tracks.iter() {
+            let (times, idx, count, kind) = match track {
+                AnimationTrack::LightPos { light, keyframes } => //
+                    (keyframes.iter().map(|k| k.time).collect::<Vec<_>>(), *light, lights, "light"),
+                AnimationTrack::LightStrength { light, keyframes } => //
+                    (keyframes.iter().map(|k| k.time).collect(), *light, lights, "light"),
+                AnimationTrack::MaterialColor { material, keyframes } => //
+                    (keyframes.iter().map(|k| k.time).collect(), *material, materials, "material"),
+            };
+
+            if times.is_empty() {
+                anyhow::bail!("Animation tracks must contain at least one keyframe");
+            }
+
+            if times.windows(2).any(|pair| pair[0] > pair[1]) {
+                anyhow::bail!("Animation keyframes must be sorted by time");
+            }
+
+            if idx >= count {
+                anyhow::bail!("Animation track targets {kind} {idx}, but the scene only has {count}");
+            }
+        }
+
+        Ok(())
+    }
+
+    // The time of the last keyframe in any track
+    fn duration(&self) -> f32 {
+        self.tracks
+            .iter()
+            .filter_map(|track| match track {
+                AnimationTrack::LightPos { keyframes, .. } | //
+                AnimationTrack::MaterialColor { keyframes, .. } => keyframes.last().map(|k| k.time),
+                AnimationTrack::LightStrength { keyframes, .. } => keyframes.last().map(|k| k.time),
+            })
+            .fold(0., f32::max)
+    }
+
+    // The number of distinct frames in a single playback
+    pub fn frames(&self) -> u32 {
+        if self.tracks.is_empty() { return 0; }
+
+        (self.duration() * self.rate).ceil() as u32 + 1
+    }
+
+    // Move on to the next frame
+    pub fn advance(&mut self) {
+        let frames = self.frames();
+
+        if self.looping && frames > 0 {
+            self.frame = (self.frame + 1) % frames;
+        } else if self.frame + 1 < frames {
+            self.frame += 1;
+        }
+    }
+
+    // Whether there are frames left to advance through
+    pub fn playing(&self) -> bool {
+        let frames = self.frames();
+
+        frames > 1 && (self.looping || self.frame + 1 < frames)
+    }
+
+    // Drops the tracks of a removed light and shifts the ones that follow it
+    pub fn remove_light(&mut self, idx: usize) {
+        self.tracks.retain_mut(|track| match track {
+            AnimationTrack::LightPos { light, .. } | //
+            AnimationTrack::LightStrength { light, .. } => {
+                if *light == idx { return false; }
+
+                if *light > idx { *light -= 1; }
+
+                true
+            },
+            AnimationTrack::MaterialColor { .. } => true,
+        });
+    }
+
+    // Writes the values of the current frame into the scene
+    pub fn apply(
+        &self,
+        lights: &mut [light::Light],
+        materials: &mut [geom::PrimMat],
+    ) -> AnimationChanges {
+        let time = self.frame as f32 / self.rate;
+
+        let mut changes = AnimationChanges::default();
+
+        for track in self.tracks.iter() {
+            match track {
+                AnimationTrack::LightPos { light, keyframes } => {
+                    if let Some(pos) = sample(keyframes, time, lerp) {
+                        lights[*light].pos = pos;
+
+                        changes.lights = true;
+                    }
+                },
+                AnimationTrack::LightStrength { light, keyframes } => {
+                    if let Some(strength) = sample(keyframes, time, |a, b, t| a + (b - a) * t) {
+                        lights[*light].strength = strength;
+
+                        changes.lights = true;
+                    }
+                },
+                AnimationTrack::MaterialColor { material, keyframes } => {
+                    if let Some(color) = sample(keyframes, time, lerp) {
+                        materials[*material].color = color;
+
+                        changes.materials = true;
+                    }
+                },
+            }
+        }
+
+        changes
+    }
+}
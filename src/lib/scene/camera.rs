@@ -1,5 +1,7 @@
 use winit::{event, keyboard};
 
+use super::path::CameraPath;
+
 #[repr(C)]
 #[derive(bytemuck::Pod, bytemuck::Zeroable)]
 #[derive(Clone, Copy)]
@@ -75,11 +77,13 @@ impl<'de> serde::Deserialize<'de> for CameraUniform {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 #[derive(Debug)]
 pub enum CameraController {
     Orbit { left: bool, right: bool, },
     Fixed,
+    // `dirty` is set whenever `frame` hasn't been applied to the camera yet
+    Path { path: CameraPath, frame: u32, dirty: bool, },
 }
 
 impl<'de> serde::Deserialize<'de> for CameraController {
@@ -90,24 +94,23 @@ impl<'de> serde::Deserialize<'de> for CameraController {
         enum Intermediate {
             Orbit,
             Fixed,
+            Path(CameraPath),
         }
 
-        #[allow(clippy::from_over_into)]
-        impl Into<CameraController> for Intermediate {
-            fn into(self) -> CameraController {
-                match self {
-                    Intermediate::Orbit => //
-                        CameraController::Orbit { 
-                            left: false, 
-                            right: false,
-                        },
-                    Intermediate::Fixed => //
-                        CameraController::Fixed,
-                }
-            }
-        }
+        match Intermediate::deserialize(deserializer)? {
+            Intermediate::Orbit => Ok(CameraController::Orbit { 
+                left: false, 
+                right: false,
+            }),
+            Intermediate::Fixed => Ok(CameraController::Fixed),
+            Intermediate::Path(path) => {
+                use serde::de;
+
+                path.validate().map_err(de::Error::custom)?;
 
-        Ok(Intermediate::deserialize(deserializer)?.into())
+                Ok(CameraController::path(path))
+            },
+        }
     }
 }
 
@@ -116,63 +119,84 @@ impl serde::Serialize for CameraController {
         where S: serde::Serializer {
 
         #[derive(serde::Serialize)]
-        enum Intermediate {
+        enum Intermediate<'a> {
             Orbit,
             Fixed,
+            Path(&'a CameraPath),
         }
 
-        impl From<CameraController> for Intermediate {
-            fn from(value: CameraController) -> Self {
+        impl<'a> From<&'a CameraController> for Intermediate<'a> {
+            fn from(value: &'a CameraController) -> Self {
                 match value {
                     CameraController::Orbit { .. } => Intermediate::Orbit,
                     CameraController::Fixed => Intermediate::Fixed,
+                    CameraController::Path { path, .. } => Intermediate::Path(path),
                 }
             }
         }
 
-        Into::<Intermediate>::into(*self).serialize(serializer)
+        Intermediate::from(self).serialize(serializer)
     }
 }
 
 impl CameraController {
+    // Playback begins from the first keyframe
+    pub const fn path(path: CameraPath) -> Self {
+        Self::Path { path, frame: 0, dirty: true, }
+    }
+
     #[allow(dead_code)]
     pub fn handle_event(&mut self, event: &event::WindowEvent) -> bool {
-        // The fixed camera never consumes an event
-        let Self::Orbit {
-            left, right, ..
-        } = self else { return false; };
-
         match event {
             event::WindowEvent::KeyboardInput {
                 event: event::KeyEvent {
-                    logical_key: keyboard::Key::Named(key),
+                    logical_key,
                     state, ..
                 }, ..
-            } => {
-                let pressed = matches!(state, event::ElementState::Pressed);
-
-                let mut handled = true;
-                match *key {
-                    keyboard::NamedKey::ArrowLeft => *left = pressed,
-                    keyboard::NamedKey::ArrowRight => *right = pressed,
-                    _ => handled = false,
-                }
-    
-                handled
-            },
+            } => self.handle_key(logical_key, state.is_pressed()),
             _ => false
         }
     }
 
+    // Separated from `handle_event` so recorded sessions can be replayed
+    pub fn handle_key(&mut self, key: &keyboard::Key, pressed: bool) -> bool {
+        // Only the orbit camera consumes events
+        let Self::Orbit {
+            left, right, ..
+        } = self else { return false; };
+
+        match key {
+            keyboard::Key::Named(keyboard::NamedKey::ArrowLeft) => *left = pressed,
+            keyboard::Key::Named(keyboard::NamedKey::ArrowRight) => *right = pressed,
+            _ => return false,
+        }
+
+        true
+    }
+
     #[allow(dead_code)]
     pub fn update(&mut self, uniform: &mut CameraUniform, dt: f32) -> bool {
         // Arbitrary multiplier I've decided on to set default
         // rotation speed
         const SPEED: f32 = 0.1;
 
-        let Self::Orbit { 
-            left, right, ..
-        } = self else { return false; };
+        let (left, right) = match self {
+            Self::Orbit { left, right, .. } => (*left, *right),
+            Self::Fixed => return false,
+            // Paths ignore frame time, they only move when advanced
+            Self::Path { path, frame, dirty } => {
+                if !*dirty { return false; }
+
+                *dirty = false;
+
+                return match path.sample(*frame) {
+                    Some(sampled) => {
+                        *uniform = sampled; true
+                    },
+                    None => false,
+                };
+            },
+        };
 
         fn orbit(uni: &mut CameraUniform, mult: f32) {
             let x = uni.pos[0] - uni.at[0];
@@ -188,18 +212,44 @@ impl CameraController {
             uni.pos = [x, uni.pos[1], z];
         }
 
-        if *left {
+        if left {
             orbit(uniform, SPEED * dt);
 
             return true;
         }
 
-        if *right {
-            orbit(uniform, -1. * SPEED * dt);
+        if right {
+            orbit(uniform, -SPEED * dt);
 
             return true;
         }
 
         false
     }
-}
\ No newline at end of file
+
+    // Move a path on to its next frame
+    // Called once for every compute pass that is dispatched
+    pub fn advance(&mut self) {
+        if let Self::Path { path, frame, dirty } = self {
+            let frames = path.frames();
+
+            if path.looping && frames > 0 {
+                *frame = (*frame + 1) % frames;
+                *dirty = true;
+            } else if *frame + 1 < frames {
+                *frame += 1;
+                *dirty = true;
+            }
+        }
+    }
+
+    // Whether the controller will move the camera without any input
+    pub fn playing(&self) -> bool {
+        match self {
+            Self::Path { path, frame, dirty } => {
+                *dirty || path.looping || *frame + 1 < path.frames()
+            },
+            _ => false,
+        }
+    }
+}
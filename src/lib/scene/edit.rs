@@ -0,0 +1,145 @@
+use crate::geom;
+use crate::geom::light as light;
+
+use super::{camera, Scene};
+
+// A single change to an active scene, small enough to be sent from the web UI
+#[derive(Clone)]
+#[derive(serde::Deserialize)]
+#[derive(Debug)]
+#[serde(tag = "edit", rename_all = "snake_case")]
+pub enum SceneEdit {
+    AddLight {
+        light: light::Light,
+    },
+    RemoveLight {
+        light: usize,
+    },
+    EditMaterial {
+        material: usize,
+        value: geom::PrimMat,
+    },
+    // Prim indices are relative to the mesh's own vertices
+    AppendMesh {
+        prims: Vec<geom::Prim>,
+        vertices: Vec<geom::PrimVertex>,
+    },
+    CameraController {
+        controller: camera::CameraController,
+    },
+}
+
+// The GPU-side work that follows an edit
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub enum SceneUpdate {
+    // The light buffer changed length
+    Lights,
+    Material(usize),
+    // The handler's structure has to be rebuilt.
+    // Holds the lengths of the scene's prims & vertices before the edit
+    Geometry { prims: usize, vertices: usize },
+    // The camera may have moved
+    Camera,
+}
+
+impl Scene {
+    // Applies an edit to the CPU-side scene
+    pub fn edit(&mut self, edit: SceneEdit) -> anyhow::Result<SceneUpdate> {
+        let Self::Active {
+            camera,
+            camera_controller,
+            prims,
+            vertices,
+            lights,
+            materials,
+            animation,
+        } = self else {
+            anyhow::bail!("Unable to edit unloaded scene");
+        };
+
+        let update = match edit {
+            SceneEdit::AddLight { light } => {
+                lights.push(light);
+
+                SceneUpdate::Lights
+            },
+            SceneEdit::RemoveLight { light } => {
+                if light >= lights.len() {
+                    anyhow::bail!("Unable to remove light {light}, the scene only has {}", lights.len());
+                }
+
+                // Empty storage buffers can't be bound
+                if lights.len() == 1 {
+                    anyhow::bail!("Scenes require at least one light");
+                }
+
+                lights.remove(light);
+
+                animation.remove_light(light);
+
+                SceneUpdate::Lights
+            },
+            SceneEdit::EditMaterial { material, value } => {
+                let Some(target) = materials.get_mut(material) else {
+                    anyhow::bail!("Unable to edit material {material}, the scene only has {}", materials.len());
+                };
+
+                *target = value;
+
+                SceneUpdate::Material(material)
+            },
+            SceneEdit::AppendMesh { prims: mesh_prims, vertices: mesh_vertices } => {
+                if mesh_prims.is_empty() {
+                    anyhow::bail!("Unable to append a mesh without prims");
+                }
+
+                for geom::Prim { indices, material } in mesh_prims.iter() {
+                    if indices.iter().any(|&idx| idx as usize >= mesh_vertices.len()) {
+                        anyhow::bail!("Mesh prim references a vertex that doesn't exist");
+                    }
+
+                    if *material < 0 || *material as usize >= materials.len() {
+                        anyhow::bail!("Mesh prim references material {material}, but the scene only has {}", materials.len());
+                    }
+                }
+
+                let update = SceneUpdate::Geometry {
+                    prims: prims.len(),
+                    vertices: vertices.len(),
+                };
+
+                let offset = vertices.len() as u32;
+
+                prims.extend(mesh_prims.into_iter().map(|prim| geom::Prim {
+                    indices: prim.indices.map(|idx| idx + offset),
+                    ..prim
+                }));
+
+                vertices.extend(mesh_vertices);
+
+                update
+            },
+            SceneEdit::CameraController { controller } => {
+                *camera_controller = controller;
+
+                // Paths jump straight to their first keyframe,
+                // other controllers keep the current camera
+                camera_controller.update(camera, 0.);
+
+                SceneUpdate::Camera
+            },
+        };
+
+        Ok(update)
+    }
+
+    // Reverts a `SceneUpdate::Geometry` edit.
+    // New prims & vertices are only ever appended
+    pub fn revert_geometry(&mut self, prims_len: usize, vertices_len: usize) {
+        if let Self::Active { prims, vertices, .. } = self {
+            prims.truncate(prims_len);
+            vertices.truncate(vertices_len);
+        }
+    }
+}
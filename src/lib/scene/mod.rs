@@ -1,7 +1,13 @@
 mod camera;
+mod path;
+mod animation;
+mod edit;
 
 // Export for use in `builder` binary
 pub use camera::{CameraUniform, CameraController};
+pub use path::{CameraPath, CameraKeyframe, CameraInterpolation};
+pub use animation::{Animation, AnimationTrack, AnimationKeyframe, AnimationChanges};
+pub use edit::{SceneEdit, SceneUpdate};
 
 use crate::geom;
 use crate::geom::light as light;
@@ -13,7 +19,9 @@ pub struct ScenePack {
     pub bg_layout: wgpu::BindGroupLayout,
 }
 
+#[derive(Clone)]
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum Scene {
     Unloaded,
     Active {
@@ -23,6 +31,7 @@ pub enum Scene {
         vertices: Vec<geom::PrimVertex>,
         lights: Vec<light::Light>,
         materials: Vec<geom::PrimMat>,
+        animation: animation::Animation,
     },
 }
 
@@ -38,10 +47,14 @@ impl<'de> serde::Deserialize<'de> for Scene {
             vertices: Vec<geom::PrimVertex>,
             lights: Vec<light::Light>,
             materials: Vec<geom::PrimMat>,
+            #[serde(default)]
+            animation: animation::Animation,
         }
 
-        impl From<Intermediate> for Scene {
-            fn from(value: Intermediate) -> Scene {
+        impl TryFrom<Intermediate> for Scene {
+            type Error = anyhow::Error;
+
+            fn try_from(value: Intermediate) -> anyhow::Result<Scene> {
                 let Intermediate {
                     camera,
                     camera_controller,
@@ -49,20 +62,27 @@ impl<'de> serde::Deserialize<'de> for Scene {
                     vertices,
                     lights,
                     materials,
+                    animation,
                 } = value;
 
-                Self::Active {
+                // The authored lights & materials are kept as they are,
+                // frames are applied once the scene is loaded (see `Scene::animate`)
+                animation.validate(lights.len(), materials.len())?;
+
+                Ok(Self::Active {
                     camera,
                     camera_controller,
                     prims,
                     vertices,
                     lights,
                     materials,
-                }
+                    animation,
+                })
             }
         }
 
-        Ok(Intermediate::deserialize(deserializer)?.into())
+        Scene::try_from(Intermediate::deserialize(deserializer)?)
+            .map_err(serde::de::Error::custom)
     }
 }
 
@@ -78,6 +98,13 @@ impl serde::Serialize for Scene {
             vertices: &'a [geom::PrimVertex],
             lights: &'a [light::Light],
             materials: &'a [geom::PrimMat],
+            #[serde(skip_serializing_if = "no_tracks")]
+            animation: &'a animation::Animation,
+        }
+
+        // Static scenes are written without an animation section
+        fn no_tracks(animation: &&animation::Animation) -> bool {
+            animation.tracks.is_empty()
         }
 
         #[allow(clippy::from_over_into)]
@@ -92,6 +119,7 @@ impl serde::Serialize for Scene {
                         vertices,
                         lights,
                         materials,
+                        animation,
                     } => Intermediate {
                         camera,
                         camera_controller,
@@ -99,6 +127,7 @@ impl serde::Serialize for Scene {
                         vertices,
                         lights,
                         materials,
+                        animation,
                     },
                 }
             }
@@ -125,12 +154,14 @@ impl Scene {
             vertices: vec![geom::PrimVertex::new(N3, N3)],
             lights: vec![light::Light { pos: N3, strength: 0., }],
             materials: vec![geom::PrimMat::new(N3, N3, 0.)],
+            animation: animation::Animation::default(),
         };
 
-        scene.pack(device)
+        scene.pack(device, None)
     }
 
-    pub fn pack(&self, device: &wgpu::Device) -> ScenePack {
+    // Handlers may supply the prims in their own order (see `IntrsPack::prims`)
+    pub fn pack(&self, device: &wgpu::Device, order: Option<&[geom::Prim]>) -> ScenePack {
         use wgpu::util::DeviceExt as _;
 
         let Scene::Active { 
@@ -163,7 +194,7 @@ impl Scene {
         ];
         
         // Then we add all the others
-        primitives.extend(prims.iter().copied());
+        primitives.extend(order.unwrap_or(prims).iter().copied());
 
         //
         // group(2) Scene Buffer and Groups
@@ -271,13 +302,23 @@ impl Scene {
         }
     }
 
+    // Writes the animation's current frame into the lights & materials
+    pub fn animate(&mut self) -> AnimationChanges {
+        let Scene::Active { 
+            lights, 
+            materials, 
+            animation, .. 
+        } = self else { return AnimationChanges::default(); };
+
+        animation.apply(lights, materials)
+    }
+
     pub fn add_mesh(
         &mut self, 
         obj: wavefront::Obj,
         material: i32,
+        config: geom::mesh::MeshConfig,
     ) -> anyhow::Result<()> {
-        use crate::geom::V3Ops as _;
-
         let Self::Active {
             vertices,
             prims, .. 
@@ -285,59 +326,20 @@ impl Scene {
             anyhow::bail!("Unable to add mesh to unloaded scene"); 
         };
 
-        let mut obj_normals = vec![vec![]; obj.positions().len()];
-        let mut obj_prims = vec![];
-
-        for [
-            (pa, na, idx_a), 
-            (pb, nb, idx_b), 
-            (pc, nc, idx_c)
-        ] in obj.triangles().map(|[a, b, c]| [
-                (a.position(), a.normal(), a.position_index()), 
-                (b.position(), b.normal(), b.position_index()), 
-                (c.position(), c.normal(), c.position_index()),
-        ]) {
-            let ab = pb.sub(pa);
-            let ac = pc.sub(pa);
-    
-            let normal = ab.cross(ac).normalize();
-            
-            obj_normals[idx_a].push(match na {
-                Some(normal) => normal,
-                None => normal.scale(pa.angle(pb, pc)),
-            });
-
-            obj_normals[idx_b].push(match nb {
-                Some(normal) => normal,
-                None => normal.scale(pb.angle(pc, pa)),
-            });
-
-            obj_normals[idx_c].push(match nc {
-                Some(normal) => normal,
-                None => normal.scale(pc.angle(pa, pb)),
-            });
-    
-            obj_prims.push(geom::Prim { 
-                indices: [
-                    (idx_a + vertices.len()) as u32, 
-                    (idx_b + vertices.len()) as u32,
-                    (idx_c + vertices.len()) as u32
-                ],
-                material,
-            });
-        }
-    
-        let normals = obj_normals.into_iter().map(|normal| {
-            normal.into_iter().fold([0.; 3], |n, c| n.add(c)).normalize()
-        }).collect::<Vec<_>>();
-
-        vertices.extend({
-            obj.positions().iter().enumerate().map(|(idx, pos)| {
-                geom::PrimVertex::new(*pos, normals[idx])
-            })
-        });
+        // Weld, cull and decimate before generating normals
+        let mut mesh = geom::mesh::Mesh::from_obj(&obj);
+            mesh.process(config);
+
+        let (mesh_vertices, mesh_indices) = mesh.assemble(config);
+
+        let offset = vertices.len() as u32;
+
+        prims.extend(mesh_indices.into_iter().map(|indices| geom::Prim {
+            indices: indices.map(|idx| idx + offset),
+            material,
+        }));
 
-        prims.append(&mut obj_prims);
+        vertices.extend(mesh_vertices);
 
         Ok(())
     }
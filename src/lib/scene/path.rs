@@ -0,0 +1,142 @@
+use super::camera::CameraUniform;
+
+#[derive(Clone, Copy)]
+#[derive(PartialEq, Eq)]
+#[derive(Default)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum CameraInterpolation {
+    Linear,
+    #[default]
+    CatmullRom,
+}
+
+#[derive(Clone, Copy)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Debug)]
+pub struct CameraKeyframe {
+    // Seconds since the start of the path
+    pub time: f32,
+    pub pos: [f32; 3],
+    pub at: [f32; 3],
+}
+
+// A sequence of keyframes that the camera follows.
+// Playback advances by one frame per compute pass (not wall-clock time),
+// so every run sees the exact same sequence of views
+#[derive(Clone)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Debug)]
+pub struct CameraPath {
+    pub keyframes: Vec<CameraKeyframe>,
+    #[serde(default)]
+    pub interpolation: CameraInterpolation,
+    // The number of frames played per second of path time
+    #[serde(default = "CameraPath::default_rate")]
+    pub rate: f32,
+    // Restart from the first keyframe once the path is complete
+    #[serde(default)]
+    pub looping: bool,
+}
+
+impl CameraPath {
+    const fn default_rate() -> f32 { 60. }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.keyframes.is_empty() {
+            anyhow::bail!("Camera path must contain at least one keyframe");
+        }
+
+        if self.keyframes.windows(2).any(|pair| pair[0].time > pair[1].time) {
+            anyhow::bail!("Camera path keyframes must be sorted by time");
+        }
+
+        if !(self.rate.is_finite() && self.rate > 0.) {
+            anyhow::bail!("Camera path rate must be positive");
+        }
+
+        Ok(())
+    }
+
+    // The number of distinct frames in a single playback
+    pub fn frames(&self) -> u32 {
+        let (Some(first), Some(last)) = (
+            self.keyframes.first(),
+            self.keyframes.last()
+        ) else { return 0; };
+
+        ((last.time - first.time) * self.rate).ceil() as u32 + 1
+    }
+
+    // Evaluate the camera at the given frame
+    pub fn sample(&self, frame: u32) -> Option<CameraUniform> {
+        let frames = self.frames();
+
+        if frames == 0 { return None; }
+
+        let frame = if self.looping {
+            frame % frames
+        } else {
+            frame.min(frames - 1)
+        };
+
+        let keyframes = &self.keyframes;
+
+        let time = keyframes[0].time + frame as f32 / self.rate;
+
+        // The segment [keyframes[idx], keyframes[idx + 1]] contains `time`
+        let idx = keyframes
+            .partition_point(|keyframe| keyframe.time <= time)
+            .saturating_sub(1)
+            .min(keyframes.len().saturating_sub(2));
+
+        let k1 = keyframes[idx];
+        let k2 = keyframes[(idx + 1).min(keyframes.len() - 1)];
+
+        let span = k2.time - k1.time;
+        let t = if span > 0. {
+            ((time - k1.time) / span).clamp(0., 1.)
+        } else {
+            0.
+        };
+
+        let (pos, at) = match self.interpolation {
+            CameraInterpolation::Linear => (
+                lerp(k1.pos, k2.pos, t),
+                lerp(k1.at, k2.at, t),
+            ),
+            CameraInterpolation::CatmullRom => {
+                // The end points are repeated
+                let k0 = keyframes[idx.saturating_sub(1)];
+                let k3 = keyframes[(idx + 2).min(keyframes.len() - 1)];
+
+                (
+                    catmull_rom([k0.pos, k1.pos, k2.pos, k3.pos], t),
+                    catmull_rom([k0.at, k1.at, k2.at, k3.at], t),
+                )
+            },
+        };
+
+        Some(CameraUniform::new(pos, at))
+    }
+}
+
+pub(super) fn lerp(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    [0, 1, 2].map(|i| a[i] + (b[i] - a[i]) * t)
+}
+
+// Uniform Catmull-Rom spline through p[1] and p[2]
+fn catmull_rom(p: [[f32; 3]; 4], t: f32) -> [f32; 3] {
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    [0, 1, 2].map(|i| {
+        let [p0, p1, p2, p3] = p.map(|p| p[i]);
+
+        0.5 * (2. * p1
+            + (p2 - p0) * t
+            + (2. * p0 - 5. * p1 + 4. * p2 - p3) * t2
+            + (3. * p1 - p0 - 3. * p2 + p3) * t3)
+    })
+}
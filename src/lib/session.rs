@@ -0,0 +1,211 @@
+use std::{fs, io, path};
+
+use winit::{event, keyboard};
+
+use crate::scene;
+
+// Everything that can move the camera or change the image
+#[derive(Clone)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Debug)]
+pub enum SessionEvent {
+    // Input consumed by `CameraController::handle_event`
+    Key { key: keyboard::Key, pressed: bool },
+    // A call to `CameraController::update` that moved the camera
+    Update { dt: f32 },
+    // A camera path moved on to its next frame
+    Advance,
+    Compute(crate::ComputeConfig),
+    Overlay(crate::OverlayConfig),
+    // The performance HUD was toggled
+    Hud(bool),
+    // A handler swap or comparison that succeeded (native only)
+    Handler(crate::handlers::IntrsKind),
+    Compare(Option<crate::CompareConfig>),
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Debug)]
+pub struct SessionEntry {
+    // Milliseconds since the session began
+    pub time: f64,
+    pub event: SessionEvent,
+}
+
+// A recorded interactive session
+// The starting state is stored so replays begin from the same view
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Debug)]
+pub struct SessionLog {
+    pub camera: scene::CameraUniform,
+    pub camera_controller: scene::CameraController,
+    pub compute: crate::ComputeConfig,
+    pub overlay: crate::OverlayConfig,
+    // Missing from sessions recorded before the HUD was tracked
+    #[serde(default)]
+    pub hud: bool,
+    pub entries: Vec<SessionEntry>,
+}
+
+impl SessionLog {
+    pub fn read<P: AsRef<path::Path>>(path: P) -> anyhow::Result<Self> {
+        let reader = io::BufReader::new(fs::File::open(path)?);
+
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+#[derive(Debug)]
+pub enum SessionMode {
+    // The session is written to the given path on exit
+    Record(path::PathBuf),
+    Replay(SessionLog),
+}
+
+#[derive(Debug)]
+pub(crate) enum Session {
+    Inactive,
+    Recording {
+        out: path::PathBuf,
+        start: Option<chrono::DateTime<chrono::Local>>,
+        log: SessionLog,
+    },
+    Replaying {
+        start: Option<chrono::DateTime<chrono::Local>>,
+        log: SessionLog,
+        cursor: usize,
+    },
+}
+
+impl Session {
+    // Replays overwrite the initial camera and config
+    pub fn new(
+        mode: Option<SessionMode>,
+        config: &mut crate::Config,
+        scene: &mut scene::Scene,
+    ) -> Self {
+        let scene::Scene::Active { camera, camera_controller, .. } = scene else {
+            if mode.is_some() {
+                log::warn!("Sessions can't be recorded or replayed without a scene");
+            }
+
+            return Self::Inactive;
+        };
+
+        match mode {
+            None => Self::Inactive,
+            Some(SessionMode::Record(out)) => Self::Recording {
+                out,
+                start: None,
+                log: SessionLog {
+                    camera: *camera,
+                    camera_controller: camera_controller.clone(),
+                    compute: config.compute,
+                    overlay: config.overlay,
+                    hud: config.hud,
+                    entries: Vec::new(),
+                },
+            },
+            Some(SessionMode::Replay(log)) => {
+                *camera = log.camera;
+                *camera_controller = log.camera_controller.clone();
+
+                config.compute = log.compute;
+                config.overlay = log.overlay;
+                config.hud = log.hud;
+
+                log::info!("Replaying session ({} events)", log.entries.len());
+
+                Self::Replaying { start: None, log, cursor: 0 }
+            },
+        }
+    }
+
+    // Live camera input is ignored during replays
+    pub fn replaying(&self) -> bool {
+        matches!(self, Self::Replaying { .. })
+    }
+
+    fn elapsed(start: &mut Option<chrono::DateTime<chrono::Local>>) -> f64 {
+        let now = chrono::Local::now();
+
+        let elapsed = now.signed_duration_since(*start.get_or_insert(now));
+
+        elapsed
+            .num_microseconds()
+            .map(|micros| 0.001 * micros as f64)
+            .unwrap_or(elapsed.num_milliseconds() as f64)
+    }
+
+    // Called once per iteration of the event loop
+    // Returns the events that are due to be replayed
+    pub fn poll(&mut self) -> Vec<SessionEvent> {
+        match self {
+            Self::Inactive => Vec::new(),
+            Self::Recording { start, .. } => {
+                let _ = Self::elapsed(start);
+
+                Vec::new()
+            },
+            Self::Replaying { start, log, cursor } => {
+                if *cursor == log.entries.len() { return Vec::new(); }
+
+                let time = Self::elapsed(start);
+
+                let due = log.entries[*cursor..]
+                    .iter()
+                    .take_while(|entry| entry.time <= time)
+                    .map(|entry| entry.event.clone())
+                    .collect::<Vec<_>>();
+
+                *cursor += due.len();
+
+                if *cursor == log.entries.len() {
+                    log::info!("Session replay complete");
+                }
+
+                due
+            },
+        }
+    }
+
+    pub fn record(&mut self, event: SessionEvent) {
+        if let Self::Recording { start, log, .. } = self {
+            let time = Self::elapsed(start);
+
+            log.entries.push(SessionEntry { time, event });
+        }
+    }
+
+    // Records a window event that was handled by the camera controller
+    pub fn record_input(&mut self, event: &event::WindowEvent) {
+        if let event::WindowEvent::KeyboardInput {
+            event: event::KeyEvent {
+                logical_key,
+                state, ..
+            }, ..
+        } = event {
+            self.record(SessionEvent::Key {
+                key: logical_key.clone(),
+                pressed: state.is_pressed(),
+            });
+        }
+    }
+
+    // Writes the recorded session to disk
+    pub fn finish(self) -> anyhow::Result<()> {
+        if let Self::Recording { out, log, .. } = self {
+            if let Some(parent) = out.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            let writer = io::BufWriter::new(fs::File::create(&out)?);
+
+            serde_json::to_writer(writer, &log)?;
+
+            log::info!("Saved session ({} events) to {}", log.entries.len(), out.display());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+// Draws the output of two handlers side-by-side (split),
+// or the first handler's output with their difference on top (diff)
+
+struct VertexInput {
+    @location(0) pos: vec2<f32>
+};
+
+struct VertexOutput {
+    @builtin(position) clip_pos: vec4<f32>,
+    @location(0) tex: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var tex_a: texture_2d<f32>;
+
+@group(0) @binding(1)
+var tex_b: texture_2d<f32>;
+
+struct Size {
+    width: u32,
+    height: u32
+}
+
+@group(0) @binding(2)
+var<uniform> size: Size;
+
+// NOTE: Must match `CompareMode`
+const MODE_SPLIT: u32 = 0u;
+const MODE_DIFF: u32 = 1u;
+
+struct Compare {
+    mode: u32,
+}
+
+@group(0) @binding(3)
+var<uniform> compare: Compare;
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    out.clip_pos = vec4<f32>(in.pos, 0.0, 1.0);
+    out.tex = (in.pos + 1.0) * 0.5;
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let x: i32 = i32(in.tex.x * f32(size.width));
+    let y: i32 = i32(in.tex.y * f32(size.height));
+
+    let a = textureLoad(tex_a, vec2<i32>(x, y), 0);
+    let b = textureLoad(tex_b, vec2<i32>(x, y), 0);
+
+    if(compare.mode == MODE_DIFF) {
+        // Any mismatch stands out against the dimmed image
+        let diff = abs(a.rgb - b.rgb) * 4.0;
+
+        return vec4<f32>(a.rgb * 0.25 + diff, 1.0);
+    }
+
+    // A single column divides the halves
+    let divider = i32(size.width / 2u);
+    if(x == divider) {
+        return vec4<f32>(1.0);
+    }
+
+    return select(a, b, x > divider);
+}
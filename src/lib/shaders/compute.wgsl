@@ -28,6 +28,7 @@ struct Config {
     bounces: u32,
     eps: f32,
     ambience: f32,
+    debug: u32,
 }
 
 @group(1) @binding(0)
@@ -97,6 +98,31 @@ struct Hit {
     t: f32,
 }
 
+//
+// Instrumentation
+
+// Handlers should call `count_node` on each node visit
+// and `count_test` on each primitive intersection test
+struct Count {
+    rays: u32,
+    nodes: u32,
+    tests: u32,
+    shadows: u32,
+    bounces: u32,
+}
+
+var<private> count: Count;
+
+fn count_ray() { count.rays += 1u; }
+fn count_node() { count.nodes += 1u; }
+fn count_test() { count.tests += 1u; }
+fn count_shadow() { count.shadows += 1u; count.rays += 1u; }
+fn count_bounce() { count.bounces += 1u; }
+
+// NOTE: This does nothing unless the Scheduler requests
+// an instrumented shader variant, in which case it is replaced
+fn count_flush(row: u32) {}
+
 //
 // Raytracer
 
@@ -117,13 +143,13 @@ fn camera_ray(coord: vec2<i32>) -> Ray {
     return Ray(camera.pos, normalize(pt - camera.pos));
 }
 
-fn hit(intrs: Intrs, r: Ray) -> Hit {
-    let at: vec3<f32> = r.origin + (r.dir * intrs.t);
+// The weights of the vertices `s.a`, `s.b` and `s.c` at the given point
+fn barycentric(s: Prim, at: vec3<f32>) -> vec3<f32> {
     // NOTE: As of now, 
     // I have no explanation for why these need to be flipped...
-    let b: vec3<f32> = vertices[intrs.s.a].pos;
-    let c: vec3<f32> = vertices[intrs.s.b].pos;
-    let a: vec3<f32> = vertices[intrs.s.c].pos;
+    let b: vec3<f32> = vertices[s.a].pos;
+    let c: vec3<f32> = vertices[s.b].pos;
+    let a: vec3<f32> = vertices[s.c].pos;
 
     let v0: vec3<f32> = b - a;
     let v1: vec3<f32> = c - a;
@@ -141,9 +167,17 @@ fn hit(intrs: Intrs, r: Ray) -> Hit {
     let w: f32 = (d00 * d21 - d01 * d20) / denom;
     let u: f32 = 1.0 - v - w;
 
-    let na: vec3<f32> = vertices[intrs.s.a].normal * v;
-    let nb: vec3<f32> = vertices[intrs.s.b].normal * w;
-    let nc: vec3<f32> = vertices[intrs.s.c].normal * u;
+    return vec3<f32>(v, w, u);
+}
+
+fn hit(intrs: Intrs, r: Ray) -> Hit {
+    let at: vec3<f32> = r.origin + (r.dir * intrs.t);
+
+    let weights: vec3<f32> = barycentric(intrs.s, at);
+
+    let na: vec3<f32> = vertices[intrs.s.a].normal * weights.x;
+    let nb: vec3<f32> = vertices[intrs.s.b].normal * weights.y;
+    let nc: vec3<f32> = vertices[intrs.s.c].normal * weights.z;
 
     let normal = normalize(na + nb + nc);
 
@@ -199,6 +233,8 @@ fn shadowed(pack: LightingPack) -> bool {
 
     let shadow_ray: Ray = Ray(shadow_origin, light_dir);
 
+    count_shadow();
+
     let shadow_intrs = intrs(shadow_ray, pack.hit.s);
     if(intrs_valid(shadow_intrs)) {
         let shadow_hit = hit(shadow_intrs, shadow_ray);
@@ -222,9 +258,13 @@ fn lighting(camera_ray: Ray) -> vec3<f32> {
     var color: vec3<f32> = vec3<f32>(0.0);
 
     for(var i: u32 = 0u; i < config.bounces; i = i + 1u) {
+        count_ray();
+
         let intrs: Intrs = intrs(ray, primitives[0]);
         if(!intrs_valid(intrs)) { break; }
 
+        count_bounce();
+
         let material: Material = materials[intrs.s.material];
 
         let hit = hit(intrs, ray);
@@ -279,6 +319,84 @@ fn lighting(camera_ray: Ray) -> vec3<f32> {
     return color;
 }
 
+//
+// Debug Visualisation
+
+// NOTE: These values must match `DebugMode`
+const DEBUG_SHADED: u32 = 0u;
+const DEBUG_NORMALS: u32 = 1u;
+const DEBUG_DEPTH: u32 = 2u;
+const DEBUG_PRIM: u32 = 3u;
+const DEBUG_MATERIAL: u32 = 4u;
+const DEBUG_BARYCENTRICS: u32 = 5u;
+const DEBUG_HEATMAP: u32 = 6u;
+
+// Scatters neighboring IDs across the color space
+fn debug_hash(x: u32) -> u32 {
+    var h: u32 = x;
+        h ^= h >> 16u;
+        h *= 0x7feb352du;
+        h ^= h >> 15u;
+        h *= 0x846ca68bu;
+        h ^= h >> 16u;
+
+    return h;
+}
+
+fn debug_color(id: u32) -> vec3<f32> {
+    let h = debug_hash(id);
+
+    return vec3<f32>(
+        f32(h & 0xFFu), 
+        f32((h >> 8u) & 0xFFu), 
+        f32((h >> 16u) & 0xFFu),
+    ) / 255.0;
+}
+
+// Ramps from blue (cheap) through green to red (expensive)
+fn debug_heat(t: f32) -> vec3<f32> {
+    let r = clamp(2.0 * t - 1.0, 0.0, 1.0);
+    let g = 1.0 - abs(2.0 * t - 1.0);
+    let b = clamp(1.0 - 2.0 * t, 0.0, 1.0);
+
+    return vec3<f32>(r, g, b);
+}
+
+// Only the camera ray is traced in debug modes
+fn debug(camera_ray: Ray) -> vec3<f32> {
+    count_ray();
+
+    let intrs: Intrs = intrs(camera_ray, primitives[0]);
+
+    // Misses still have a traversal cost, so they are included.
+    // The scale saturates at 4096 node visits + intersection tests
+    if(config.debug == DEBUG_HEATMAP) {
+        let cost = f32(count.nodes + count.tests);
+
+        return debug_heat(log2(1.0 + cost) / 12.0);
+    }
+
+    if(!intrs_valid(intrs)) { return vec3<f32>(0.0); }
+
+    let hit = hit(intrs, camera_ray);
+
+    if(config.debug == DEBUG_NORMALS) {
+        return hit.normal * 0.5 + 0.5;
+    } else if(config.debug == DEBUG_DEPTH) {
+        return vec3<f32>(1.0 - log(1.0 + hit.t) / log(1.0 + config.t_max));
+    } else if(config.debug == DEBUG_PRIM) {
+        let s = intrs.s;
+
+        return debug_color(s.a ^ debug_hash(s.b ^ debug_hash(s.c)));
+    } else if(config.debug == DEBUG_MATERIAL) {
+        return debug_color(u32(intrs.s.material));
+    } else if(config.debug == DEBUG_BARYCENTRICS) {
+        return barycentric(intrs.s, hit.at);
+    }
+
+    return vec3<f32>(0.0);
+}
+
 // NOTE: The workgroup size is effected by config options, 
 // the x & y values are replaced at runtime
 @compute @workgroup_size(16, 16, 1)
@@ -286,9 +404,16 @@ fn main_cs(@builtin(global_invocation_id) id: vec3<u32>) {
     if(id.x < size.width && id.y < size.height) {
         let coord: vec2<i32> = vec2<i32>(i32(id.x), i32(id.y));
 
-        let color: vec3<f32> = lighting(camera_ray(coord));
+        var color: vec3<f32>;
+        if(config.debug == DEBUG_SHADED) {
+            color = lighting(camera_ray(coord));
+        } else {
+            color = debug(camera_ray(coord));
+        }
 
         textureStore(out, coord, vec4<f32>(color, 1.0));
+
+        count_flush(id.y);
     }
 }
 
@@ -0,0 +1,64 @@
+// NOTE: This won't compile on its own.
+// Ray tests shared by the `IntrsHandler`s,
+// inserted ahead of their logic by `shaders::source`
+
+fn intrs_tri(r: Ray, s: Prim) -> Intrs {
+    count_test();
+
+    let e1: vec3<f32> = vertices[s.b].pos - vertices[s.a].pos;
+    let e2: vec3<f32> = vertices[s.c].pos - vertices[s.a].pos;
+
+    let p: vec3<f32> = cross(r.dir, e2);
+    let t: vec3<f32> = r.origin - vertices[s.a].pos;
+    let q: vec3<f32> = cross(t, e1);
+
+    let det = dot(e1, p);
+
+    var u: f32 = 0.0;
+    var v: f32 = 0.0;
+    if(det > config.eps) {
+        u = dot(t, p);
+        if(u < 0.0 || u > det) { return intrs_empty(); }
+
+        v = dot(r.dir, q);
+        if(v < 0.0 || u + v > det) { return intrs_empty(); }
+    } else if(det < -1.0 * config.eps) {
+        u = dot(t, p);
+        if(u > 0.0 || u < det) { return intrs_empty(); }
+
+        v = dot(r.dir, q);
+        if(v > 0.0 || u + v < det) { return intrs_empty(); }
+    } else {
+        return intrs_empty();
+    }
+
+    let w: f32 = dot(e2, q) / det;
+    
+    if(w > config.t_max || w < config.t_min) {
+        return intrs_empty();
+    } else {
+        return Intrs(s, w);
+    }
+}
+
+// Wobble for the box test below
+const EPS: f32 = 0.000002;
+
+// Direction components smaller than this are clamped,
+// dividing by zero isn't consistent across backends
+const DIR_MIN: f32 = 0x1.p-40f;
+
+fn collides(lo: vec3<f32>, hi: vec3<f32>, ray: Ray) -> bool {
+    let dir = select(ray.dir, vec3<f32>(DIR_MIN), abs(ray.dir) < vec3<f32>(DIR_MIN));
+
+    let t0s = (lo - EPS - ray.origin) / dir;
+    let t1s = (hi + EPS - ray.origin) / dir;
+
+    let t_mins = min(t0s, t1s);
+    let t_maxs = max(t0s, t1s);
+
+    let t_min = max(config.t_min, max(t_mins.x, max(t_mins.y, t_mins.z)));
+    let t_max = min(config.t_max, min(t_maxs.x, min(t_maxs.y, t_maxs.z)));
+
+    return (t_min <= t_max);
+}
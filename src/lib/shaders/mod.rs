@@ -7,8 +7,14 @@ pub enum ShaderStage<'a, 'b: 'a> {
         wg: u32, 
         pack: &'a handlers::IntrsPack<'b>,
         logic: &'a str,
+        // Counters are written to group(1), see `timing::Scheduler::instrumented`
+        instrument: bool,
     },
     Render,
+    Overlay,
+    Refit,
+    Compare,
+    Text,
 }
 
 pub fn source<'a, 'b: 'a>(
@@ -32,7 +38,19 @@ pub fn source<'a, 'b: 'a>(
         ShaderStage::Render => { //
             include_str!("render.wgsl").into()
         },
-        ShaderStage::Compute { wg, pack, logic, .. } => {
+        ShaderStage::Overlay => { //
+            include_str!("overlay.wgsl").into()
+        },
+        ShaderStage::Refit => { //
+            include_str!("refit.wgsl").into()
+        },
+        ShaderStage::Compare => { //
+            include_str!("compare.wgsl").into()
+        },
+        ShaderStage::Text => { //
+            include_str!("text.wgsl").into()
+        },
+        ShaderStage::Compute { wg, pack, logic, instrument } => {
             let source: &'static str = include_str!("compute.wgsl");
 
             let source = source.replace(
@@ -74,8 +92,16 @@ pub fn source<'a, 'b: 'a>(
                 source.insert_str(main_cs_idx(&source)?, &binding);
             }
 
-            // Add the intersection logic
-            let source = source.replace(LOGIC_DEFAULT, logic);
+            // Add the shared ray tests, followed by the intersection logic
+            let mut source = source.replace(LOGIC_DEFAULT, &format!("{}\n{}", 
+                include_str!("intrs.wgsl"), 
+                logic,
+            ));
+
+            // Swap the empty hooks out for the instrumented variant
+            if instrument {
+                source = source.replace(COUNTERS_DEFAULT, COUNTERS);
+            }
 
             borrow::Cow::Borrowed({
                 Box::leak(source.into_boxed_str())
@@ -87,4 +113,37 @@ pub fn source<'a, 'b: 'a>(
 }
 
 const LOGIC_DEFAULT: &str = //
-    "fn intrs(ray: Ray, excl: Prim) -> Intrs { return intrs_empty(); }";
\ No newline at end of file
+    "fn intrs(ray: Ray, excl: Prim) -> Intrs { return intrs_empty(); }";
+
+const COUNTERS_DEFAULT: &str = //
+    "fn count_flush(row: u32) {}";
+
+// NOTE: The layout of `Counters` must match `timing::BenchCounts`
+const COUNTERS: &str = "\
+struct Counters {
+    pixels: atomic<u32>,
+    rays: atomic<u32>,
+    nodes: atomic<u32>,
+    tests: atomic<u32>,
+    shadows: atomic<u32>,
+    bounces: atomic<u32>,
+    depth: atomic<u32>,
+}
+
+// Each row of pixels accumulates into its own entry,
+// which keeps the totals well within u32
+@group(1) @binding(1)
+var<storage, read_write> counters: array<Counters>;
+
+fn count_flush(row: u32) {
+    // The window may have grown since the buffer was allocated
+    let idx = row % arrayLength(&counters);
+
+    atomicAdd(&counters[idx].pixels, 1u);
+    atomicAdd(&counters[idx].rays, count.rays);
+    atomicAdd(&counters[idx].nodes, count.nodes);
+    atomicAdd(&counters[idx].tests, count.tests);
+    atomicAdd(&counters[idx].shadows, count.shadows);
+    atomicAdd(&counters[idx].bounces, count.bounces);
+    atomicMax(&counters[idx].depth, count.bounces);
+}";
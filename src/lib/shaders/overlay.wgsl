@@ -0,0 +1,103 @@
+// Draws the bounds of each BVH node as a wireframe box.
+// Each instance is a single node, each pair of vertices is an edge
+
+struct Camera { pos: vec3<f32>, at: vec3<f32>, }
+
+@group(0) @binding(0)
+var<uniform> camera: Camera;
+
+struct Overlay {
+    depth_min: u32,
+    depth_max: u32,
+    leaves: u32,
+}
+
+@group(0) @binding(1)
+var<uniform> overlay: Overlay;
+
+struct BoxInput {
+    @location(0) min: vec3<f32>,
+    @location(1) depth: u32,
+    @location(2) max: vec3<f32>,
+    @location(3) leaf: u32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_pos: vec4<f32>,
+    @location(0) color: vec3<f32>,
+}
+
+// Inverts the basis constructed by `camera_ray` in the compute shader,
+// so boxes line up with the ray-traced image
+fn project(p: vec3<f32>) -> vec4<f32> {
+    let dir = normalize(camera.at - camera.pos);
+
+    let up = vec3<f32>(0., 1.0, 0.0);
+    let right = cross(dir, up);
+
+    let det = dot(right, cross(up, dir));
+
+    let d = p - camera.pos;
+
+    let c = vec3<f32>(
+        dot(d, cross(up, dir)),
+        dot(d, cross(dir, right)),
+        dot(d, cross(right, up)),
+    ) / det;
+
+    // Points behind the camera have z < 0, so they are clipped
+    return vec4<f32>(2.0 * c.x, 2.0 * c.y, 0.5 * c.z, c.z);
+}
+
+// Each depth of the tree gets its own hue
+fn hue(h: f32) -> vec3<f32> {
+    let k = fract(h + vec3<f32>(0.0, 2.0 / 3.0, 1.0 / 3.0));
+
+    return clamp(abs(k * 6.0 - 3.0) - 1.0, vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) idx: u32,
+    bb: BoxInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+
+    var visible = bb.depth >= overlay.depth_min && bb.depth <= overlay.depth_max;
+    if(overlay.leaves == 1u) {
+        visible &= bb.leaf == 1u;
+    }
+
+    // Both ends of the edge are clipped
+    if(!visible) {
+        out.clip_pos = vec4<f32>(0.0, 0.0, -1.0, 1.0);
+        out.color = vec3<f32>(0.0);
+
+        return out;
+    }
+
+    // Bits of each corner select min (0) or max (1) along x, y & z
+    var edges = array<u32, 24>(
+        0u, 1u, 2u, 3u, 4u, 5u, 6u, 7u,
+        0u, 2u, 1u, 3u, 4u, 6u, 5u, 7u,
+        0u, 4u, 1u, 5u, 2u, 6u, 3u, 7u,
+    );
+
+    let corner = edges[idx % 24u];
+
+    let p = select(bb.min, bb.max, vec3<bool>(
+        (corner & 1u) != 0u,
+        (corner & 2u) != 0u,
+        (corner & 4u) != 0u,
+    ));
+
+    out.clip_pos = project(p);
+    out.color = hue(f32(bb.depth) * 0.13);
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(in.color, 1.0);
+}
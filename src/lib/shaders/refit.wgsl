@@ -0,0 +1,92 @@
+// Recomputes the bounds of every BVH node from the current vertices.
+// Dispatched once per level of the tree, starting from the deepest,
+// so both children of a node are always refit before the node itself
+
+struct Bounds {
+    min: vec3<f32>,
+    max: vec3<f32>,
+}
+
+// Mirrors `bvh::AabbUniform`
+struct Aabb {
+    fst: u32,
+    snd: u32,
+    item_idx: u32,
+    item_count: u32,
+    bounds: Bounds,
+}
+
+// The handler's node type, only the trailing bounds are written
+struct Node {
+    links: vec4<u32>,
+    bounds: Bounds,
+}
+
+struct Prim {
+    a: u32,
+    b: u32,
+    c: u32,
+    material: i32,
+}
+
+struct Vertex {
+    pos: vec3<f32>,
+    normal: vec3<f32>,
+}
+
+// The range of `order` covered by a single dispatch
+struct Level {
+    start: u32,
+    count: u32,
+}
+
+@group(0) @binding(0)
+var<storage, read_write> nodes: array<Node>;
+
+@group(0) @binding(1)
+var<storage, read> topology: array<Aabb>;
+
+@group(0) @binding(2)
+var<storage, read> order: array<u32>;
+
+@group(0) @binding(3)
+var<storage, read> primitives: array<Prim>;
+
+@group(0) @binding(4)
+var<storage, read> vertices: array<Vertex>;
+
+@group(0) @binding(5)
+var<uniform> level: Level;
+
+@compute @workgroup_size(64, 1, 1)
+fn main_cs(@builtin(global_invocation_id) id: vec3<u32>) {
+    if(id.x >= level.count) { return; }
+
+    let idx = order[level.start + id.x];
+    let node = topology[idx];
+
+    var lo = vec3<f32>(3.40282347e+38);
+    var hi = vec3<f32>(-3.40282347e+38);
+
+    if(node.fst == 0u && node.snd == 0u) {
+        for(var i: u32 = node.item_idx; i < (node.item_idx + node.item_count); i = i + 1u) {
+            // The first primitive is the 'null' prim
+            let prim = primitives[i + 1u];
+
+            let a = vertices[prim.a].pos;
+            let b = vertices[prim.b].pos;
+            let c = vertices[prim.c].pos;
+
+            lo = min(lo, min(a, min(b, c)));
+            hi = max(hi, max(a, max(b, c)));
+        }
+    } else {
+        let fst = nodes[node.fst].bounds;
+        let snd = nodes[node.snd].bounds;
+
+        lo = min(fst.min, snd.min);
+        hi = max(fst.max, snd.max);
+    }
+
+    nodes[idx].bounds = Bounds(lo, hi);
+}
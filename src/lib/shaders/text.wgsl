@@ -0,0 +1,95 @@
+// Draws lines of text from a 5x7 bitmap font.
+// Each instance is a single character cell, drawn as a quad
+
+struct Screen {
+    size: vec2<f32>,
+    scale: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> screen: Screen;
+
+// Two words per glyph, rows 0-3 in the first and rows 4-6 in the second.
+// Each row uses the low 5 bits of its byte, the leftmost column is 0x10
+@group(0) @binding(1)
+var<uniform> font: array<vec4<u32>, 32>;
+
+// NOTE: Must match `GLYPH_SOLID`
+const GLYPH_SOLID: u32 = 64u;
+
+// Must match `Text::CELL`
+const CELL: vec2<f32> = vec2<f32>(6.0, 9.0);
+
+struct GlyphInput {
+    @location(0) pos: vec2<f32>,
+    @location(1) glyph: u32,
+    @location(2) color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_pos: vec4<f32>,
+    // Position within the cell (unscaled pixels)
+    @location(0) cell: vec2<f32>,
+    @location(1) @interpolate(flat) glyph: u32,
+    @location(2) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) idx: u32,
+    in: GlyphInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+    );
+
+    let corner = corners[idx % 6u];
+
+    // Pixels are measured from the top-left of the screen
+    let px = in.pos + corner * CELL * screen.scale;
+
+    out.clip_pos = vec4<f32>(
+        px.x / screen.size.x * 2.0 - 1.0,
+        1.0 - px.y / screen.size.y * 2.0,
+        0.0,
+        1.0,
+    );
+    out.cell = corner * CELL;
+    out.glyph = in.glyph;
+    out.color = in.color;
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    if(in.glyph == GLYPH_SOLID) {
+        return in.color;
+    }
+
+    // Glyphs are inset by a pixel from the top-left of their cell
+    let col = i32(floor(in.cell.x)) - 1;
+    let row = i32(floor(in.cell.y)) - 1;
+
+    if(col < 0 || col > 4 || row < 0 || row > 6) {
+        discard;
+    }
+
+    let word_idx = in.glyph * 2u + u32(row / 4);
+    let word = font[word_idx / 4u][word_idx % 4u];
+
+    let bits = (word >> (u32(row % 4) * 8u)) & 0xFFu;
+
+    if((bits & (0x10u >> u32(col))) == 0u) {
+        discard;
+    }
+
+    return in.color;
+}
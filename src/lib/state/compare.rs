@@ -0,0 +1,536 @@
+use wgpu::util::DeviceExt as _;
+use winit::dpi;
+
+use crate::{handlers, pipelines, scene, shaders, timer, vertex};
+
+use super::package;
+
+// How the two handlers' outputs are combined on screen
+// NOTE: The discriminants must match the MODE_* constants in `compare.wgsl`
+#[repr(u32)]
+#[derive(Clone, Copy)]
+#[derive(PartialEq, Eq)]
+#[derive(Default)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareMode {
+    // The primary handler on the left, the compared handler on the right
+    #[default]
+    Split = 0,
+    // The primary handler, with any differences highlighted
+    Diff = 1,
+}
+
+impl CompareMode {
+    pub const fn next(self) -> Self {
+        match self {
+            CompareMode::Split => CompareMode::Diff,
+            CompareMode::Diff => CompareMode::Split,
+        }
+    }
+}
+
+// Renders the same scene & camera with a second handler
+#[derive(Clone, Copy)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Debug)]
+pub struct CompareConfig {
+    pub handler: handlers::IntrsKind,
+    #[serde(default)]
+    pub mode: CompareMode,
+}
+
+// Mirrors `Compare` in `compare.wgsl`
+#[repr(C)]
+#[derive(bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Clone, Copy)]
+struct CompareUniform {
+    mode: u32,
+    _p0: [u32; 3],
+}
+
+impl From<CompareMode> for CompareUniform {
+    fn from(value: CompareMode) -> Self {
+        Self { mode: value as u32, _p0: [0; 3] }
+    }
+}
+
+// Everything the comparison shares with the primary pipeline
+#[derive(Clone, Copy)]
+pub struct CompareShared<'a> {
+    pub device: &'a wgpu::Device,
+    pub tex_format: wgpu::TextureFormat,
+    pub tex_view_format: wgpu::TextureFormat,
+    // The primary handler's output
+    pub texture: &'a wgpu::Texture,
+    pub size: dpi::PhysicalSize<u32>,
+    pub size_buffer: &'a wgpu::Buffer,
+    pub config_group_layout: &'a wgpu::BindGroupLayout,
+    pub scene_group_layout: &'a wgpu::BindGroupLayout,
+}
+
+// A second compute pipeline, built from another handler.
+// Its output is drawn next to (or blended with) the primary handler's
+#[derive(Debug)]
+pub struct Compare {
+    config: CompareConfig,
+
+    pack_vars: handlers::IntrsPack<'static>,
+    pack_stats: handlers::IntrsStats,
+
+    // Handlers may order the scene's prims differently,
+    // in which case the scene group is rebuilt around its own prims
+    // NOTE: Otherwise the primary scene group is shared
+    prims: Option<wgpu::Buffer>,
+    scene_group: Option<wgpu::BindGroup>,
+
+    shader_compute: wgpu::ShaderModule,
+
+    texture: wgpu::Texture,
+    compute_group: wgpu::BindGroup,
+    compute_pipeline: wgpu::ComputePipeline,
+
+    mode_buffer: wgpu::Buffer,
+    render_group_layout: wgpu::BindGroupLayout,
+    render_group: wgpu::BindGroup,
+    render_pipeline: wgpu::RenderPipeline,
+
+    // Times the comparison's compute pass
+    timer: timer::PassTimer,
+}
+
+impl Compare {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        shared: CompareShared,
+        queue: &wgpu::Queue,
+        wg: u32,
+        config: CompareConfig,
+        scene: &scene::Scene,
+        // The prims as ordered by the primary handler (see `IntrsPack::prims`)
+        order_primary: Option<&[crate::geom::Prim]>,
+        scene_camera_buffer: &wgpu::Buffer,
+        scene_buffers: &[wgpu::Buffer],
+    ) -> anyhow::Result<Self> {
+        let CompareShared {
+            device,
+            tex_format,
+            scene_group_layout, ..
+        } = shared;
+
+        let handler = config.handler.build()?;
+
+        let (pack_vars, pack_stats) = handler.vars(scene, device)?;
+
+        // The primary's scene group can be shared if both handlers order prims alike
+        let prims = match scene {
+            scene::Scene::Active { prims, .. } => {
+                let order = pack_vars.prims.as_deref().unwrap_or(prims);
+                let order_primary = order_primary.unwrap_or(prims);
+
+                let reordered = bytemuck::cast_slice::<_, u8>(order) != //
+                    bytemuck::cast_slice::<_, u8>(order_primary);
+
+                reordered.then(|| {
+                    // Matches the layout of `Scene::pack`, including the 'null' prim
+                    let mut contents = vec![crate::geom::Prim { indices: [0; 3], material: -1 }];
+                    contents.extend(order.iter().copied());
+
+                    device.create_buffer_init(
+                        &wgpu::util::BufferInitDescriptor {
+                            label: None,
+                            contents: bytemuck::cast_slice(&contents),
+                            usage: scene_buffers[0].usage(),
+                        }
+                    )
+                })
+            },
+            scene::Scene::Unloaded => None,
+        };
+
+        let scene_group = prims.as_ref().map(|prims| {
+            Self::scene_group(device, scene_group_layout, scene_camera_buffer, prims, scene_buffers)
+        });
+
+        // Counters are only written by the primary pipeline
+        let shader_compute = device.create_shader_module(
+            wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: shaders::source(shaders::ShaderStage::Compute {
+                    wg,
+                    pack: &pack_vars,
+                    logic: handler.logic(),
+                    instrument: false,
+                })?,
+            },
+        );
+
+        let shader_render = device.create_shader_module(
+            wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: shaders::source(shaders::ShaderStage::Compare)?,
+            },
+        );
+
+        let (texture, compute_group, compute_pipeline) = //
+            Self::compute(shared, &shader_compute, &pack_vars);
+
+        let mode_buffer = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&[CompareUniform::from(config.mode)]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            }
+        );
+
+        let tex_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float {
+                    filterable: false
+                },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None
+        };
+
+        let uniform_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            count: None,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            }
+        };
+
+        let render_group_layout = device.create_bind_group_layout(
+            &wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[
+                    tex_entry(0),
+                    tex_entry(1),
+                    uniform_entry(2),
+                    uniform_entry(3),
+                ],
+            }
+        );
+
+        let render_group = Self::render_group(
+            shared,
+            &render_group_layout,
+            &texture,
+            &mode_buffer
+        );
+
+        let render_pipeline_layout = device.create_pipeline_layout(
+            &wgpu::PipelineLayoutDescriptor {
+                label: None,
+                push_constant_ranges: &[],
+                bind_group_layouts: &[&render_group_layout],
+            }
+        );
+
+        // The SRGB texture view isn't available on web
+        cfg_if::cfg_if! {
+            if #[cfg(target_arch = "wasm32")] {
+                let fragment_format = tex_format;
+            } else {
+                let fragment_format = tex_format.add_srgb_suffix();
+            }
+        }
+
+        let render_pipeline = device.create_render_pipeline(
+            &wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: Some(&render_pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader_render,
+                    entry_point: "vs_main",
+                    buffers: &[vertex::Vertex::description()],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader_render,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: fragment_format,
+                        blend: Some(wgpu::BlendState::REPLACE),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: Some(wgpu::Face::Back),
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+            }
+        );
+
+        Ok(Self {
+            config,
+            pack_vars,
+            pack_stats,
+            prims,
+            scene_group,
+            shader_compute,
+            texture,
+            compute_group,
+            compute_pipeline,
+            mode_buffer,
+            render_group_layout,
+            render_group,
+            render_pipeline,
+            timer: timer::PassTimer::new(device, queue),
+        })
+    }
+
+    fn scene_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        camera: &wgpu::Buffer,
+        prims: &wgpu::Buffer,
+        scene_buffers: &[wgpu::Buffer],
+    ) -> wgpu::BindGroup {
+        let entries: Vec<wgpu::BindGroupEntry> = [camera, prims]
+            .into_iter()
+            .chain(scene_buffers.iter().skip(1))
+            .enumerate()
+            .map(|(binding, buffer)| wgpu::BindGroupEntry {
+                binding: binding as u32,
+                resource: buffer.as_entire_binding(),
+            }).collect();
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout,
+            entries: entries.as_slice(),
+        })
+    }
+
+    fn compute(
+        shared: CompareShared,
+        shader_compute: &wgpu::ShaderModule,
+        pack_vars: &handlers::IntrsPack,
+    ) -> (wgpu::Texture, wgpu::BindGroup, wgpu::ComputePipeline) {
+        let CompareShared {
+            device,
+            tex_format,
+            tex_view_format,
+            size,
+            size_buffer,
+            config_group_layout,
+            scene_group_layout, ..
+        } = shared;
+
+        let texture = package::PipelinePackage::texture(device, tex_format, tex_view_format, size);
+
+        let tex_view = package::PipelinePackage::view(&texture, tex_format);
+
+        let handlers::IntrsPack { vars, layout, .. } = pack_vars;
+
+        let layouts = if vars.is_empty() {
+            vec![config_group_layout, scene_group_layout]
+        } else {
+            vec![config_group_layout, scene_group_layout, layout]
+        };
+
+        let builder = pipelines::PipelineBuilder {
+            device,
+            tex_format,
+            tex_view: &tex_view,
+            module: shader_compute,
+            size: size_buffer,
+            layouts: layouts.as_slice(),
+        };
+
+        let pipelines::Pipeline {
+            inner: compute_pipeline,
+            group: compute_group, ..
+        } = builder.into();
+
+        (texture, compute_group, compute_pipeline)
+    }
+
+    fn render_group(
+        shared: CompareShared,
+        layout: &wgpu::BindGroupLayout,
+        texture: &wgpu::Texture,
+        mode_buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        let CompareShared {
+            device,
+            tex_view_format,
+            texture: texture_primary,
+            size_buffer, ..
+        } = shared;
+
+        let view_a = package::PipelinePackage::view(texture_primary, tex_view_format);
+        let view_b = package::PipelinePackage::view(texture, tex_view_format);
+
+        device.create_bind_group(
+            &wgpu::BindGroupDescriptor {
+                label: None,
+                layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&view_a),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(&view_b),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: size_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: mode_buffer.as_entire_binding(),
+                    },
+                ],
+            }
+        )
+    }
+
+    pub fn config(&self) -> CompareConfig {
+        self.config
+    }
+
+    pub fn stats(&self) -> handlers::IntrsStats {
+        self.pack_stats
+    }
+
+    pub fn timer(&self) -> &timer::PassTimer {
+        &self.timer
+    }
+
+    // The compared handler's output
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    pub fn update_mode(&mut self, queue: &wgpu::Queue, mode: CompareMode) {
+        self.config.mode = mode;
+
+        queue.write_buffer(
+            &self.mode_buffer,
+            0,
+            bytemuck::cast_slice(&[CompareUniform::from(mode)])
+        );
+    }
+
+    // Both textures are rebuilt alongside the primary pipeline
+    pub fn resize(&mut self, shared: CompareShared) {
+        let (texture, compute_group, compute_pipeline) = //
+            Self::compute(shared, &self.shader_compute, &self.pack_vars);
+
+        self.render_group = Self::render_group(
+            shared,
+            &self.render_group_layout,
+            &texture,
+            &self.mode_buffer
+        );
+
+        self.texture = texture;
+        self.compute_group = compute_group;
+        self.compute_pipeline = compute_pipeline;
+    }
+
+    // Follows the primary scene group when one of its buffers is replaced
+    pub fn regroup(
+        &mut self,
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        camera: &wgpu::Buffer,
+        scene_buffers: &[wgpu::Buffer],
+    ) {
+        if let Some(prims) = &self.prims {
+            self.scene_group = Some(Self::scene_group(device, layout, camera, prims, scene_buffers));
+        }
+    }
+
+    // Dispatches the comparison over the same pixels as the primary pass.
+    // Returns true if the pass is being timed
+    pub fn encode(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        config_group: &wgpu::BindGroup,
+        scene_group: &wgpu::BindGroup,
+        workgroups: [u32; 2],
+    ) -> bool {
+        self.timer.poll();
+
+        let timed = self.timer.armed();
+
+        let Self {
+            scene_group: scene_group_compare,
+            compute_group,
+            compute_pipeline,
+            pack_vars: handlers::IntrsPack { vars, group, .. },
+            timer, ..
+        } = self;
+
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: None,
+                timestamp_writes: if timed { timer.timestamp_writes() } else { None },
+            });
+
+            compute_pass.set_pipeline(compute_pipeline);
+
+            compute_pass.set_bind_group(0, compute_group, &[]);
+            compute_pass.set_bind_group(1, config_group, &[]);
+            compute_pass.set_bind_group(2, scene_group_compare.as_ref().unwrap_or(scene_group), &[]);
+
+            if !vars.is_empty() {
+                compute_pass.set_bind_group(3, group, &[]);
+            }
+
+            let [x, y] = workgroups;
+
+            compute_pass.dispatch_workgroups(x, y, 1);
+        }
+
+        if timed {
+            timer.resolve(encoder);
+        }
+
+        timed
+    }
+
+    // Called once the comparison pass has been submitted
+    pub fn post(&self, queue: &wgpu::Queue, timed: bool) {
+        if timed {
+            self.timer.post(queue);
+        }
+    }
+
+    pub fn render(&self) -> (&wgpu::RenderPipeline, &wgpu::BindGroup) {
+        (&self.render_pipeline, &self.render_group)
+    }
+
+    pub fn destroy(&self) {
+        self.pack_vars.destroy();
+
+        if let Some(prims) = &self.prims {
+            prims.destroy();
+        }
+
+        self.mode_buffer.destroy();
+
+        self.timer.destroy();
+    }
+}
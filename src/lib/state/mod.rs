@@ -1,18 +1,26 @@
 mod package;
+mod compare;
+
+pub use compare::{CompareConfig, CompareMode};
 
 use std::{mem, sync};
 
 use winit::{dpi, window};
 
-use crate::{handlers, scene, shaders, timing, vertex};
+use crate::{capture, geom, handlers, hud, overlay, refit, scene, shaders, text, timer, timing, vertex};
+use crate::geom::light as light;
 
 #[derive(Debug)]
 struct StateInternals {
     window_size: dpi::PhysicalSize<u32>,
+    adapter_info: wgpu::AdapterInfo,
     device: wgpu::Device,
     queue: wgpu::Queue,
-    surface: wgpu::Surface<'static>,
+    // NOTE: Headless states have no surface to present to
+    surface: Option<wgpu::Surface<'static>>,
     surface_config: wgpu::SurfaceConfiguration,
+    // The format the render pass samples the compute texture through
+    tex_view_format: wgpu::TextureFormat,
 }
 
 impl StateInternals {
@@ -94,6 +102,9 @@ impl StateInternals {
             .await
             .unwrap();
 
+        // Reported alongside benchmark results
+        let adapter_info = adapter.get_info();
+
             let surface_capabilities = surface.get_capabilities(&adapter);
 
             cfg_if::cfg_if! {
@@ -129,17 +140,94 @@ impl StateInternals {
                 desired_maximum_frame_latency: 1,
             };
     
+            // The SRGB texture view isn't available on web
+            cfg_if::cfg_if! {
+                if #[cfg(target_arch = "wasm32")] {
+                    let tex_view_format = Self::TEXTURE_FORMAT;
+                } else {
+                    let tex_view_format = Self::TEXTURE_FORMAT.add_srgb_suffix();
+                }
+            }
+
             // Configure the surface (no longer platform-specific)
             surface.configure(&device, &surface_config);
     
             Ok(Self {
                 window_size,
+                adapter_info,
                 device,
                 queue,
-                surface,
+                surface: Some(surface),
                 surface_config,
+                tex_view_format,
             })
     }
+
+    // Used for rendering without a window (i.e. image regression tests)
+    // Software adapters are preferred so results are consistent across machines
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn headless(window_size: dpi::PhysicalSize<u32>) -> anyhow::Result<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+
+        let mut adapter = None;
+        for force_fallback_adapter in [true, false] {
+            adapter = instance.request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: None,
+                force_fallback_adapter,
+            }).await;
+
+            if adapter.is_some() { break; }
+        }
+
+        let adapter = adapter
+            .ok_or(anyhow::anyhow!("Unable to find a headless adapter"))?;
+
+        let device_desc = wgpu::DeviceDescriptor {
+            label: None,
+            required_features: wgpu::Features::empty(),
+            required_limits: wgpu::Limits::default(),
+        };
+
+        let (device, queue) = adapter
+            .request_device(&device_desc, None)
+            .await?;
+
+        let adapter_info = adapter.get_info();
+
+        // Software adapters (i.e. llvmpipe on GL) may not support reinterpreting
+        // the texture as SRGB, but the render pass isn't used here anyway
+        let tex_view_format = if adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::VIEW_FORMATS) {
+            Self::TEXTURE_FORMAT.add_srgb_suffix()
+        } else {
+            Self::TEXTURE_FORMAT
+        };
+
+        // This is never used to configure a surface
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: Self::TEXTURE_FORMAT,
+            width: window_size.width,
+            height: window_size.height,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            view_formats: Vec::new(),
+            desired_maximum_frame_latency: 1,
+        };
+
+        Ok(Self {
+            window_size,
+            adapter_info,
+            device,
+            queue,
+            surface: None,
+            surface_config,
+            tex_view_format,
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -149,12 +237,17 @@ pub struct State<S: timing::Scheduler> {
 
     // Keeps track of frame time
     scheduler: S,
+    // Passed back to the scheduler whenever a scene is loaded
+    config_scheduler: S::Config,
 
     // CPU-side of the intersection logic
     pack_vars: handlers::IntrsPack<'static>,
-    #[allow(dead_code)]
     pack_stats: handlers::IntrsStats,
 
+    // Reported by the HUD
+    prims: usize,
+    wg: u32,
+
     // Shader modules
     shader_compute: wgpu::ShaderModule,
     shader_render: wgpu::ShaderModule,
@@ -177,6 +270,8 @@ pub struct State<S: timing::Scheduler> {
     config_group: wgpu::BindGroup,
 
     // Texture binding group and compute pipeline
+    // NOTE: The texture is held onto so it can be captured
+    compute_texture: wgpu::Texture,
     compute_group: wgpu::BindGroup,
     compute_pipeline: wgpu::ComputePipeline,
     
@@ -185,13 +280,39 @@ pub struct State<S: timing::Scheduler> {
     indices: wgpu::Buffer,
     render_group: wgpu::BindGroup,
     render_pipeline: wgpu::RenderPipeline,
+
+    // Wireframe BVH bounds, drawn after the screen-space quad
+    // NOTE: Only present if the IntrsHandler has a BVH
+    overlay: Option<overlay::Overlay>,
+
+    // Follows vertex updates by refitting the handler's BVH
+    // NOTE: Only present if the IntrsHandler exposes its nodes
+    refit: Option<refit::Refit>,
+    // The handler's structure can't follow moving vertices
+    refit_unsupported: bool,
+
+    // Screenshots and recorded frames that are being read back
+    capture: capture::Capture,
+
+    // Times the primary compute pass
+    timer: timer::PassTimer,
+
+    // Labels drawn over the ray-traced image
+    text: text::Text,
+
+    // Frame statistics, drawn with `text`
+    hud: hud::Hud,
+
+    // A second handler, drawn next to (or blended with) the first
+    compare: Option<compare::Compare>,
 }
 
 impl<S: timing::Scheduler> State<S> {
     pub async fn new<H: handlers::IntrsHandler>(
         config: crate::Config, 
         config_handler: H::Config,
-        scene: &mut scene::Scene,
+        config_scheduler: S::Config,
+        scene: &scene::Scene,
         window: sync::Arc<window::Window>,
     ) -> anyhow::Result<Self> {
         // We only build this once
@@ -200,7 +321,7 @@ impl<S: timing::Scheduler> State<S> {
 
         match H::new(config_handler) {
             Ok(handler) => {
-                match State::init(internals, config, scene, handler) {
+                match State::init(internals, config, config_scheduler, scene, handler) {
                     Ok(state) => Ok(state),
                     Err((_, e)) => {
                         // NOTE: When we load additional scenes after this,
@@ -223,9 +344,26 @@ impl<S: timing::Scheduler> State<S> {
         }
     }
 
+    // Builds a state without a window or surface
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn headless<H: handlers::IntrsHandler>(
+        config: crate::Config, 
+        config_handler: H::Config,
+        config_scheduler: S::Config,
+        scene: &scene::Scene,
+        size: dpi::PhysicalSize<u32>,
+    ) -> anyhow::Result<Self> {
+        let internals = StateInternals::headless(size).await?;
+
+        let handler = H::new(config_handler)?;
+
+        State::init(internals, config, config_scheduler, scene, handler)
+            .map_err(|(_, e)| e)
+    }
+
     // This function replaces self with a new state object
     // (that has initialized a new scene's data)
-    #[cfg(target_arch = "wasm32")]
+    // NOTE: The scheduler restarts with the same config
     pub fn load<H: handlers::IntrsHandler>(
         &mut self, 
         config: crate::Config, 
@@ -241,11 +379,35 @@ impl<S: timing::Scheduler> State<S> {
                 pack_vars,
                 scene_camera_buffer,
                 scene_buffers,
-                config_buffer, ..
+                config_buffer, 
+                overlay,
+                refit,
+                timer,
+                text,
+                compare, ..
             } = state;
     
             // The CPU-side intersection buffers
             pack_vars.destroy();
+
+            // The BVH overlay's instances and uniform
+            if let Some(overlay) = overlay {
+                overlay.destroy();
+            }
+
+            // The refit pass's topology and levels
+            if let Some(refit) = refit {
+                refit.destroy();
+            }
+
+            // The comparison's intersection buffers and uniform
+            if let Some(compare) = compare {
+                compare.destroy();
+            }
+
+            // The timer's query buffers and the text instances
+            timer.destroy();
+            text.destroy();
     
             // The Camera uniform buffer
             scene_camera_buffer.destroy();
@@ -260,13 +422,33 @@ impl<S: timing::Scheduler> State<S> {
             config_buffer.destroy();
         }
 
+        // The comparison is rebuilt around the new scene
+        let compare = self.compare
+            .as_ref()
+            .map(compare::Compare::config);
+
         match H::new(config_handler) {
             Ok(handler) => {
-                match Self::init::<H>(internals, config, scene, handler) {
+                match Self::init::<H>(
+                    internals, 
+                    config, 
+                    self.config_scheduler.clone(), 
+                    scene, 
+                    handler
+                ) {
                     Ok(state) => {
                         destroy(self); 
                         
-                        let _ = mem::replace(self, state);
+                        let previous = mem::replace(self, state);
+
+                        // Let the previous scheduler wrap up its results
+                        if let Err(e) = previous.scheduler.finish() {
+                            log::error!("{e:#}");
+                        }
+
+                        if compare.is_some() {
+                            self.compare(config, scene, compare)?;
+                        }
                     },
                     Err((internals, e)) => {
                         #[cfg(target_arch = "wasm32")]
@@ -293,10 +475,12 @@ impl<S: timing::Scheduler> State<S> {
     // because we need to recover the StateInternals if
     // initialization fails.
     // By doing this, we can keep the program going on the current scene
+    #[allow(clippy::result_large_err)]
     fn init<H: handlers::IntrsHandler>(
         internals: StateInternals,
         config: crate::Config,
-        scene: &mut scene::Scene,
+        config_scheduler: S::Config,
+        scene: &scene::Scene,
         handler: H,
     ) -> Result<Self, (StateInternals, anyhow::Error)> {
         use wgpu::util::DeviceExt as _;
@@ -318,6 +502,15 @@ impl<S: timing::Scheduler> State<S> {
             }
         );
 
+        // Collection of IntrsHandler-specific bindings
+        // Handlers may order the scene's prims, so this comes before packing
+        let (pack_vars, pack_stats) = match handler.vars(scene, &internals.device) {
+            Ok(vars) => vars,
+            Err(e) => {
+                return Err((internals, e));
+            },
+        };
+
         // Get all the buffers, groups associated with the scene
         // These fill group(3)
         let scene::ScenePack {
@@ -325,7 +518,7 @@ impl<S: timing::Scheduler> State<S> {
             buffers: scene_buffers,
             bg: scene_group, 
             bg_layout: scene_group_layout, ..
-        } = scene.pack(&internals.device);
+        } = scene.pack(&internals.device, pack_vars.prims.as_deref());
 
         // We have to hold onto the Config buffer since it can be updated live
         let config_buffer = internals.device.create_buffer_init(
@@ -358,11 +551,28 @@ impl<S: timing::Scheduler> State<S> {
             },
         ];
 
-        // Collection of IntrsHandler-specific bindings
-        let (pack_vars, pack_stats) = handler.vars(scene, &internals.device);
+        let prims = match scene {
+            scene::Scene::Unloaded => 0,
+            scene::Scene::Active { prims, .. } => prims.len(),
+        };
+
+        // Describes this configuration to the scheduler
+        let scheduler_info = timing::SchedulerInfo {
+            adapter: internals.adapter_info.clone(),
+            stats: pack_stats,
+            prims,
+            size,
+            wg: config.resolution.wg(),
+            compute: config.compute,
+        };
 
         // Frame scheduler + benchmark handler
-        let scheduler = S::init(&internals.queue, &internals.device, pack_stats);
+        let scheduler = S::init(
+            &internals.queue, 
+            &internals.device, 
+            scheduler_info, 
+            config_scheduler.clone()
+        );
 
         // The scheduler's buffers (if its using them)
         // need to piggyback off group 2
@@ -423,6 +633,7 @@ impl<S: timing::Scheduler> State<S> {
                     wg: config.resolution.wg(),
                     pack: &pack_vars,
                     logic: handler.logic(),
+                    instrument: scheduler.instrumented(),
                 }) {
                     Ok(source) => source,
                     Err(e) => {
@@ -450,6 +661,70 @@ impl<S: timing::Scheduler> State<S> {
             }
         );
 
+        // Only handlers that traverse a BVH get an overlay
+        let overlay = match handler.bvh() {
+            Some(data) if !data.uniforms.is_empty() => {
+                let shader_overlay = internals.device.create_shader_module(
+                    wgpu::ShaderModuleDescriptor {
+                        label: None,
+                        source: match shaders::source(shaders::ShaderStage::Overlay) {
+                            Ok(source) => source,
+                            Err(e) => { 
+                                return Err((internals, e)); 
+                            },
+                        },
+                    },
+                );
+
+                Some(overlay::Overlay::new(
+                    &internals.device,
+                    StateInternals::TEXTURE_FORMAT,
+                    &shader_overlay,
+                    &scene_camera_buffer,
+                    data,
+                    config.overlay,
+                ))
+            },
+            _ => None,
+        };
+
+        // The refit pass writes straight into the handler's node buffer
+        let refit_target = handler.refit_var().and_then(|name| {
+            pack_vars.vars
+                .iter()
+                .find(|var| var.var_name == name)
+                .map(|var| &var.buffer)
+        });
+
+        let refit = match (handler.bvh(), refit_target) {
+            (Some(data), Some(nodes)) if !data.uniforms.is_empty() => {
+                let shader_refit = internals.device.create_shader_module(
+                    wgpu::ShaderModuleDescriptor {
+                        label: None,
+                        source: match shaders::source(shaders::ShaderStage::Refit) {
+                            Ok(source) => source,
+                            Err(e) => { 
+                                return Err((internals, e)); 
+                            },
+                        },
+                    },
+                );
+
+                // Prims are followed by vertices in the scene's buffers
+                Some(refit::Refit::new(
+                    &internals.device,
+                    &shader_refit,
+                    data,
+                    nodes,
+                    &scene_buffers[0],
+                    &scene_buffers[1],
+                ))
+            },
+            _ => None,
+        };
+
+        let refit_unsupported = handler.bvh().is_some() && refit.is_none();
+
         let handlers::IntrsPack { 
             vars, 
             layout, .. 
@@ -462,6 +737,7 @@ impl<S: timing::Scheduler> State<S> {
         };
 
         let package::PipelinePackage {
+            texture: compute_texture,
             compute_group,
             compute_pipeline,
             render_group,
@@ -469,6 +745,7 @@ impl<S: timing::Scheduler> State<S> {
         } = package::PipelinePackage::new(
             &internals.device, 
             StateInternals::TEXTURE_FORMAT,
+            internals.tex_view_format,
             &shader_compute, 
             &shader_render, 
             size,
@@ -476,14 +753,44 @@ impl<S: timing::Scheduler> State<S> {
             layouts.as_slice(),
         );
 
+        let shader_text = internals.device.create_shader_module(
+            wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: match shaders::source(shaders::ShaderStage::Text) {
+                    Ok(source) => source,
+                    Err(e) => { 
+                        return Err((internals, e)); 
+                    },
+                },
+            },
+        );
+
+        let text = text::Text::new(
+            &internals.device,
+            StateInternals::TEXTURE_FORMAT,
+            &shader_text,
+        );
+
+        let timer = timer::PassTimer::new(&internals.device, &internals.queue);
+
+        // Recorded frames are tagged with the camera that produced them
+        let mut capture = capture::Capture::default();
+        if let scene::Scene::Active { camera, .. } = scene {
+            capture.set_camera(*camera);
+        }
+
         Ok(Self {
             internals: Some(internals),
 
             scheduler,
+            config_scheduler,
 
             pack_vars,
             pack_stats,
 
+            prims,
+            wg: config.resolution.wg(),
+
             shader_compute,
             shader_render,
 
@@ -498,6 +805,7 @@ impl<S: timing::Scheduler> State<S> {
             config_group_layout,
             config_group,
 
+            compute_texture,
             compute_group,
             compute_pipeline,
 
@@ -505,12 +813,27 @@ impl<S: timing::Scheduler> State<S> {
             indices,
             render_group,
             render_pipeline,
+
+            overlay,
+
+            refit,
+            refit_unsupported,
+
+            capture,
+
+            timer,
+
+            text,
+
+            hud: hud::Hud::new(config.hud),
+
+            compare: None,
         })
     }
 
     pub fn resize_hard(&mut self, size: dpi::PhysicalSize<u32>) {
         let Self {
-            internals: Some(StateInternals { device, queue, .. }),
+            internals: Some(StateInternals { device, queue, tex_view_format, .. }),
             shader_compute,
             shader_render,
             pack_vars: handlers::IntrsPack { vars, layout, .. }, 
@@ -532,6 +855,7 @@ impl<S: timing::Scheduler> State<S> {
         };
 
         let package::PipelinePackage {
+            texture: compute_texture,
             compute_group,
             compute_pipeline,
             render_group,
@@ -539,6 +863,7 @@ impl<S: timing::Scheduler> State<S> {
         } = package::PipelinePackage::new(
             device, 
             StateInternals::TEXTURE_FORMAT,
+            *tex_view_format,
             shader_compute, 
             shader_render, 
             size, 
@@ -546,11 +871,25 @@ impl<S: timing::Scheduler> State<S> {
             layouts.as_slice(),
         );
 
+        self.compute_texture = compute_texture;
         self.compute_group = compute_group;
         self.compute_pipeline = compute_pipeline;
 
         self.render_group = render_group;
         self.render_pipeline = render_pipeline;
+
+        if let Some(compare) = &mut self.compare {
+            compare.resize(compare::CompareShared {
+                device,
+                tex_format: StateInternals::TEXTURE_FORMAT,
+                tex_view_format: *tex_view_format,
+                texture: &self.compute_texture,
+                size,
+                size_buffer,
+                config_group_layout,
+                scene_group_layout,
+            });
+        }
     }
 
     pub fn resize(
@@ -573,7 +912,9 @@ impl<S: timing::Scheduler> State<S> {
             surface_config.width = size.width;
             surface_config.height = size.height;
 
-            surface.configure(device, surface_config);
+            if let Some(surface) = surface {
+                surface.configure(device, surface_config);
+            }
 
             if let crate::Resolution::Dynamic { .. } = config.resolution {
                 self.resize_hard(size);
@@ -584,12 +925,52 @@ impl<S: timing::Scheduler> State<S> {
     pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
         let Self {
             internals: Some(StateInternals { 
+                window_size,
                 device, 
                 queue, 
                 surface, .. 
             }), ..
         } = self else { unreachable!(); };
 
+        // Headless states are never presented
+        let Some(surface) = surface else { return Ok(()); };
+
+        // Each handler is labelled with the duration of its compute pass
+        if let Some(compare) = &self.compare {
+            let labels = [
+                label(&self.pack_stats, &self.timer),
+                label(&compare.stats(), compare.timer()),
+            ];
+
+            let line = text::Text::line_height();
+
+            // Split labels sit in their own halves, diff labels are stacked
+            let positions = match compare.config().mode {
+                CompareMode::Split => [[8., 8.], [window_size.width as f32 * 0.5 + 8., 8.]],
+                CompareMode::Diff => [[8., 8.], [8., 8. + line]],
+            };
+
+            for (label, pos) in labels.iter().zip(positions) {
+                self.text.push(label, pos, [1.; 4]);
+            }
+        }
+
+        // Drawn in the bottom-left corner, clear of the comparison's labels
+        self.hud.frame();
+        self.hud.push(&mut self.text, hud::HudInfo {
+            timer: &self.timer,
+            size: {
+                let wgpu::Extent3d { width, height, .. } = self.compute_texture.size();
+
+                dpi::PhysicalSize::new(width, height)
+            },
+            wg: self.wg,
+            stats: self.pack_stats,
+            prims: self.prims,
+        }, *window_size);
+
+        self.text.upload(queue, *window_size);
+
         let output = surface.get_current_texture()?;
 
         let view = output.texture
@@ -619,11 +1000,17 @@ impl<S: timing::Scheduler> State<S> {
                 }
             );
 
+            // Comparisons draw both handlers' textures
+            let (render_pipeline, render_group) = match &self.compare {
+                Some(compare) => compare.render(),
+                None => (&self.render_pipeline, &self.render_group),
+            };
+
             // Apply the render pipeline
-            render_pass.set_pipeline(&self.render_pipeline);
+            render_pass.set_pipeline(render_pipeline);
 
             // This contains the texture and size
-            render_pass.set_bind_group(0, &self.render_group, &[]);
+            render_pass.set_bind_group(0, render_group, &[]);
 
             // The indices for the screen-space quad
             render_pass.set_index_buffer(
@@ -639,6 +1026,14 @@ impl<S: timing::Scheduler> State<S> {
                 0, 
                 0..1
             ); 
+
+            // Draw the BVH bounds on top (if enabled)
+            if let Some(overlay) = &self.overlay {
+                overlay.draw(&mut render_pass);
+            }
+
+            // Labels are drawn over everything else
+            self.text.draw(&mut render_pass);
         }
 
         // Submit for execution (async)
@@ -650,10 +1045,30 @@ impl<S: timing::Scheduler> State<S> {
         Ok(())
     }
 
-    pub fn update(&mut self, config: crate::Config) {
-        if self.scheduler.ready() {
+    // Returns true if a compute pass was dispatched
+    pub fn update(&mut self, config: crate::Config) -> bool {
+        let ready = self.scheduler.ready();
+
+        if ready {
             self.update_internal(config);
         }
+
+        ready
+    }
+
+    // The scheduler requests compute passes on every frame
+    pub fn continuous(&self) -> bool {
+        self.scheduler.continuous()
+    }
+
+    // The scheduler has completed its work
+    pub fn finished(&self) -> bool {
+        self.scheduler.finished()
+    }
+
+    // Consumes the state, waiting on the scheduler to wrap up
+    pub fn finish(self) -> anyhow::Result<()> {
+        self.scheduler.finish()
     }
 
     fn update_internal(&mut self, config: crate::Config) {
@@ -669,9 +1084,32 @@ impl<S: timing::Scheduler> State<S> {
             wgpu::CommandEncoderDescriptor::default()
         });
 
+        self.timer.poll();
+
+        let wg = config.resolution.wg();
+
+        let dpi::PhysicalSize {
+            width,
+            height, ..
+        } = match config.resolution {
+            crate::Resolution::Dynamic { .. } => *window_size,
+            crate::Resolution::Sized(size) => size,
+            crate::Resolution::Fixed { size, .. } => size,
+        };
+
+        let workgroups = [width.div_euclid(wg), height.div_euclid(wg)];
+
+        let mut desc = self.scheduler.desc();
+
+        // Schedulers that write their own timestamps aren't timed twice
+        let timed = desc.timestamp_writes.is_none() && self.timer.armed();
+        if timed {
+            desc.timestamp_writes = self.timer.timestamp_writes();
+        }
+
         {
             let mut compute_pass = encoder
-                .begin_compute_pass(&self.scheduler.desc());
+                .begin_compute_pass(&desc);
 
             compute_pass.set_pipeline(&self.compute_pipeline);
 
@@ -690,29 +1128,47 @@ impl<S: timing::Scheduler> State<S> {
                 compute_pass.set_bind_group(3, group, &[]);
             }
 
-            let wg = config.resolution.wg();
+            let [x, y] = workgroups;
 
-            let dpi::PhysicalSize {
-                width,
-                height, ..
-            } = match config.resolution {
-                crate::Resolution::Dynamic { .. } => *window_size,
-                crate::Resolution::Sized(size) => size,
-                crate::Resolution::Fixed { size, .. } => size,
-            };
+            compute_pass.dispatch_workgroups(x, y, 1);
+        }
 
-            compute_pass.dispatch_workgroups(
-                width.div_euclid(wg), 
-                height.div_euclid(wg), 
-                1
-            );
+        if timed {
+            self.timer.resolve(&mut encoder);
         }
 
         self.scheduler.pre(&mut encoder);
 
+        // Copy the output if a recording is in progress
+        self.capture.frame(device, &mut encoder, &self.compute_texture);
+
         queue.submit(Some(encoder.finish()));
 
+        if timed {
+            self.timer.post(queue);
+        }
+
         self.scheduler.post(queue, device);
+
+        self.capture.post();
+
+        // The comparison is submitted separately, so each pass is timed on its own
+        if let Some(compare) = &mut self.compare {
+            let mut encoder = device.create_command_encoder(&{
+                wgpu::CommandEncoderDescriptor::default()
+            });
+
+            let timed = compare.encode(
+                &mut encoder,
+                &self.config_group,
+                &self.scene_group,
+                workgroups
+            );
+
+            queue.submit(Some(encoder.finish()));
+
+            compare.post(queue, timed);
+        }
     }
 
     pub fn update_camera_buffer(&mut self, camera: scene::CameraUniform) {
@@ -726,9 +1182,314 @@ impl<S: timing::Scheduler> State<S> {
             0, 
             bytemuck::cast_slice(&[camera]),
         );
+
+        self.capture.set_camera(camera);
+    }
+
+    // Overwrites one of the scene's storage buffers in place,
+    // the number of elements can't change without reloading the scene
+    fn update_scene_buffer<T: bytemuck::Pod>(&self, idx: usize, contents: &[T]) -> anyhow::Result<()> {
+        let Self {
+            internals: Some(StateInternals { queue, .. }), 
+            scene_buffers, ..
+        } = self else { unreachable!(); };
+
+        let buffer = &scene_buffers[idx];
+
+        let count = buffer.size() as usize / mem::size_of::<T>();
+        if contents.len() != count {
+            anyhow::bail!("Expected {count} elements, found {}", contents.len());
+        }
+
+        queue.write_buffer(buffer, 0, bytemuck::cast_slice(contents));
+
+        Ok(())
+    }
+
+    // Swaps one of the scene's storage buffers for one of a different length.
+    // The layout doesn't depend on buffer sizes, so only the group is rebuilt
+    fn replace_scene_buffer<T: bytemuck::Pod>(&mut self, idx: usize, contents: &[T]) {
+        use wgpu::util::DeviceExt as _;
+
+        let Self {
+            internals: Some(StateInternals { device, .. }), 
+            scene_group_layout,
+            scene_group,
+            scene_camera_buffer,
+            scene_buffers, ..
+        } = self else { unreachable!(); };
+
+        let buffer = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(contents),
+                usage: scene_buffers[idx].usage(),
+            }
+        );
+
+        // The previous buffer is released once in-flight passes complete
+        scene_buffers[idx] = buffer;
+
+        let entries: Vec<wgpu::BindGroupEntry> = [&*scene_camera_buffer]
+            .into_iter()
+            .chain(scene_buffers.iter())
+            .enumerate()
+            .map(|(binding, buffer)| wgpu::BindGroupEntry {
+                binding: binding as u32,
+                resource: buffer.as_entire_binding(),
+            }).collect();
+
+        *scene_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: scene_group_layout,
+            entries: entries.as_slice(),
+        });
+
+        if let Some(compare) = &mut self.compare {
+            compare.regroup(device, scene_group_layout, scene_camera_buffer, scene_buffers);
+        }
+    }
+
+    // Applies an edit to the scene, then patches its buffers in place.
+    // Only new geometry requires the handler (and pipeline) to be rebuilt
+    pub fn edit<H: handlers::IntrsHandler>(
+        &mut self,
+        config: crate::Config,
+        config_handler: H::Config,
+        scene: &mut scene::Scene,
+        edit: scene::SceneEdit,
+    ) -> anyhow::Result<()> {
+        match scene.edit(edit)? {
+            scene::SceneUpdate::Lights => {
+                let scene::Scene::Active { lights, .. } = scene else { unreachable!(); };
+
+                self.replace_scene_buffer(2, lights);
+            },
+            scene::SceneUpdate::Material(idx) => {
+                let scene::Scene::Active { materials, .. } = scene else { unreachable!(); };
+
+                let Self {
+                    internals: Some(StateInternals { queue, .. }), 
+                    scene_buffers, ..
+                } = self else { unreachable!(); };
+
+                queue.write_buffer(
+                    &scene_buffers[3],
+                    (idx * mem::size_of::<geom::PrimMat>()) as u64,
+                    bytemuck::bytes_of(&materials[idx]),
+                );
+            },
+            scene::SceneUpdate::Geometry { prims, vertices } => {
+                // Keep the scene in step with the state that remains
+                if let Err(e) = self.load::<H>(config, config_handler, scene) {
+                    scene.revert_geometry(prims, vertices);
+
+                    return Err(e);
+                }
+            },
+            scene::SceneUpdate::Camera => {
+                let scene::Scene::Active { camera, .. } = scene else { unreachable!(); };
+
+                self.update_camera_buffer(*camera);
+            },
+        }
+
+        Ok(())
+    }
+
+    // Scene buffers are ordered as in `Scene::pack`, without the camera
+    pub fn update_lights(&mut self, lights: &[light::Light]) -> anyhow::Result<()> {
+        self.update_scene_buffer(2, lights)
+    }
+
+    pub fn update_materials(&mut self, materials: &[geom::PrimMat]) -> anyhow::Result<()> {
+        self.update_scene_buffer(3, materials)
+    }
+
+    // Replaces the positions (and normals) of every vertex in the scene,
+    // the BVH keeps its topology but its bounds are refit on the GPU
+    pub fn update_vertices(&mut self, vertices: &[geom::PrimVertex]) -> anyhow::Result<()> {
+        if self.refit_unsupported {
+            anyhow::bail!("{} can't be refit, the scene has to be reloaded", self.pack_stats.name);
+        }
+
+        // Only the primary handler's structure is refit
+        if let Some(compare) = &self.compare {
+            anyhow::bail!("{} can't be refit while comparing handlers", compare.stats().name);
+        }
+
+        self.update_scene_buffer(1, vertices)?;
+
+        let Self {
+            internals: Some(StateInternals { device, queue, .. }), 
+            refit, ..
+        } = self else { unreachable!(); };
+
+        // Submitted before the next compute pass, so it always sees the new bounds
+        if let Some(refit) = refit {
+            let mut encoder = device.create_command_encoder(&{
+                wgpu::CommandEncoderDescriptor::default()
+            });
+
+            refit.encode(&mut encoder);
+
+            queue.submit(Some(encoder.finish()));
+        }
+
+        Ok(())
+    }
+
+    // Save the compute output as a PNG once it has been read back
+    pub fn screenshot(&mut self) {
+        let Self {
+            internals: Some(StateInternals { device, queue, .. }), 
+            compute_texture,
+            capture, ..
+        } = self else { unreachable!(); };
+
+        capture.screenshot(device, queue, compute_texture);
+    }
+
+    // Start or stop dumping every frame (native only)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn toggle_recording(&mut self) -> anyhow::Result<bool> {
+        self.capture.toggle_recording()?;
+
+        Ok(self.capture.recording())
+    }
+
+    // Dispatch a single compute pass and read back its output (blocking)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn snapshot(&mut self, config: crate::Config) -> anyhow::Result<capture::CaptureImage> {
+        // Wait for any previous pass to complete
+        while !self.update(config) {
+            let Self {
+                internals: Some(StateInternals { device, .. }), ..
+            } = self else { unreachable!(); };
+
+            device.poll(wgpu::Maintain::Wait);
+        }
+
+        let Self {
+            internals: Some(StateInternals { device, queue, .. }), 
+            compute_texture, ..
+        } = self else { unreachable!(); };
+
+        capture::Capture::read(device, queue, compute_texture)
+    }
+
+    // Reads back the comparison's output from the last compute pass (blocking)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn snapshot_compared(&mut self) -> anyhow::Result<capture::CaptureImage> {
+        let Self {
+            internals: Some(StateInternals { device, queue, .. }), 
+            compare, ..
+        } = self else { unreachable!(); };
+
+        let Some(compare) = compare else {
+            anyhow::bail!("No handler is being compared");
+        };
+
+        capture::Capture::read(device, queue, compare.texture())
+    }
+
+    // Starts, changes or stops comparing against a second handler.
+    // Changing only the mode keeps the compared handler
+    pub fn compare(
+        &mut self,
+        config: crate::Config,
+        scene: &scene::Scene,
+        config_compare: Option<CompareConfig>,
+    ) -> anyhow::Result<()> {
+        let Self {
+            internals: Some(StateInternals { 
+                device, 
+                queue, 
+                window_size,
+                tex_view_format, .. 
+            }), 
+            size_buffer,
+            scene_group_layout,
+            scene_camera_buffer,
+            scene_buffers,
+            config_group_layout,
+            compute_texture,
+            pack_vars,
+            compare, ..
+        } = self else { unreachable!(); };
+
+        match (compare.as_mut(), config_compare) {
+            (Some(current), Some(config_compare)) //
+                if current.config().handler == config_compare.handler => {
+                current.update_mode(queue, config_compare.mode);
+            },
+            (_, config_compare) => {
+                if let Some(current) = compare.take() {
+                    current.destroy();
+                }
+
+                let Some(config_compare) = config_compare else {
+                    return Ok(());
+                };
+
+                let size = match config.resolution {
+                    crate::Resolution::Dynamic(_) => *window_size,
+                    crate::Resolution::Sized(size) => size,
+                    crate::Resolution::Fixed { size, .. } => size,
+                };
+
+                let shared = compare::CompareShared {
+                    device,
+                    tex_format: StateInternals::TEXTURE_FORMAT,
+                    tex_view_format: *tex_view_format,
+                    texture: compute_texture,
+                    size,
+                    size_buffer,
+                    config_group_layout,
+                    scene_group_layout,
+                };
+
+                *compare = Some(compare::Compare::new(
+                    shared,
+                    queue,
+                    config.resolution.wg(),
+                    config_compare,
+                    scene,
+                    pack_vars.prims.as_deref(),
+                    scene_camera_buffer,
+                    scene_buffers,
+                )?);
+            },
+        }
+
+        Ok(())
+    }
+
+    // Checks for completed captures, must be called regularly
+    pub fn poll_captures(&mut self) {
+        let Self {
+            internals: Some(StateInternals { device, .. }), 
+            capture, ..
+        } = self else { unreachable!(); };
+
+        capture.poll(device);
+    }
+
+    pub fn update_overlay(&mut self, config: crate::OverlayConfig) {
+        let Self {
+            internals: Some(StateInternals { queue, .. }), 
+            overlay, ..
+        } = self else { unreachable!(); };
+
+        if let Some(overlay) = overlay {
+            overlay.update(queue, config);
+        }
+    }
+
+    pub fn update_hud(&mut self, enabled: bool) {
+        self.hud.update(enabled);
     }
 
-    #[cfg(target_arch = "wasm32")]
     pub fn update_config(&mut self, config: crate::ComputeConfig) {
         let Self {
             internals: Some(StateInternals { queue, .. }), 
@@ -740,4 +1501,14 @@ impl<S: timing::Scheduler> State<S> {
             bytemuck::cast_slice(&[config])
         );
     }
-}
\ No newline at end of file
+}
+
+// Names a handler alongside the duration of its compute pass.
+// Durations without timestamp queries include queueing, so they are marked
+fn label(stats: &handlers::IntrsStats, timer: &timer::PassTimer) -> String {
+    match timer.ms() {
+        Some(ms) if timer.precise() => format!("{} {ms:.2} MS", stats.name),
+        Some(ms) => format!("{} ~{ms:.2} MS", stats.name),
+        None => format!("{} -- MS", stats.name),
+    }
+}
@@ -3,6 +3,7 @@ use winit::dpi;
 use crate::pipelines;
 
 pub struct PipelinePackage {
+    pub texture: wgpu::Texture,
     pub compute_group: wgpu::BindGroup,
     pub compute_pipeline: wgpu::ComputePipeline,
     pub render_group: wgpu::BindGroup,
@@ -10,76 +11,23 @@ pub struct PipelinePackage {
 }
 
 impl PipelinePackage {
+    // `tex_view_format` is the format the render pass samples the texture through
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         device: &wgpu::Device,
         tex_format: wgpu::TextureFormat,
+        tex_view_format: wgpu::TextureFormat,
         shader_compute: &wgpu::ShaderModule,
         shader_render: &wgpu::ShaderModule,
         size: dpi::PhysicalSize<u32>,
         size_buffer: &wgpu::Buffer,
         layouts: &[&wgpu::BindGroupLayout],
     ) -> Self {
-        let dpi::PhysicalSize {
-            width,
-            height, ..
-        } = size;
-
-        // Init the texture
-        let texture = device.create_texture(
-            &wgpu::TextureDescriptor {
-                label: None,
-                size: wgpu::Extent3d {
-                    width,
-                    height,
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: tex_format,
-                usage: wgpu::TextureUsages::STORAGE_BINDING 
-                     | wgpu::TextureUsages::TEXTURE_BINDING,
-                view_formats: &[
-                    tex_format,
-                    tex_format.add_srgb_suffix(),
-                ],
-            }
-        );
+        let texture = Self::texture(device, tex_format, tex_view_format, size);
 
-        // The SRGB texture view isn't available on web
-        cfg_if::cfg_if! {
-            if #[cfg(target_arch = "wasm32")] {
-                let tex_view_render_format = tex_format;
-            } else {
-                let tex_view_render_format = tex_format.add_srgb_suffix();
-            }
-        }
+        let tex_view_render = Self::view(&texture, tex_view_format);
 
-        let tex_view_render = texture.create_view(
-            &wgpu::TextureViewDescriptor {
-                label: None,
-                format: Some(tex_view_render_format),
-                dimension: Some(wgpu::TextureViewDimension::D2),
-                aspect: wgpu::TextureAspect::All,
-                base_mip_level: 0,
-                mip_level_count: Some(1),
-                base_array_layer: 0,
-                array_layer_count: Some(1),
-            }
-        );
-
-        let tex_view_compute = texture.create_view(
-            &wgpu::TextureViewDescriptor {
-                label: None,
-                format: Some(tex_format),
-                dimension: Some(wgpu::TextureViewDimension::D2),
-                aspect: wgpu::TextureAspect::All,
-                base_mip_level: 0,
-                mip_level_count: Some(1),
-                base_array_layer: 0,
-                array_layer_count: Some(1),
-            }
-        );
+        let tex_view_compute = Self::view(&texture, tex_format);
 
         // Build the compute pipeline
         let builder = pipelines::PipelineBuilder {
@@ -113,10 +61,61 @@ impl PipelinePackage {
 
         // Assemble and return
         Self {
+            texture,
             compute_group,
             compute_pipeline,
             render_group,
             render_pipeline,
         }
     }
+
+    // The compute pass writes to this texture, the render pass samples it
+    pub fn texture(
+        device: &wgpu::Device,
+        tex_format: wgpu::TextureFormat,
+        tex_view_format: wgpu::TextureFormat,
+        size: dpi::PhysicalSize<u32>,
+    ) -> wgpu::Texture {
+        let dpi::PhysicalSize {
+            width,
+            height, ..
+        } = size;
+
+        device.create_texture(
+            &wgpu::TextureDescriptor {
+                label: None,
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: tex_format,
+                usage: wgpu::TextureUsages::STORAGE_BINDING 
+                     | wgpu::TextureUsages::TEXTURE_BINDING
+                     | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[
+                    tex_format,
+                    tex_view_format,
+                ],
+            }
+        )
+    }
+
+    pub fn view(texture: &wgpu::Texture, format: wgpu::TextureFormat) -> wgpu::TextureView {
+        texture.create_view(
+            &wgpu::TextureViewDescriptor {
+                label: None,
+                format: Some(format),
+                dimension: Some(wgpu::TextureViewDimension::D2),
+                aspect: wgpu::TextureAspect::All,
+                base_mip_level: 0,
+                mip_level_count: Some(1),
+                base_array_layer: 0,
+                array_layer_count: Some(1),
+            }
+        )
+    }
 }
\ No newline at end of file
@@ -0,0 +1,354 @@
+use std::mem;
+
+use wgpu::util::DeviceExt as _;
+
+// A 5x7 bitmap font covering ASCII 0x20 (space) to 0x5F (underscore).
+// Each row is 5 bits wide, with the most significant bit on the left
+const FONT: [[u8; 7]; 64] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04], // !
+    [0x0A, 0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00], // "
+    [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A], // #
+    [0x04, 0x0F, 0x14, 0x0E, 0x05, 0x1E, 0x04], // $
+    [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03], // %
+    [0x0C, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0D], // &
+    [0x0C, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00], // '
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02], // (
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08], // )
+    [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00], // *
+    [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00], // +
+    [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08], // ,
+    [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00], // -
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C], // .
+    [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00], // /
+    [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E], // 0
+    [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E], // 1
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F], // 2
+    [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E], // 3
+    [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02], // 4
+    [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E], // 5
+    [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E], // 6
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08], // 7
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E], // 8
+    [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C], // 9
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00], // :
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x04, 0x08], // ;
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02], // <
+    [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00], // =
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08], // >
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04], // ?
+    [0x0E, 0x11, 0x01, 0x0D, 0x15, 0x15, 0x0E], // @
+    [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11], // A
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E], // B
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E], // C
+    [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C], // D
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F], // E
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10], // F
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F], // G
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // H
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // I
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C], // J
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11], // K
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F], // L
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11], // M
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11], // N
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // O
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10], // P
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D], // Q
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11], // R
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E], // S
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // T
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // U
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04], // V
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A], // W
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11], // X
+    [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04], // Y
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F], // Z
+    [0x0E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0E], // [
+    [0x00, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00], // \
+    [0x0E, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0E], // ]
+    [0x04, 0x0A, 0x11, 0x00, 0x00, 0x00, 0x00], // ^
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F], // _
+];
+
+// Drawn as a solid cell, behind each line of text
+const GLYPH_SOLID: u32 = FONT.len() as u32;
+
+// A single character cell, drawn as an instance of a quad
+#[repr(C)]
+#[derive(bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+struct TextGlyph {
+    // Top-left corner of the cell (pixels)
+    pos: [f32; 2],
+    glyph: u32,
+    _p0: u32,
+    color: [f32; 4],
+}
+
+impl TextGlyph {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
+        0 => Float32x2,
+        1 => Uint32,
+        2 => Float32x4
+    ];
+
+    fn description<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+// Mirrors `Screen` in `text.wgsl`
+#[repr(C)]
+#[derive(bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Clone, Copy)]
+struct TextScreen {
+    size: [f32; 2],
+    scale: f32,
+    _p0: u32,
+}
+
+// Lines of text drawn over the ray-traced image (i.e. timings).
+// Text is rebuilt on every frame it is drawn
+#[derive(Debug)]
+pub struct Text {
+    glyphs: Vec<TextGlyph>,
+    count: u32,
+    instances: wgpu::Buffer,
+    screen: wgpu::Buffer,
+    font: wgpu::Buffer,
+    group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl Text {
+    // Each glyph is drawn as two triangles
+    const VERTICES: u32 = 6;
+
+    // Size of a character cell (including spacing) before scaling
+    pub const CELL: [f32; 2] = [6., 9.];
+
+    // Glyphs are scaled up by an integer factor to stay crisp
+    const SCALE: f32 = 2.;
+
+    // The number of glyphs that fit in the instance buffer
+    const CAPACITY: usize = 1024;
+
+    pub fn new(
+        device: &wgpu::Device,
+        tex_format: wgpu::TextureFormat,
+        module: &wgpu::ShaderModule,
+    ) -> Self {
+        let instances = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: (mem::size_of::<TextGlyph>() * Self::CAPACITY) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let screen = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: mem::size_of::<TextScreen>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // Rows 0-3 are packed into the first word of each glyph, rows 4-6 into the second
+        let font = FONT
+            .iter()
+            .flat_map(|rows| {
+                let pack = |rows: &[u8]| rows
+                    .iter()
+                    .enumerate()
+                    .fold(0u32, |word, (idx, &row)| word | (row as u32) << (idx * 8));
+
+                [pack(&rows[0..4]), pack(&rows[4..7])]
+            })
+            .collect::<Vec<_>>();
+
+        let font = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&font),
+                usage: wgpu::BufferUsages::UNIFORM,
+            }
+        );
+
+        let uniform = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+            count: None,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+        };
+
+        let layout = device.create_bind_group_layout(
+            &wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[uniform(0), uniform(1)],
+            }
+        );
+
+        let group = device.create_bind_group(
+            &wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: screen.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: font.as_entire_binding(),
+                    },
+                ],
+            }
+        );
+
+        let pipeline_layout = device.create_pipeline_layout(
+            &wgpu::PipelineLayoutDescriptor {
+                label: None,
+                push_constant_ranges: &[],
+                bind_group_layouts: &[&layout],
+            }
+        );
+
+        // The SRGB texture view isn't available on web
+        cfg_if::cfg_if! {
+            if #[cfg(target_arch = "wasm32")] {
+                let fragment_format = tex_format;
+            } else {
+                let fragment_format = tex_format.add_srgb_suffix();
+            }
+        }
+
+        let pipeline = device.create_render_pipeline(
+            &wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module,
+                    entry_point: "vs_main",
+                    buffers: &[TextGlyph::description()],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: fragment_format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: None,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+            }
+        );
+
+        Self {
+            glyphs: Vec::new(),
+            count: 0,
+            instances,
+            screen,
+            font,
+            group,
+            pipeline,
+        }
+    }
+
+    // The height of a single line of text (pixels)
+    pub const fn line_height() -> f32 {
+        Self::CELL[1] * Self::SCALE
+    }
+
+    // Queues a line of text, with its top-left corner at `pos` (pixels).
+    // Lowercase letters are drawn as uppercase, unsupported characters as '?'
+    pub fn push(&mut self, text: &str, pos: [f32; 2], color: [f32; 4]) {
+        const BACKGROUND: [f32; 4] = [0., 0., 0., 0.6];
+
+        let [x, y] = pos;
+
+        let cell = |idx: usize, glyph: u32, color: [f32; 4]| TextGlyph {
+            pos: [x + (idx as f32 * Self::CELL[0] * Self::SCALE), y],
+            glyph,
+            _p0: 0,
+            color,
+        };
+
+        let chars = text.chars().count();
+
+        // The background is drawn first, so it sits behind the glyphs
+        self.glyphs.extend((0..chars).map(|idx| cell(idx, GLYPH_SOLID, BACKGROUND)));
+
+        self.glyphs.extend(text
+            .chars()
+            .map(|c| c.to_ascii_uppercase())
+            .map(|c| match c {
+                ' '..='_' => c as u32 - ' ' as u32,
+                _ => '?' as u32 - ' ' as u32,
+            })
+            .enumerate()
+            .map(|(idx, glyph)| cell(idx, glyph, color)));
+    }
+
+    // Writes the queued text to the GPU, then clears it for the next frame
+    pub fn upload(&mut self, queue: &wgpu::Queue, size: winit::dpi::PhysicalSize<u32>) {
+        self.glyphs.truncate(Self::CAPACITY);
+
+        self.count = self.glyphs.len() as u32;
+
+        if self.count > 0 {
+            queue.write_buffer(&self.instances, 0, bytemuck::cast_slice(&self.glyphs));
+
+            queue.write_buffer(&self.screen, 0, bytemuck::bytes_of(&TextScreen {
+                size: [size.width as f32, size.height as f32],
+                scale: Self::SCALE,
+                _p0: 0,
+            }));
+        }
+
+        self.glyphs.clear();
+    }
+
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        let Self {
+            count,
+            instances,
+            group,
+            pipeline, ..
+        } = self;
+
+        if *count == 0 { return; }
+
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, group, &[]);
+        render_pass.set_vertex_buffer(0, instances.slice(..));
+        render_pass.draw(0..Self::VERTICES, 0..*count);
+    }
+
+    // Only called when reloading scenes
+    pub fn destroy(&self) {
+        self.instances.destroy();
+        self.screen.destroy();
+        self.font.destroy();
+    }
+}
@@ -0,0 +1,167 @@
+use std::sync;
+
+// Resolved timestamps are copied here before being read back
+#[derive(Debug)]
+struct PassQueries {
+    // Nanoseconds per timestamp tick
+    period: f32,
+    set: wgpu::QuerySet,
+    resolve: wgpu::Buffer,
+    read: wgpu::Buffer,
+}
+
+// Measures the duration of a single compute pass with timestamp queries.
+// Adapters without TIMESTAMP_QUERY fall back to the time between
+// submission and completion, which includes any queueing overhead
+#[derive(Debug)]
+pub struct PassTimer {
+    queries: Option<PassQueries>,
+    // Set while a measurement is in flight
+    pending: sync::Arc<sync::atomic::AtomicBool>,
+    // Set once the timestamps can be read back
+    completed: sync::Arc<sync::atomic::AtomicBool>,
+    // The smoothed duration of recent passes (MS)
+    ms: sync::Arc<sync::Mutex<Option<f32>>>,
+}
+
+impl PassTimer {
+    // Weight given to each new measurement
+    const SMOOTHING: f32 = 0.1;
+
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let size = 2 * wgpu::QUERY_SIZE as u64;
+
+        let queries = device
+            .features()
+            .contains(wgpu::Features::TIMESTAMP_QUERY)
+            .then(|| PassQueries {
+                period: queue.get_timestamp_period(),
+                set: device.create_query_set(&wgpu::QuerySetDescriptor {
+                    label: None,
+                    ty: wgpu::QueryType::Timestamp,
+                    count: 2,
+                }),
+                resolve: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: None,
+                    size,
+                    usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                    mapped_at_creation: false,
+                }),
+                read: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: None,
+                    size,
+                    usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                }),
+            });
+
+        Self {
+            queries,
+            pending: sync::Arc::new(sync::atomic::AtomicBool::new(false)),
+            completed: sync::Arc::new(sync::atomic::AtomicBool::new(false)),
+            ms: sync::Arc::new(sync::Mutex::new(None)),
+        }
+    }
+
+    fn record(ms: &sync::Mutex<Option<f32>>, sample: f32) {
+        if let Ok(mut ms) = ms.lock() {
+            *ms = Some(match *ms {
+                Some(prev) => prev + (sample - prev) * Self::SMOOTHING,
+                None => sample,
+            });
+        }
+    }
+
+    // Reads back a completed measurement, must be called before each timed pass
+    pub fn poll(&mut self) {
+        let Some(PassQueries { period, read, .. }) = &self.queries else { return; };
+
+        if !self.completed.swap(false, sync::atomic::Ordering::Acquire) { return; }
+
+        {
+            let data = read.slice(..).get_mapped_range();
+
+            let timestamps: &[u64] = bytemuck::cast_slice(&data);
+
+            if let [start, end] = timestamps[..] {
+                let ms = end.saturating_sub(start) as f32 * period / 1_000_000.;
+
+                Self::record(&self.ms, ms);
+            }
+        }
+
+        read.unmap();
+
+        self.pending.store(false, sync::atomic::Ordering::Release);
+    }
+
+    // Only one measurement is taken at a time
+    pub fn armed(&self) -> bool {
+        !self.pending.load(sync::atomic::Ordering::Acquire)
+    }
+
+    // Passed to the timed pass while armed
+    pub fn timestamp_writes(&self) -> Option<wgpu::ComputePassTimestampWrites<'_>> {
+        self.queries.as_ref().map(|PassQueries { set, .. }| wgpu::ComputePassTimestampWrites {
+            query_set: set,
+            beginning_of_pass_write_index: Some(0),
+            end_of_pass_write_index: Some(1),
+        })
+    }
+
+    // Encoded after the timed pass while armed
+    pub fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+        if let Some(PassQueries { set, resolve, read, .. }) = &self.queries {
+            encoder.resolve_query_set(set, 0..2, resolve, 0);
+            encoder.copy_buffer_to_buffer(resolve, 0, read, 0, resolve.size());
+        }
+    }
+
+    // Called once the timed pass has been submitted
+    pub fn post(&self, queue: &wgpu::Queue) {
+        self.pending.store(true, sync::atomic::Ordering::Release);
+
+        match &self.queries {
+            Some(PassQueries { read, .. }) => {
+                let completed = self.completed.clone();
+
+                read.slice(..).map_async(wgpu::MapMode::Read, move |_| {
+                    completed.store(true, sync::atomic::Ordering::Release);
+                });
+            },
+            None => {
+                let pending = self.pending.clone();
+
+                let ms = self.ms.clone();
+
+                let start = chrono::Local::now();
+
+                queue.on_submitted_work_done(move || {
+                    let elapsed = chrono::Local::now() - start;
+
+                    Self::record(&ms, elapsed.num_microseconds().unwrap_or(0) as f32 / 1000.);
+
+                    pending.store(false, sync::atomic::Ordering::Release);
+                });
+            },
+        }
+    }
+
+    // The smoothed duration of recent passes (MS)
+    pub fn ms(&self) -> Option<f32> {
+        self.ms.lock().ok().and_then(|ms| *ms)
+    }
+
+    // Whether `ms` comes from timestamp queries
+    pub fn precise(&self) -> bool {
+        self.queries.is_some()
+    }
+
+    // Only called when reloading scenes
+    pub fn destroy(&self) {
+        if let Some(PassQueries { resolve, read, .. }) = &self.queries {
+            resolve.destroy();
+            read.destroy();
+        }
+    }
+}
@@ -0,0 +1,872 @@
+pub mod plot;
+
+use std::{fs, mem, path, str, sync, thread};
+
+use anyhow::Context as _;
+
+use winit::dpi;
+
+use crate::handlers;
+
+pub struct SchedulerEntry<'a> {
+    pub ty: wgpu::BindingType,
+    pub resource: wgpu::BindingResource<'a>,
+}
+
+// Describes the run that a Scheduler is responsible for
+#[derive(Clone)]
+#[derive(Debug)]
+pub struct SchedulerInfo {
+    pub adapter: wgpu::AdapterInfo,
+    pub stats: handlers::IntrsStats,
+    pub prims: usize,
+    pub size: dpi::PhysicalSize<u32>,
+    pub wg: u32,
+    pub compute: crate::ComputeConfig,
+}
+
+pub trait Scheduler {
+    type Config: Default + Clone;
+
+    fn init(
+        queue: &wgpu::Queue, 
+        device: &wgpu::Device, 
+        info: SchedulerInfo,
+        config: Self::Config,
+    ) -> Self;
+
+    fn entry(&self) -> Option<SchedulerEntry<'_>>;
+    fn desc(&self) -> wgpu::ComputePassDescriptor<'_>;
+    fn pre(&self, encoder: &mut wgpu::CommandEncoder);
+    fn post(&self, queue: &wgpu::Queue, device: &wgpu::Device);
+    fn ready(&mut self) -> bool;
+
+    // Indicates that compute passes should be submitted every frame,
+    // even if nothing in the scene has changed
+    fn continuous(&self) -> bool;
+
+    // Once this returns true, the event loop exits
+    fn finished(&self) -> bool;
+
+    // Waits on any work the scheduler has left (e.g. writing results),
+    // returning the first error it ran into
+    fn finish(self) -> anyhow::Result<()> where Self: Sized;
+
+    // Requests the instrumented compute shader,
+    // which writes its counters to binding 1 of the config group.
+    // The Scheduler must provide this binding through `entry`
+    fn instrumented(&self) -> bool;
+}
+
+pub struct DefaultScheduler {
+    completed: sync::Arc<sync::atomic::AtomicBool>,
+    buffer: wgpu::Buffer,
+    buffer_read: wgpu::Buffer,
+}
+
+impl Scheduler for DefaultScheduler {
+    type Config = ();
+
+    fn init(
+        _queue: &wgpu::Queue, 
+        device: &wgpu::Device, 
+        _info: SchedulerInfo,
+        _config: (),
+    ) -> Self {
+
+        Self {
+            completed: sync::Arc::new(sync::atomic::AtomicBool::new(true)),
+            buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: None,
+                size: wgpu::MAP_ALIGNMENT,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            }),
+            buffer_read: device.create_buffer(&wgpu::BufferDescriptor {
+                label: None,
+                size: wgpu::MAP_ALIGNMENT,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: true,
+            }),
+        }
+    }
+
+    fn entry(&self) -> Option<SchedulerEntry<'_>> {
+        let entry = SchedulerEntry { 
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            }, 
+            resource: self.buffer.as_entire_binding(), 
+        };
+
+        Some(entry)
+    }
+
+    fn desc(&self) -> wgpu::ComputePassDescriptor<'_> {
+        wgpu::ComputePassDescriptor::default()
+    }
+
+    fn pre(&self, encoder: &mut wgpu::CommandEncoder) {
+        let Self { 
+            buffer, 
+            buffer_read, .. 
+        } = self;
+
+        // Queue the copy operation
+        encoder.copy_buffer_to_buffer(
+            buffer, 0, 
+            buffer_read, 0, 
+            wgpu::MAP_ALIGNMENT,
+        );
+    }
+
+    fn post(&self, _queue: &wgpu::Queue, _device: &wgpu::Device) {
+        let Self { 
+            completed, 
+            buffer_read, .. 
+        } = self;
+
+        let completed = completed.clone();
+        buffer_read.slice(..).map_async(wgpu::MapMode::Read, move |_| {
+            completed.store(true, sync::atomic::Ordering::Release);
+        });
+    }
+
+    fn ready(&mut self) -> bool {
+        let Self { 
+            completed, 
+            buffer_read, .. 
+        } = self;
+
+        let completed = completed
+            .fetch_and(false, sync::atomic::Ordering::Acquire);
+
+        if completed {
+            buffer_read.unmap();
+        }
+
+        completed
+    }
+
+    fn continuous(&self) -> bool { false }
+
+    fn finished(&self) -> bool { false }
+
+    fn finish(self) -> anyhow::Result<()> { Ok(()) }
+
+    fn instrumented(&self) -> bool { false }
+}
+
+// The file types BenchScheduler can write its results to
+#[derive(Clone, Copy)]
+#[derive(PartialEq, Eq)]
+#[derive(Debug)]
+pub enum BenchFormat {
+    Png,
+    Svg,
+    Csv,
+    Json,
+}
+
+impl BenchFormat {
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            BenchFormat::Png => "png",
+            BenchFormat::Svg => "svg",
+            BenchFormat::Csv => "csv",
+            BenchFormat::Json => "json",
+        }
+    }
+}
+
+impl str::FromStr for BenchFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "png" => Ok(BenchFormat::Png),
+            "svg" => Ok(BenchFormat::Svg),
+            "csv" => Ok(BenchFormat::Csv),
+            "json" => Ok(BenchFormat::Json),
+            _ => Err(format!("Unrecognized benchmark format [{s}]")),
+        }
+    }
+}
+
+// Runtime options for BenchScheduler
+#[derive(Clone)]
+#[derive(Debug)]
+pub struct BenchConfig {
+    // The total number of passes to record (100 by default)
+    // If None: Benchmarking won't stop, results are only written every `interval`
+    pub passes: Option<usize>,
+
+    // The number of passes to discard before recording begins
+    pub warmup: usize,
+
+    // Write results every N recorded passes
+    // If None: Results are only written once all passes have been recorded
+    pub interval: Option<usize>,
+
+    // Results are written to `{out}/{name}.{extension}`
+    pub out: path::PathBuf,
+    pub name: String,
+    pub formats: Vec<BenchFormat>,
+
+    // The name of the scene being benchmarked (included in metadata)
+    pub scene: String,
+
+    // Count rays, node visits and intersection tests on the GPU
+    // NOTE: The atomics involved inflate compute pass durations
+    pub instrument: bool,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            passes: Some(100),
+            warmup: 0,
+            interval: Some(10),
+            out: path::PathBuf::from("."),
+            name: String::from("benchmark"),
+            formats: vec![BenchFormat::Png],
+            scene: String::new(),
+            instrument: false,
+        }
+    }
+}
+
+// Serializable mirror of wgpu::AdapterInfo
+#[derive(Clone)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Debug)]
+pub struct BenchAdapter {
+    pub name: String,
+    pub vendor: u32,
+    pub device: u32,
+    pub device_type: String,
+    pub driver: String,
+    pub driver_info: String,
+    pub backend: String,
+}
+
+impl From<wgpu::AdapterInfo> for BenchAdapter {
+    fn from(value: wgpu::AdapterInfo) -> Self {
+        let wgpu::AdapterInfo {
+            name,
+            vendor,
+            device,
+            device_type,
+            driver,
+            driver_info,
+            backend,
+        } = value;
+
+        Self {
+            name,
+            vendor,
+            device,
+            device_type: format!("{device_type:?}"),
+            driver,
+            driver_info,
+            backend: format!("{backend:?}"),
+        }
+    }
+}
+
+// Everything needed to reproduce a benchmark run
+#[derive(Clone)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Debug)]
+pub struct BenchMeta {
+    pub adapter: BenchAdapter,
+    pub scene: String,
+    pub prims: usize,
+    pub handler: String,
+    pub size: usize,
+    // Construction time of the handler's hierarchy (MS)
+    #[serde(default)]
+    pub build: Option<f32>,
+    pub width: u32,
+    pub height: u32,
+    pub workgroup_size: u32,
+    pub bounces: u32,
+    pub warmup: usize,
+    pub date: String,
+}
+
+impl BenchMeta {
+    pub fn new(info: SchedulerInfo, scene: String, warmup: usize) -> Self {
+        let SchedulerInfo {
+            adapter,
+            stats: handlers::IntrsStats { name, size, build },
+            prims,
+            size: dpi::PhysicalSize { width, height },
+            wg,
+            compute,
+        } = info;
+
+        Self {
+            adapter: adapter.into(),
+            scene,
+            prims,
+            handler: String::from(name),
+            size,
+            build,
+            width,
+            height,
+            workgroup_size: wg,
+            bounces: compute.bounces,
+            warmup,
+            date: chrono::Local::now().to_rfc3339(),
+        }
+    }
+}
+
+// Statistics over the compute pass durations of a single benchmark
+#[derive(Clone, Copy)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Debug)]
+pub struct BenchSummary {
+    pub passes: usize,
+    pub mean: f32,
+    pub median: f32,
+    pub p95: f32,
+    pub stddev: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl BenchSummary {
+    pub fn new(samples: &[f32]) -> Self {
+        let mut sorted = samples.to_vec();
+            sorted.sort_by(f32::total_cmp);
+
+        let passes = sorted.len();
+
+        // Nearest-rank percentile
+        let percentile = |p: f32| -> f32 {
+            if passes == 0 { return 0.; }
+
+            let rank = (p * passes as f32).ceil() as usize;
+
+            sorted[rank.clamp(1, passes) - 1]
+        };
+
+        let mean = sorted.iter().sum::<f32>() / passes.max(1) as f32;
+
+        let median = match passes {
+            0 => 0.,
+            _ if passes.is_multiple_of(2) => //
+                0.5 * (sorted[passes / 2 - 1] + sorted[passes / 2]),
+            _ => sorted[passes / 2],
+        };
+
+        // Sample standard deviation
+        let stddev = if passes > 1 {
+            let sum = sorted
+                .iter()
+                .map(|value| (value - mean) * (value - mean))
+                .sum::<f32>();
+
+            (sum / (passes - 1) as f32).sqrt()
+        } else { 0. };
+
+        Self {
+            passes,
+            mean,
+            median,
+            p95: percentile(0.95),
+            stddev,
+            min: sorted.first().copied().unwrap_or_default(),
+            max: sorted.last().copied().unwrap_or_default(),
+        }
+    }
+}
+
+// A single row of counters as written by the instrumented shader
+// NOTE: The layout must match `Counters` in `shaders::COUNTERS`
+#[repr(C)]
+#[derive(bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Clone, Copy)]
+struct BenchCounts {
+    pixels: u32,
+    rays: u32,
+    nodes: u32,
+    tests: u32,
+    shadows: u32,
+    bounces: u32,
+    depth: u32,
+}
+
+// Traversal statistics gathered by an instrumented benchmark
+#[derive(Clone, Copy)]
+#[derive(Default)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Debug)]
+pub struct BenchCounters {
+    // Totals across all recorded passes
+    // Rays include camera, reflection and shadow rays
+    pub pixels: u64,
+    pub rays: u64,
+    pub nodes: u64,
+    pub tests: u64,
+    pub shadows: u64,
+    pub bounces: u64,
+
+    // The deepest bounce reached by any pixel
+    pub depth: u32,
+
+    // Averages per pixel, per pass
+    pub rays_avg: f32,
+    pub nodes_avg: f32,
+    pub tests_avg: f32,
+    pub shadows_avg: f32,
+    pub bounces_avg: f32,
+
+    pub rays_per_sec: f64,
+}
+
+impl BenchCounters {
+    fn from_rows(rows: &[BenchCounts]) -> Self {
+        rows.iter().fold(Self::default(), |mut acc, row| {
+            acc.pixels += row.pixels as u64;
+            acc.rays += row.rays as u64;
+            acc.nodes += row.nodes as u64;
+            acc.tests += row.tests as u64;
+            acc.shadows += row.shadows as u64;
+            acc.bounces += row.bounces as u64;
+            acc.depth = acc.depth.max(row.depth);
+            acc
+        })
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.pixels += other.pixels;
+        self.rays += other.rays;
+        self.nodes += other.nodes;
+        self.tests += other.tests;
+        self.shadows += other.shadows;
+        self.bounces += other.bounces;
+        self.depth = self.depth.max(other.depth);
+    }
+
+    // Derive averages from the totals
+    // The duration (MS) is the sum of all recorded passes
+    fn summarize(mut self, duration: f32) -> Self {
+        let pixels = self.pixels.max(1) as f64;
+
+        self.rays_avg = (self.rays as f64 / pixels) as f32;
+        self.nodes_avg = (self.nodes as f64 / pixels) as f32;
+        self.tests_avg = (self.tests as f64 / pixels) as f32;
+        self.shadows_avg = (self.shadows as f64 / pixels) as f32;
+        self.bounces_avg = (self.bounces as f64 / pixels) as f32;
+
+        self.rays_per_sec = if duration > 0. {
+            self.rays as f64 / (0.001 * duration as f64)
+        } else { 0. };
+
+        self
+    }
+}
+
+// The contents of a JSON benchmark result
+#[derive(Clone)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Debug)]
+pub struct BenchReport {
+    pub meta: BenchMeta,
+    pub summary: BenchSummary,
+    // Only present for instrumented benchmarks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counters: Option<BenchCounters>,
+    // Compute pass durations (MS)
+    pub samples: Vec<f32>,
+}
+
+impl BenchReport {
+    // Write the report in each of the requested formats
+    fn save(
+        &self, 
+        out: &path::Path, 
+        name: &str, 
+        formats: &[BenchFormat]
+    ) -> anyhow::Result<()> {
+        let Self { meta, summary, counters, samples } = self;
+
+        for format in formats {
+            let path = out.join(format!("{name}.{}", format.extension()));
+
+            match format {
+                BenchFormat::Png => {
+                    let svg = plot::graph(samples, Some(summary.mean), meta)?;
+
+                    plot::rasterize(&svg)?.save_png(path)?;
+                },
+                BenchFormat::Svg => {
+                    fs::write(path, plot::graph(samples, Some(summary.mean), meta)?)?;
+                },
+                BenchFormat::Csv => {
+                    // Metadata (and counters) are prepended as comments
+                    let mut lines = Vec::new();
+
+                    for value in [serde_json::to_value(meta)?, serde_json::to_value(counters)?] {
+                        if let Some(value) = value.as_object() {
+                            lines.extend({
+                                value
+                                    .iter()
+                                    .map(|(key, value)| format!("# {key}: {value}"))
+                            });
+                        }
+                    }
+
+                    lines.push(String::from("pass,duration"));
+                    lines.extend({
+                        samples
+                            .iter()
+                            .enumerate()
+                            .map(|(pass, value)| format!("{pass},{value}"))
+                    });
+
+                    fs::write(path, lines.join("\n"))?;
+                },
+                BenchFormat::Json => {
+                    fs::write(path, serde_json::to_string_pretty(self)?)?;
+                },
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// The buffers used to read back an instrumented pass
+#[derive(Debug)]
+struct BenchCountersBuffers {
+    completed: sync::Arc<sync::atomic::AtomicBool>,
+    buffer: wgpu::Buffer,
+    buffer_read: wgpu::Buffer,
+}
+
+// The results of a single compute pass
+struct BenchPass {
+    duration: f32,
+    counters: Option<BenchCounters>,
+}
+
+#[derive(Debug)]
+pub struct BenchScheduler {
+    period: f32,
+    completed: sync::Arc<sync::atomic::AtomicBool>,
+    set: wgpu::QuerySet,
+    buffer: wgpu::Buffer,
+    buffer_read: wgpu::Buffer,
+    counters: Option<BenchCountersBuffers>,
+    times_handle: thread::JoinHandle<anyhow::Result<()>>,
+    times_sender: sync::mpsc::Sender<BenchPass>,
+}
+
+impl Scheduler for BenchScheduler {
+    type Config = BenchConfig;
+
+    fn init(
+        queue: &wgpu::Queue, 
+        device: &wgpu::Device, 
+        info: SchedulerInfo,
+        config: BenchConfig,
+    ) -> Self {
+        let (times_sender, times_reciever) = sync::mpsc::channel();
+
+        // Each row of pixels has its own set of counters
+        let counters = config.instrument.then(|| {
+            let size = (info.size.height.max(1) as usize * //
+                mem::size_of::<BenchCounts>()) as u64;
+
+            BenchCountersBuffers {
+                completed: sync::Arc::new(sync::atomic::AtomicBool::new(true)),
+                buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: None,
+                    size,
+                    usage: wgpu::BufferUsages::STORAGE //
+                        | wgpu::BufferUsages::COPY_SRC //
+                        | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                }),
+                buffer_read: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: None,
+                    size,
+                    usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: true,
+                }),
+            }
+        });
+
+        let times_handle = std::thread::spawn(move || {
+            let BenchConfig {
+                passes,
+                warmup,
+                interval,
+                out,
+                name,
+                formats,
+                scene, ..
+            } = config;
+
+            let meta = BenchMeta::new(info, scene, warmup);
+
+            fs::create_dir_all(&out).with_context(|| {
+                format!("Failed to create benchmark directory {}", out.display())
+            })?;
+
+            let mut data = Vec::new();
+
+            // Totals across all recorded (instrumented) passes
+            let mut totals: Option<BenchCounters> = None;
+
+            // The number of passes that have been discarded
+            let mut skipped = 0;
+
+            loop {
+                match times_reciever.recv() {
+                    Ok(BenchPass { duration: 0., .. }) => continue,
+                    Ok(_) if skipped < warmup => skipped += 1,
+                    Ok(BenchPass { duration, counters }) => {
+                        data.push(duration);
+
+                        if let Some(counters) = counters {
+                            totals.get_or_insert_with(BenchCounters::default).merge(&counters);
+                        }
+
+                        // Indicates that the final data point has been collected
+                        let complete = passes == Some(data.len());
+
+                        // If the last pass has completed or interval is reached
+                        if interval
+                            .map(|i| data.len() % i)
+                            .unwrap_or(1) == 0 || complete {
+
+                            let report = BenchReport {
+                                meta: meta.clone(),
+                                summary: BenchSummary::new(&data),
+                                counters: totals.map(|totals| {
+                                    totals.summarize(data.iter().sum())
+                                }),
+                                samples: data.clone(),
+                            };
+
+                            report.save(&out, &name, &formats).with_context(|| {
+                                format!("Failed to save benchmark [{name}] to {}", out.display())
+                            })?;
+                        }
+
+                        // Break if we've recorded all data points
+                        if complete {
+                            break Ok(());
+                        }
+                    },
+                    Err(_) => break Ok(()),
+                }
+            } 
+        });
+
+        Self {
+            period: queue.get_timestamp_period(),
+            completed: sync::Arc::new(sync::atomic::AtomicBool::new(true)),
+            set: device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: None,
+                ty: wgpu::QueryType::Timestamp,
+                count: 2,
+            }),
+            buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: None,
+                size: 2 * wgpu::QUERY_SIZE as u64,
+                usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            }),
+            buffer_read: device.create_buffer(&wgpu::BufferDescriptor {
+                label: None,
+                size: 2 * wgpu::QUERY_SIZE as u64,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: true,
+            }),
+            counters,
+            times_handle,
+            times_sender,
+        }
+    }
+
+    fn entry(&self) -> Option<SchedulerEntry<'_>> {
+        let BenchCountersBuffers { buffer, .. } = self.counters.as_ref()?;
+
+        let entry = SchedulerEntry {
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: false },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            resource: buffer.as_entire_binding(),
+        };
+
+        Some(entry)
+    }
+
+    fn desc(&self) -> wgpu::ComputePassDescriptor<'_> {
+        let Self { set: query_set, .. } = self;
+
+        wgpu::ComputePassDescriptor {
+            label: None,
+            timestamp_writes: Some(wgpu::ComputePassTimestampWrites {
+                query_set,
+                beginning_of_pass_write_index: Some(0),
+                end_of_pass_write_index: Some(1),
+            }),
+        }
+    }
+
+    fn pre(&self, encoder: &mut wgpu::CommandEncoder) {
+        let Self {
+            set: query_set, 
+            buffer, 
+            counters, .. 
+        } = self;
+
+        encoder.resolve_query_set(query_set, 0..2, buffer, 0);    
+
+        // Read the counters back, then reset them for the next pass
+        if let Some(BenchCountersBuffers { buffer, buffer_read, .. }) = counters {
+            encoder.copy_buffer_to_buffer(
+                buffer, 0, 
+                buffer_read, 0, 
+                buffer.size(),
+            );
+
+            encoder.clear_buffer(buffer, 0, None);
+        }
+    }
+
+    fn post(&self, queue: &wgpu::Queue, device: &wgpu::Device) {
+        let Self { 
+            completed,
+            buffer, 
+            buffer_read, .. 
+        } = self;
+
+        // We will submit a second set of encoded commands which 
+        // is responsible for copying timestamp information to `buffer_read`
+        let mut encoder = device.create_command_encoder(&{
+            wgpu::CommandEncoderDescriptor::default()
+        });
+
+        // Queue the copy operation
+        encoder.copy_buffer_to_buffer(
+            buffer, 0, 
+            buffer_read, 0, 
+            2 * wgpu::QUERY_SIZE as u64,
+        );
+
+        // Submit the command
+        queue.submit(Some(encoder.finish()));
+
+        // Update state when the copy has executed...
+        // by extension, this tells us when the 
+        let completed = completed.clone();
+        buffer_read.slice(..).map_async(wgpu::MapMode::Read, move |_| {
+            completed.store(true, sync::atomic::Ordering::Release);
+        });
+
+        if let Some(BenchCountersBuffers { completed, buffer_read, .. }) = &self.counters {
+            let completed = completed.clone();
+            buffer_read.slice(..).map_async(wgpu::MapMode::Read, move |_| {
+                completed.store(true, sync::atomic::Ordering::Release);
+            });
+        }
+    }
+
+    fn ready(&mut self) -> bool {
+        let Self {
+            period,
+            completed,
+            buffer_read, 
+            counters,
+            times_sender, .. 
+        } = self;
+
+        // Instrumented passes also wait on the counters
+        if let Some(BenchCountersBuffers { completed, .. }) = counters {
+            if !completed.load(sync::atomic::Ordering::Acquire) {
+                return false;
+            }
+        }
+
+        let completed = completed
+            .fetch_and(false, sync::atomic::Ordering::Acquire);
+
+        if completed {
+            let data = buffer_read.slice(..).get_mapped_range();
+
+            let timestamps = data
+                .chunks_exact(wgpu::QUERY_SIZE as usize)
+                .take(2)
+                .map(|time| u64::from_ne_bytes(time.try_into().unwrap()))
+                .collect::<Vec<_>>();
+
+            let [start, end, ..] = timestamps[..] else { unreachable!(); };
+
+            // Collect the counters and release the buffer
+            let counters = counters.as_ref().map(|counters| {
+                let BenchCountersBuffers { 
+                    completed, 
+                    buffer_read, .. 
+                } = counters;
+
+                let totals = BenchCounters::from_rows({
+                    bytemuck::cast_slice(&buffer_read.slice(..).get_mapped_range())
+                });
+
+                completed.store(false, sync::atomic::Ordering::Release);
+
+                buffer_read.unmap();
+
+                totals
+            });
+
+            if let Some(frame_time) = end.checked_sub(start) {
+                let frame_time = 0.000001 * *period * frame_time as f32;
+
+                let _ = times_sender.send(BenchPass { 
+                    duration: frame_time, 
+                    counters,
+                });
+            }
+        }  
+
+        if completed {
+            buffer_read.unmap();
+        }
+
+        completed
+    }
+
+    fn continuous(&self) -> bool { true }
+
+    fn finished(&self) -> bool {
+        self.times_handle.is_finished()
+    }
+
+    fn finish(self) -> anyhow::Result<()> {
+        let Self { times_handle, times_sender, .. } = self;
+
+        // The thread stops early once nothing else can be sent
+        drop(times_sender);
+
+        times_handle
+            .join()
+            .map_err(|_| anyhow::anyhow!("Benchmark thread panicked"))?
+    }
+
+    fn instrumented(&self) -> bool {
+        self.counters.is_some()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,299 @@
+use resvg::tiny_skia;
+
+use plotlib::{repr, view, style, page};
+
+use super::{BenchAdapter, BenchMeta, BenchReport};
+
+// Series colors, these are cycled through when comparing handlers
+const PALETTE: &[&str] = &[
+    "#E41A1C", "#377EB8", "#4DAF4A", "#984EA3", 
+    "#FF7F00", "#A65628", "#F781BF", "#999999",
+];
+
+// Construct a graph (SVG) from data points
+pub(super) fn graph(
+    samples: &[f32], 
+    avg: Option<f32>, 
+    meta: &BenchMeta,
+) -> anyhow::Result<Vec<u8>> {
+    let data = samples
+        .iter()
+        .enumerate()
+        .map(|(idx, &value)| (idx as f64, value as f64))
+        .collect::<Vec<_>>();
+
+    let data_min = data
+        .iter()
+        .map(|(_, value)| *value)
+        .fold(f64::INFINITY, |a, b| a.min(b));
+
+    let data_max = data
+        .iter()
+        .map(|(_, value)| *value)
+        .fold(f64::NEG_INFINITY, |a, b| a.max(b));
+
+    let data_len = data.len();
+
+    let chart_view = { 
+        let chart = repr::Plot::new(data)
+            .legend(String::from("Compute Pass Duration (MS)"))
+            .line_style(style::LineStyle::new().colour("#FF0000"));
+
+        let chart_title = {
+            let BenchMeta { handler, .. } = meta;
+
+            repr::Plot::new(Vec::with_capacity(0))
+                .legend(format!("Handler: {handler}"))
+        };
+
+        let chart_size = {
+            let BenchMeta { size, .. } = meta;
+
+            repr::Plot::new(Vec::with_capacity(0))
+                .legend(format!("Size: {size} bytes"))
+        };
+
+        let chart_adapter = {
+            let BenchMeta { 
+                adapter: BenchAdapter { name, backend, .. }, .. 
+            } = meta;
+
+            repr::Plot::new(Vec::with_capacity(0))
+                .legend(format!("Adapter: {name} ({backend})"))
+        };
+
+        let chart_avg = {
+            let chart_avg = avg
+                .map(|avg| format!("Average: {avg}ms"))
+                .unwrap_or(String::from(""));
+            
+            repr::Plot::new(Vec::with_capacity(0))
+                .legend(chart_avg)
+        };
+
+        view::ContinuousView::new()
+            .add(chart_title)
+            .add(chart_size)
+            .add(chart_adapter)
+            .add(chart_avg)
+            .add(chart)
+            .x_range(0., data_len as f64)
+            .x_label("Frame")
+            .y_range(data_min, data_max)
+    };
+
+    svg(&chart_view)
+}
+
+// Describes the configuration shared by a set of compared reports
+fn describe(reports: &[&BenchReport]) -> String {
+    let Some(BenchReport { meta, .. }) = reports.first() else {
+        return String::new();
+    };
+
+    let BenchMeta { 
+        scene, 
+        width, 
+        height, 
+        bounces, .. 
+    } = meta;
+
+    format!("{scene}, {width}x{height}, {bounces} bounces")
+}
+
+fn svg(view: &dyn view::View) -> anyhow::Result<Vec<u8>> {
+    match page::Page::single(view).to_svg() {
+        Ok(svg) => {
+            let mut bytes: Vec<u8> = Vec::new();
+
+            svg::write(&mut bytes, &svg)?;
+
+            Ok(bytes)
+        },
+        Err(e) => anyhow::bail!(e),
+    }
+}
+
+// Overlay the compute pass durations of each report on one chart
+pub fn lines(reports: &[&BenchReport]) -> anyhow::Result<Vec<u8>> {
+    let samples = reports
+        .iter()
+        .flat_map(|BenchReport { samples, .. }| samples.iter())
+        .map(|&value| value as f64);
+
+    let data_min = samples.clone().fold(f64::INFINITY, f64::min);
+    let data_max = samples.fold(f64::NEG_INFINITY, f64::max);
+
+    let data_len = reports
+        .iter()
+        .map(|BenchReport { samples, .. }| samples.len())
+        .max()
+        .unwrap_or_default();
+
+    let mut chart_view = view::ContinuousView::new()
+        .x_range(0., data_len as f64)
+        .x_label(format!("Frame ({})", describe(reports)))
+        .y_label("Compute Pass Duration (MS)")
+        .y_range(data_min, data_max);
+
+    for (idx, report) in reports.iter().enumerate() {
+        let BenchReport { meta, summary, samples, .. } = report;
+
+        let data = samples
+            .iter()
+            .enumerate()
+            .map(|(idx, &value)| (idx as f64, value as f64))
+            .collect::<Vec<_>>();
+
+        let colour = PALETTE[idx % PALETTE.len()];
+
+        chart_view = chart_view.add({
+            repr::Plot::new(data)
+                .legend(format!("{} (Average: {}ms)", meta.handler, summary.mean))
+                .line_style(style::LineStyle::new().colour(colour))
+        });
+    }
+
+    svg(&chart_view)
+}
+
+// One box plot of compute pass durations per report
+pub fn boxes(reports: &[&BenchReport]) -> anyhow::Result<Vec<u8>> {
+    let mut chart_view = view::CategoricalView::new()
+        .x_label(format!("Handler ({})", describe(reports)))
+        .y_label("Compute Pass Duration (MS)");
+
+    for (idx, report) in reports.iter().enumerate() {
+        let BenchReport { meta, samples, .. } = report;
+
+        let colour = PALETTE[idx % PALETTE.len()];
+
+        chart_view = chart_view.add({
+            repr::BoxPlot::from_vec(samples.iter().map(|&v| v as f64).collect())
+                .label(meta.handler.clone())
+                .style(&style::BoxStyle::new().fill(colour))
+        });
+    }
+
+    svg(&chart_view)
+}
+
+// Mean speedup of each report relative to the baseline
+pub fn speedup(
+    reports: &[&BenchReport], 
+    baseline: &BenchReport
+) -> anyhow::Result<Vec<u8>> {
+    let mut chart_view = view::CategoricalView::new()
+        .x_label(format!("Handler ({})", describe(reports)))
+        .y_label(format!("Speedup vs. {}", baseline.meta.handler));
+
+    for (idx, report) in reports.iter().enumerate() {
+        let BenchReport { meta, summary, .. } = report;
+
+        let colour = PALETTE[idx % PALETTE.len()];
+
+        chart_view = chart_view.add({
+            repr::BarChart::new((baseline.summary.mean / summary.mean) as f64)
+                .label(meta.handler.clone())
+                .style(&style::BoxStyle::new().fill(colour))
+        });
+    }
+
+    svg(&chart_view)
+}
+
+// Render an SVG document into a Pixmap
+pub fn rasterize(bytes: &[u8]) -> anyhow::Result<tiny_skia::Pixmap> {
+    use resvg::usvg;
+
+    use once_cell::sync;
+
+    static FONT_DATABASE: sync::Lazy<usvg::fontdb::Database> = sync::Lazy::new(|| {
+        let mut fonts = usvg::fontdb::Database::new();
+        
+        fonts.load_system_fonts();
+        fonts
+    });
+
+    // Reparse it from this buffer
+    let tree = usvg::Tree::from_data(
+        bytes, 
+        &usvg::Options::default(), 
+        &FONT_DATABASE
+    )?;
+
+    // Grab the graph dimensions
+    let temp = tree.size();
+
+    let ( // Extract width and height from it
+        width, 
+        height
+    ) = (temp.width() as u32, temp.height() as u32);
+
+    // Construct a new Pixmap from these dimensions
+    let mut pixels = tiny_skia::Pixmap::new(width, height)
+        .unwrap();
+
+    // Render into the Pixmap
+    resvg::render(
+        &tree, 
+        tiny_skia::Transform::identity(), 
+        &mut pixels.as_mut()
+    );
+
+    Ok(pixels)
+}
+// Memory saved by each report against its traversal overhead,
+// both are percentages relative to the baseline
+pub fn memory(
+    reports: &[&BenchReport], 
+    baseline: &BenchReport
+) -> anyhow::Result<Vec<u8>> {
+    let points = reports
+        .iter()
+        .map(|report| (report, memory_tradeoff(report, baseline)))
+        .collect::<Vec<_>>();
+
+    let range = |values: Vec<f64>| {
+        let lo = values.iter().copied().fold(0., f64::min);
+        let hi = values.iter().copied().fold(0., f64::max);
+
+        // Leave some room around the outermost points
+        let pad = ((hi - lo) * 0.1).max(1.);
+
+        (lo - pad, hi + pad)
+    };
+
+    let (x_min, x_max) = range(points.iter().map(|(_, (saved, _))| *saved).collect());
+    let (y_min, y_max) = range(points.iter().map(|(_, (_, overhead))| *overhead).collect());
+
+    let mut chart_view = view::ContinuousView::new()
+        .x_range(x_min, x_max)
+        .x_label(format!("Memory Saved vs. {} (%, {})", baseline.meta.handler, describe(reports)))
+        .y_range(y_min, y_max)
+        .y_label(format!("Traversal Overhead vs. {} (%)", baseline.meta.handler));
+
+    for (idx, (report, point)) in points.into_iter().enumerate() {
+        let colour = PALETTE[idx % PALETTE.len()];
+
+        chart_view = chart_view.add({
+            repr::Plot::new(vec![point])
+                .legend(format!("{} ({} bytes)", report.meta.handler, report.meta.size))
+                .point_style(style::PointStyle::new()
+                    .marker(style::PointMarker::Circle)
+                    .colour(colour))
+        });
+    }
+
+    svg(&chart_view)
+}
+
+// The percentage of memory saved and the percentage increase in mean pass duration
+pub fn memory_tradeoff(report: &BenchReport, baseline: &BenchReport) -> (f64, f64) {
+    let size = baseline.meta.size.max(1) as f64;
+
+    let saved = 100. * (1. - report.meta.size as f64 / size);
+    let overhead = 100. * (report.summary.mean as f64 / baseline.summary.mean as f64 - 1.);
+
+    (saved, overhead)
+}
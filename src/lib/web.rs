@@ -1,8 +1,10 @@
+use std::mem;
+
 use winit::{dpi, window};
 
 use crate::{state, scene, handlers, timing};
 
-mod err {
+pub mod err {
     use std::{fmt, error};
 
     #[derive(Debug)]
@@ -33,7 +35,7 @@ mod err {
     }
 }
 
-pub type WebHandler = handlers::BvhIntrs;
+pub type WebHandler = handlers::DynIntrs;
 
 pub struct WebState {
     // These members are used for run_internal dispatch
@@ -43,8 +45,20 @@ pub struct WebState {
     pub scene: scene::Scene,
     pub scene_temp: Option<scene::Scene>,
 
+    // Edits are applied to the active scene in place
+    edits: Vec<scene::SceneEdit>,
+
+    // The handler used for every load, swapped live by `update_handler`
+    pub handler: handlers::IntrsKind,
+    update_handler: bool,
+
+    // A second handler drawn next to the first, set by `update_compare`
+    compare: Option<state::CompareConfig>,
+    update_compare: bool,
+
     // These flags tell us when there is an update pending
     update_config: bool,
+    capture: bool,
 
     // This value is only set when a resize event has occurred
     viewport: Option<dpi::PhysicalSize<u32>>,
@@ -53,8 +67,14 @@ pub struct WebState {
 pub static mut WEB_STATE: WebState = WebState {
     config: crate::Config::new(),
     update_config: true,
+    capture: false,
     scene: scene::Scene::Unloaded,
     scene_temp: None,
+    edits: Vec::new(),
+    handler: handlers::IntrsKind::Bvh,
+    update_handler: false,
+    compare: None,
+    update_compare: false,
     viewport: None,
 };
 
@@ -121,14 +141,24 @@ pub unsafe fn update<S>(state: &mut state::State<S>) -> bool
         WEB_STATE.update_config = false;
         
         state.update_config(WEB_STATE.config.compute);
+        state.update_overlay(WEB_STATE.config.overlay);
+        state.update_hud(WEB_STATE.config.hud);
 
         update = true;
     }
 
-    if let Some(scene) = WEB_STATE.scene_temp.take() {
+    if WEB_STATE.capture {
+        WEB_STATE.capture = false;
+
+        state.screenshot();
+    }
+
+    if let Some(mut scene) = WEB_STATE.scene_temp.take() {
+        scene.animate();
+
         update = match state.load::<WebHandler>(
             WEB_STATE.config, 
-            <WebHandler as handlers::IntrsHandler>::Config::default(),
+            WEB_STATE.handler,
             &scene
         ) {
             Ok(_) => {
@@ -138,6 +168,41 @@ pub unsafe fn update<S>(state: &mut state::State<S>) -> bool
         };
     }
 
+    // The scene (and its camera) carry over to the new handler
+    if WEB_STATE.update_handler {
+        WEB_STATE.update_handler = false;
+
+        if state.load::<WebHandler>(WEB_STATE.config, WEB_STATE.handler, &WEB_STATE.scene).is_ok() {
+            update = true;
+        }
+    }
+
+    // Loads keep the current comparison, so this only follows the page
+    if WEB_STATE.update_compare {
+        WEB_STATE.update_compare = false;
+
+        match state.compare(WEB_STATE.config, &WEB_STATE.scene, WEB_STATE.compare) {
+            Ok(_) => update = true,
+            Err(e) => {
+                let _ = note(&format!("Unable to compare handlers: {e}"));
+            },
+        }
+    }
+
+    for edit in mem::take(&mut WEB_STATE.edits) {
+        match state.edit::<WebHandler>(
+            WEB_STATE.config,
+            WEB_STATE.handler,
+            &mut WEB_STATE.scene,
+            edit,
+        ) {
+            Ok(_) => update = true,
+            Err(e) => {
+                let _ = note(&format!("Unable to edit scene: {e}"));
+            },
+        }
+    }
+
     if let Some(size) = WEB_STATE.viewport.take() {
         state.resize(WEB_STATE.config, size);
 
@@ -152,8 +217,6 @@ unsafe fn parse<'de, 'a: 'de, D>(
 ) -> Result<D, wasm_bindgen::JsValue>
     where D: serde::Deserialize<'de> {
     
-    use std::mem;
-
     match serialized.as_string() {
         Some(temp) => {
             let temp = mem::transmute::<&str, &'a str>(&temp);
@@ -189,6 +252,44 @@ pub unsafe fn update_scene(
     Ok(())
 }
 
+#[no_mangle]
+#[cfg(target_arch = "wasm32")]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen::prelude::wasm_bindgen)]
+pub unsafe fn edit_scene(
+    serialized: wasm_bindgen::JsValue
+) -> Result<(), crate::Failed> {
+    WEB_STATE.edits.push(parse::<scene::SceneEdit>(serialized)?);
+
+    Ok(())
+}
+
+#[no_mangle]
+#[cfg(target_arch = "wasm32")]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen::prelude::wasm_bindgen)]
+pub unsafe fn update_handler(
+    serialized: wasm_bindgen::JsValue
+) -> Result<(), crate::Failed> {
+    WEB_STATE.handler = parse::<handlers::IntrsKind>(serialized)?;
+
+    WEB_STATE.update_handler = true;
+
+    Ok(())
+}
+
+// Expects `null` to stop comparing
+#[no_mangle]
+#[cfg(target_arch = "wasm32")]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen::prelude::wasm_bindgen)]
+pub unsafe fn update_compare(
+    serialized: wasm_bindgen::JsValue
+) -> Result<(), crate::Failed> {
+    WEB_STATE.compare = parse::<Option<state::CompareConfig>>(serialized)?;
+
+    WEB_STATE.update_compare = true;
+
+    Ok(())
+}
+
 #[no_mangle]
 #[cfg(target_arch = "wasm32")]
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen::prelude::wasm_bindgen)]
@@ -200,4 +301,11 @@ pub unsafe fn update_viewport(
     });
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[no_mangle]
+#[cfg(target_arch = "wasm32")]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen::prelude::wasm_bindgen)]
+pub unsafe fn capture() {
+    WEB_STATE.capture = true;
+}
@@ -0,0 +1,430 @@
+use std::{env, fs, io, path, process};
+use std::io::Write as _;
+
+use anyhow::Context as _;
+
+use winit::dpi;
+
+use rt::{bvh, handlers, timing, scene};
+
+#[derive(Clone, Copy)]
+#[derive(clap::ValueEnum)]
+#[derive(Debug)]
+enum Handler {
+    Naive,
+    Bvh,
+    BvhSbvh,
+    BvhLbvh,
+    BvhRf,
+    BvhThreaded,
+    BvhWide,
+    BvhQuant8,
+    BvhQuant16,
+}
+
+#[derive(clap::Parser)]
+#[derive(Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// The scenes to benchmark (JSON)
+    #[clap(long = "scene", value_parser, required = true)]
+    scenes: Vec<String>,
+
+    /// The intersection handlers to benchmark
+    #[clap(long = "handler", value_enum, required = true)]
+    handlers: Vec<Handler>,
+
+    /// Each resolution is given as WIDTHxHEIGHT
+    #[clap(long = "resolution", value_parser = parse_resolution, required = true)]
+    resolutions: Vec<dpi::PhysicalSize<u32>>,
+
+    /// The maximum number of bounces per ray
+    #[clap(long = "bounces", value_parser, default_values_t = vec![4])]
+    bounces: Vec<u32>,
+
+    /// The width & height of each compute workgroup
+    #[clap(long = "workgroup-size", value_parser, default_value_t = 16)]
+    workgroup_size: u32,
+
+    /// The number of passes discarded before timing begins
+    #[clap(long, value_parser, default_value_t = 10)]
+    warmup: usize,
+
+    /// The number of timed passes
+    #[clap(long, value_parser, default_value_t = 100)]
+    passes: usize,
+
+    /// Limits the number of frames per second
+    #[clap(long, value_parser)]
+    fps: Option<u32>,
+
+    /// Replaces the scene's camera controller with a scripted path (JSON)
+    #[clap(long = "camera-path", value_parser)]
+    camera_path: Option<String>,
+
+    /// Count rays, node visits and intersection tests on the GPU
+    #[clap(long, action)]
+    instrument: bool,
+
+    /// The directory results are written to
+    #[clap(long, value_parser, default_value_t = String::from("bench"))]
+    out: String,
+
+    // Each configuration runs in its own process,
+    // which writes its report to `{out}/{record}.json`
+    #[clap(long, value_parser, hide = true)]
+    record: Option<String>,
+}
+
+fn parse_resolution(value: &str) -> Result<dpi::PhysicalSize<u32>, String> {
+    let (width, height) = value
+        .split_once('x')
+        .ok_or(format!("Resolution must be formatted as WIDTHxHEIGHT, found [{value}]"))?;
+
+    let width = width.parse::<u32>().map_err(|e| e.to_string())?;
+    let height = height.parse::<u32>().map_err(|e| e.to_string())?;
+
+    Ok(dpi::PhysicalSize::new(width, height))
+}
+
+// A single entry in the result table
+#[derive(serde::Serialize)]
+struct BenchRow {
+    meta: timing::BenchMeta,
+    summary: timing::BenchSummary,
+    counters: Option<timing::BenchCounters>,
+}
+
+impl BenchRow {
+    const HEADER: &'static str = "\
+        scene,prims,handler,size,build,adapter,backend,width,height,workgroup_size,\
+        bounces,warmup,passes,mean,median,p95,stddev,min,max,\
+        rays_avg,nodes_avg,tests_avg,shadows_avg,bounces_avg,depth,rays_per_sec\
+    ";
+
+    fn csv(&self) -> String {
+        let Self {
+            meta: timing::BenchMeta {
+                adapter: timing::BenchAdapter {
+                    name: adapter,
+                    backend, ..
+                },
+                scene,
+                prims,
+                handler,
+                size,
+                build,
+                width,
+                height,
+                workgroup_size,
+                bounces,
+                warmup, ..
+            },
+            summary: timing::BenchSummary {
+                passes,
+                mean,
+                median,
+                p95,
+                stddev,
+                min,
+                max,
+            },
+            counters,
+        } = self;
+
+        // Columns are left empty if the run wasn't instrumented
+        let counters = counters
+            .map(|counters| {
+                let timing::BenchCounters {
+                    rays_avg,
+                    nodes_avg,
+                    tests_avg,
+                    shadows_avg,
+                    bounces_avg,
+                    depth,
+                    rays_per_sec, ..
+                } = counters;
+
+                format!("\
+                    {rays_avg},{nodes_avg},{tests_avg},{shadows_avg},\
+                    {bounces_avg},{depth},{rays_per_sec}\
+                ")
+            })
+            .unwrap_or(String::from(",,,,,,"));
+
+        // Empty if the hierarchy was precomputed (or there isn't one)
+        let build = build.map(|build| build.to_string()).unwrap_or_default();
+
+        let [scene, handler, adapter, backend] = [scene, handler, adapter, backend].map(|field| {
+            Self::escape(field)
+        });
+
+        format!("\
+            {scene},{prims},{handler},{size},{build},{adapter},{backend},{width},{height},\
+            {workgroup_size},{bounces},{warmup},{passes},{mean},{median},{p95},{stddev},{min},{max},\
+            {counters}\
+        ")
+    }
+
+    // Quotes fields that contain separators, quotes or line breaks
+    fn escape(field: &str) -> String {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            String::from(field)
+        }
+    }
+}
+
+fn start<H: handlers::IntrsHandler>(
+    config: rt::Config,
+    config_handler: H::Config,
+    config_bench: timing::BenchConfig,
+    scene: scene::Scene,
+) -> anyhow::Result<()> {
+    pollster::block_on({
+        rt::run_native::<H, timing::BenchScheduler>
+            (config, config_handler, config_bench, scene, None)
+    })
+}
+
+// Benchmark a single configuration in this process
+// The report is written to `{out}/{name}.json`
+fn record(args: Args, name: String) -> anyhow::Result<()> {
+    let Args {
+        scenes,
+        handlers,
+        resolutions,
+        bounces,
+        workgroup_size,
+        warmup,
+        passes,
+        fps,
+        camera_path,
+        instrument,
+        out, ..
+    } = args;
+
+    let (
+        [scene],
+        [handler],
+        [size],
+        [bounces]
+    ) = (&scenes[..], &handlers[..], &resolutions[..], &bounces[..]) else {
+        anyhow::bail!("A recorded benchmark expects exactly one configuration");
+    };
+
+    let scene_name = path::Path::new(scene)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let scene_reader = io::BufReader::new({
+        fs::File::open(scene).with_context(|| format!("Failed to open scene {scene}"))?
+    });
+
+    let mut scene: scene::Scene = serde_json::from_reader(scene_reader)
+        .with_context(|| format!("Failed to parse scene {scene}"))?;
+
+    if let (
+        Some(path), 
+        scene::Scene::Active { camera_controller, .. }
+    ) = (camera_path, &mut scene) {
+        let path: scene::CameraPath = serde_json::from_reader({
+            io::BufReader::new(fs::File::open(&path)?)
+        }).with_context(|| format!("Failed to read camera path {path}"))?;
+
+        path.validate()?;
+
+        *camera_controller = scene::CameraController::path(path);
+    }
+
+    let config_default = rt::Config::default();
+    let config = rt::Config {
+        resolution: rt::Resolution::Fixed {
+            size: *size,
+            wg: workgroup_size,
+        },
+        compute: rt::ComputeConfig {
+            bounces: *bounces,
+            ..Default::default()
+        },
+        fps: fps.unwrap_or(config_default.fps),
+        ..config_default
+    };
+
+    let config_bench = timing::BenchConfig {
+        passes: Some(passes),
+        warmup,
+        interval: None,
+        out: path::PathBuf::from(out),
+        name,
+        formats: vec![timing::BenchFormat::Json],
+        scene: scene_name,
+        instrument,
+    };
+
+    match handler {
+        Handler::Naive => start::<handlers::BasicIntrs>
+            (config, (), config_bench, scene),
+        Handler::Bvh => start::<handlers::BvhIntrs>
+            (config, handlers::BvhConfig::Default, config_bench, scene),
+        Handler::BvhSbvh => start::<handlers::BvhIntrs>
+            (config, handlers::BvhConfig::Builder {
+                eps: handlers::BvhIntrs::default().eps,
+                builder: bvh::BvhBuilder::Sbvh,
+            }, config_bench, scene),
+        Handler::BvhLbvh => start::<handlers::BvhIntrs>
+            (config, handlers::BvhConfig::Builder {
+                eps: handlers::BvhIntrs::default().eps,
+                builder: bvh::BvhBuilder::Lbvh,
+            }, config_bench, scene),
+        Handler::BvhRf => start::<handlers::RfBvhIntrs>
+            (config, handlers::RfBvhConfig::Default, config_bench, scene),
+        Handler::BvhThreaded => start::<handlers::ThreadedBvhIntrs>
+            (config, handlers::BvhConfig::Default, config_bench, scene),
+        Handler::BvhWide => start::<handlers::WideBvhIntrs>
+            (config, handlers::WideBvhConfig::Default, config_bench, scene),
+        Handler::BvhQuant8 => start::<handlers::QuantBvhIntrs>
+            (config, handlers::QuantBvhConfig::Runtime {
+                eps: handlers::QuantBvhIntrs::default().eps,
+                precision: bvh::QuantPrecision::Bits8,
+            }, config_bench, scene),
+        Handler::BvhQuant16 => start::<handlers::QuantBvhIntrs>
+            (config, handlers::QuantBvhConfig::Default, config_bench, scene),
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    use clap::{Parser as _, ValueEnum as _};
+
+    let args = Args::parse();
+
+    if let Some(name) = args.record.clone() {
+        return record(args, name);
+    }
+
+    let Args {
+        scenes,
+        handlers,
+        resolutions,
+        bounces,
+        workgroup_size,
+        warmup,
+        passes,
+        fps,
+        camera_path,
+        instrument,
+        out, ..
+    } = args;
+
+    let out = path::PathBuf::from(out);
+
+    fs::create_dir_all(&out)?;
+
+    let exe = env::current_exe()?;
+
+    // Rows are written as each run completes, so a failed run keeps the others
+    let results = out.join("results.csv");
+
+    let mut csv = io::BufWriter::new({
+        fs::File::create(&results)
+            .with_context(|| format!("Failed to create {}", results.display()))?
+    });
+
+    writeln!(csv, "{}", BenchRow::HEADER)?;
+
+    let mut rows = Vec::new();
+
+    // Runs that failed are reported, then skipped
+    let mut failures = Vec::new();
+
+    let mut run = 0;
+    for scene in scenes.iter() {
+        for handler in handlers.iter() {
+            for size in resolutions.iter() {
+                for bounces in bounces.iter() {
+                    let handler = handler
+                        .to_possible_value()
+                        .unwrap()
+                        .get_name();
+
+                    let resolution = format!("{}x{}", size.width, size.height);
+
+                    let label = format!("[{scene}] {handler} {resolution} ({bounces} bounces)");
+
+                    println!("Benchmarking {label}");
+
+                    // Each run keeps its samples in a separate report
+                    let record = format!("run-{run}");
+
+                    run += 1;
+
+                    let mut command = process::Command::new(&exe);
+                        command
+                            .args(["--scene", scene])
+                            .args(["--handler", handler])
+                            .args(["--resolution", &resolution])
+                            .args(["--bounces", &bounces.to_string()])
+                            .args(["--workgroup-size", &workgroup_size.to_string()])
+                            .args(["--warmup", &warmup.to_string()])
+                            .args(["--passes", &passes.to_string()])
+                            .arg("--out")
+                            .arg(&out)
+                            .args(["--record", &record]);
+
+                    if let Some(fps) = fps {
+                        command.args(["--fps", &fps.to_string()]);
+                    }
+
+                    if let Some(camera_path) = camera_path.as_ref() {
+                        command.args(["--camera-path", camera_path]);
+                    }
+
+                    if instrument {
+                        command.arg("--instrument");
+                    }
+
+                    let status = command
+                        .status()
+                        .with_context(|| format!("Failed to start benchmark {label}"))?;
+
+                    let report = if status.success() {
+                        let path = out.join(format!("{record}.json"));
+
+                        fs::File::open(&path)
+                            .map_err(anyhow::Error::from)
+                            .and_then(|file| Ok(serde_json::from_reader(io::BufReader::new(file))?))
+                            .with_context(|| format!("Failed to read {}", path.display()))
+                    } else {
+                        Err(anyhow::anyhow!("Exited with {status}"))
+                    };
+
+                    match report {
+                        Ok(timing::BenchReport { meta, summary, counters, .. }) => {
+                            let row = BenchRow { meta, summary, counters };
+
+                            writeln!(csv, "{}", row.csv())?;
+                            csv.flush()?;
+
+                            rows.push(row);
+                        },
+                        Err(e) => {
+                            eprintln!("Benchmark {label} failed: {e:#}");
+
+                            failures.push(label);
+                        },
+                    }
+                }
+            }
+        }
+    }
+
+    fs::write(out.join("results.json"), serde_json::to_string_pretty(&rows)?)?;
+
+    if !failures.is_empty() {
+        anyhow::bail!("{} of {run} benchmarks failed:\n{}", failures.len(), failures.join("\n"));
+    }
+
+    Ok(())
+}
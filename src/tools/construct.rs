@@ -35,19 +35,45 @@ fn main() -> anyhow::Result<()> {
         .arg(
             clap::Arg::new("camera-fixed")
                 .long("camera-fixed")
-                .conflicts_with("camera-orbit")
+                .conflicts_with_all(&["camera-orbit", "camera-path"])
                 .action(clap::ArgAction::SetTrue))
         .arg(
             clap::Arg::new("camera-orbit")
                 .long("camera-orbit")
-                .conflicts_with("camera-fixed")
+                .conflicts_with_all(&["camera-fixed", "camera-path"])
                 .action(clap::ArgAction::SetTrue))
+        .arg(
+            clap::Arg::new("camera-path")
+                .long("camera-path")
+                .number_of_values(1)
+                .conflicts_with_all(&["camera-fixed", "camera-orbit"]))
         .arg(
             clap::Arg::new("material")
                 .long("material")
                 .number_of_values(7)
                 .value_parser(clap::value_parser!(f32))
                 .action(clap::ArgAction::Append))
+        .arg(
+            clap::Arg::new("weld")
+                .long("weld")
+                .number_of_values(1)
+                .value_parser(clap::value_parser!(f32)))
+        .arg(
+            clap::Arg::new("crease-angle")
+                .long("crease-angle")
+                .number_of_values(1)
+                .value_parser(clap::value_parser!(f32)))
+        .arg(
+            clap::Arg::new("normals")
+                .long("normals")
+                .number_of_values(1)
+                .value_parser(["smooth", "flat"])
+                .default_value("smooth"))
+        .arg(
+            clap::Arg::new("decimate")
+                .long("decimate")
+                .number_of_values(1)
+                .value_parser(clap::value_parser!(usize)))
         .get_matches();
 
     let mut lights = parsed
@@ -158,10 +184,29 @@ fn main() -> anyhow::Result<()> {
         scene::CameraController::Fixed
     } else if *parsed.get_one::<bool>("camera-orbit").unwrap() {
         scene::CameraController::Orbit { left: false, right: false, }
+    } else if let Some(path) = parsed.get_one::<String>("camera-path") {
+        let path: scene::CameraPath = serde_json::from_reader({
+            std::io::BufReader::new(fs::File::open(path)?)
+        })?;
+
+        path.validate()?;
+
+        scene::CameraController::path(path)
     } else {
         anyhow::bail!("Camera controller must be specified");
     };
 
+    // These options are applied to each model individually
+    let mesh_config = geom::mesh::MeshConfig {
+        weld: parsed.get_one::<f32>("weld").copied(),
+        crease: parsed.get_one::<f32>("crease-angle").copied(),
+        shading: match parsed.get_one::<String>("normals").unwrap().as_str() {
+            "flat" => geom::mesh::MeshShading::Flat,
+            _ => geom::mesh::MeshShading::Smooth,
+        },
+        target: parsed.get_one::<usize>("decimate").copied(),
+    };
+
     let mut scene = scene::Scene::Active {
         camera,
         camera_controller,
@@ -169,6 +214,7 @@ fn main() -> anyhow::Result<()> {
         vertices: Vec::new(),
         lights,
         materials,
+        animation: scene::Animation::default(),
     };
 
     for (path, idx) in models {
@@ -179,7 +225,7 @@ fn main() -> anyhow::Result<()> {
             None => 0,
         };
 
-        scene.add_mesh(obj, idx)?;
+        scene.add_mesh(obj, idx, mesh_config)?;
     }
 
     let out = parsed
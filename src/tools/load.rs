@@ -1,8 +1,10 @@
-use std::{fs, io};
+use std::{fs, io, path};
+
+use anyhow::Context as _;
 
 use winit::dpi;
 
-use rt::{handlers, timing, scene};
+use rt::{bvh, handlers, timing, scene};
 
 #[derive(clap::Parser)]
 #[derive(Debug)]
@@ -21,7 +23,7 @@ use rt::{handlers, timing, scene};
 ))]
 #[clap(group(
     clap::ArgGroup::new("handler")
-        .args(&["handler-bvh", "handler-bvh-rf", "handler-naive"])
+        .args(&["handler-bvh", "handler-bvh-rf", "handler-bvh-threaded", "handler-bvh-wide", "handler-bvh-quant", "handler-naive"])
         .multiple(false)
 ))]
 struct Args {
@@ -38,12 +40,61 @@ struct Args {
     #[clap(long = "handler-bvh", value_parser, min_values = 0, max_values = 1)]
     handler_bvh: Option<Vec<String>>,
 
-    #[clap(long = "handler-bvh-rf", value_parser, min_values = 0, max_values = 1)]
-    handler_bvh_rf: Option<Vec<f32>>,
+    // Accepts the same value as --handler-bvh
+    #[clap(long = "handler-bvh-threaded", value_parser, min_values = 0, max_values = 1)]
+    handler_bvh_threaded: Option<Vec<String>>,
+
+    // The construction strategy used by --handler-bvh & --handler-bvh-threaded,
+    // ignored when loading a precomputed BVH
+    #[clap(long = "bvh-builder", value_parser = ["object", "sbvh", "lbvh"])]
+    bvh_builder: Option<String>,
+
+    // Optionally takes an epsilon value, followed by the leaf size
+    #[clap(long = "handler-bvh-rf", value_parser, min_values = 0, max_values = 2)]
+    handler_bvh_rf: Option<Vec<String>>,
+
+    // Optionally takes an epsilon value, followed by the width (4 or 8)
+    #[clap(long = "handler-bvh-wide", value_parser, min_values = 0, max_values = 2)]
+    handler_bvh_wide: Option<Vec<String>>,
+
+    // Optionally takes an epsilon value, followed by the precision (8 or 16 bits)
+    #[clap(long = "handler-bvh-quant", value_parser, min_values = 0, max_values = 2)]
+    handler_bvh_quant: Option<Vec<String>>,
 
     #[clap(long = "benchmark", action)]
     benchmark: bool,
 
+    // Replaces the scene's camera controller with a scripted path (JSON)
+    #[clap(long = "camera-path", value_parser)]
+    camera_path: Option<String>,
+
+    // Exit once this many compute passes have been recorded (100 by default)
+    #[clap(long = "benchmark-passes", value_parser, requires = "benchmark")]
+    benchmark_passes: Option<usize>,
+
+    // The number of compute passes to discard before recording
+    #[clap(long = "benchmark-warmup", value_parser, requires = "benchmark")]
+    benchmark_warmup: Option<usize>,
+
+    // Results are rewritten every N recorded passes
+    #[clap(long = "benchmark-interval", value_parser, requires = "benchmark")]
+    benchmark_interval: Option<usize>,
+
+    // The directory benchmark results are written to
+    #[clap(long = "benchmark-out", value_parser, requires = "benchmark")]
+    benchmark_out: Option<String>,
+
+    #[clap(
+        long = "benchmark-format", 
+        value_parser = ["png", "svg", "csv", "json"], 
+        requires = "benchmark"
+    )]
+    benchmark_formats: Vec<String>,
+
+    // Count rays, node visits and intersection tests on the GPU
+    #[clap(long = "benchmark-instrument", action, requires = "benchmark")]
+    benchmark_instrument: bool,
+
     #[clap(long, short, value_parser)]
     width: Option<u32>,
 
@@ -64,32 +115,75 @@ struct Args {
 
     #[clap(long = "ambience", value_parser)]
     compute_ambience: Option<f32>,
+
+    // Draw the bounds of each BVH node over the image (toggled with B)
+    #[clap(long = "overlay", action)]
+    overlay: bool,
+
+    #[clap(long = "overlay-depth-min", value_parser)]
+    overlay_depth_min: Option<u32>,
+
+    #[clap(long = "overlay-depth-max", value_parser)]
+    overlay_depth_max: Option<u32>,
+
+    // Only draw leaf nodes (toggled with L)
+    #[clap(long = "overlay-leaves", action)]
+    overlay_leaves: bool,
+
+    // Save camera input and config changes to this path on exit
+    #[clap(long = "record-session", value_parser, conflicts_with = "replay")]
+    record_session: Option<String>,
+
+    // Replay a session saved with --record-session
+    #[clap(long, value_parser)]
+    replay: Option<String>,
 }
 
+// Parses the epsilon (f32) and the optional integer that follows it,
+// as passed to --handler-bvh-rf, --handler-bvh-wide & --handler-bvh-quant
+fn parse_handler_args(flag: &str, args: &[String]) -> anyhow::Result<(f32, Option<usize>)> {
+    let eps = args[0]
+        .parse::<f32>()
+        .with_context(|| format!("Epsilon passed to {flag} must be an f32"))?;
+
+    let value = args
+        .get(1)
+        .map(|arg| arg.parse::<usize>())
+        .transpose()
+        .with_context(|| format!("Second value passed to {flag} must be a non-negative integer"))?;
+
+    Ok((eps, value))
+}
+
+#[allow(clippy::too_many_arguments)]
 fn start<H: handlers::IntrsHandler>(
-    benchmark: bool,
+    benchmark: Option<timing::BenchConfig>,
     resolution: rt::Resolution, 
     fps: Option<u32>,
     config_compute: rt::ComputeConfig, 
+    config_overlay: rt::OverlayConfig,
     config_handler: H::Config,
     scene: scene::Scene,
+    session: Option<rt::session::SessionMode>,
 ) -> anyhow::Result<()> {
     let config_default = rt::Config::default();
     let config: rt::Config = rt::Config {
         resolution,
         compute: config_compute,
+        overlay: config_overlay,
         fps: fps.unwrap_or(config_default.fps),
+        ..config_default
     };
     
-    if benchmark {
+    if let Some(config_bench) = benchmark {
         pollster::block_on({
             rt::run_native::<H, timing::BenchScheduler>
-                (config, config_handler, scene)
+                (config, config_handler, config_bench, scene, session)
         })
     } else {
         pollster::block_on({
             rt::run_native::<H, timing::DefaultScheduler>
-                (config, config_handler, scene)
+                (config, config_handler, (), scene, session)
         })
     }
 }
@@ -104,14 +198,31 @@ fn main() -> anyhow::Result<()> {
         handler_naive,
         handler_bvh,
         handler_bvh_rf,
+        handler_bvh_threaded,
+        bvh_builder,
+        handler_bvh_wide,
+        handler_bvh_quant,
+        camera_path,
         benchmark,
+        benchmark_passes,
+        benchmark_warmup,
+        benchmark_interval,
+        benchmark_out,
+        benchmark_formats,
+        benchmark_instrument,
         width,
         height,
         workgroup_size,
         fps,
         compute_bounces,
         compute_camera_light_source,
-        compute_ambience, ..
+        compute_ambience,
+        overlay,
+        overlay_depth_min,
+        overlay_depth_max,
+        overlay_leaves,
+        record_session,
+        replay, ..
     } = args;
 
     let resolution =  match (width, height, workgroup_size) {
@@ -138,17 +249,83 @@ fn main() -> anyhow::Result<()> {
         ..Default::default()
     };
 
+    let config_overlay_default = rt::OverlayConfig::default();
+    let config_overlay = rt::OverlayConfig {
+        enabled: overlay,
+        depth_min: overlay_depth_min
+            .unwrap_or(config_overlay_default.depth_min),
+        depth_max: overlay_depth_max
+            .unwrap_or(config_overlay_default.depth_max),
+        leaves: overlay_leaves,
+    };
+
+    let benchmark = if benchmark {
+        let config_bench_default = timing::BenchConfig::default();
+
+        let formats = benchmark_formats
+            .iter()
+            .map(|format| format.parse::<timing::BenchFormat>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(anyhow::Error::msg)?;
+
+        Some(timing::BenchConfig {
+            passes: benchmark_passes
+                .or(config_bench_default.passes),
+            warmup: benchmark_warmup
+                .unwrap_or(config_bench_default.warmup),
+            interval: benchmark_interval
+                .or(config_bench_default.interval),
+            out: benchmark_out
+                .map(path::PathBuf::from)
+                .unwrap_or(config_bench_default.out),
+            formats: if formats.is_empty() {
+                config_bench_default.formats
+            } else {
+                formats
+            },
+            scene: path::Path::new(&path)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            instrument: benchmark_instrument,
+            ..config_bench_default
+        })
+    } else {
+        None
+    };
+
     let scene_reader = io::BufReader::new({
         fs::File::open(path)?
     });
 
-    let scene: scene::Scene = //
+    let mut scene: scene::Scene = //
         serde_json::from_reader(scene_reader)?;
 
+    if let (
+        Some(path), 
+        scene::Scene::Active { camera_controller, .. }
+    ) = (camera_path, &mut scene) {
+        let path: scene::CameraPath = serde_json::from_reader({
+            io::BufReader::new(fs::File::open(path)?)
+        })?;
+
+        path.validate()?;
+
+        *camera_controller = scene::CameraController::path(path);
+    }
+
+    let session = match (record_session, replay) {
+        (Some(out), _) => //
+            Some(rt::session::SessionMode::Record(path::PathBuf::from(out))),
+        (None, Some(replay)) => //
+            Some(rt::session::SessionMode::Replay(rt::session::SessionLog::read(replay)?)),
+        (None, None) => None,
+    };
+
     if handler_naive {
         start::<handlers::BasicIntrs>
-            (benchmark, resolution, fps, config_compute, (), scene)
-    } else if let Some(args) = handler_bvh {
+            (benchmark, resolution, fps, config_compute, config_overlay, (), scene, session)
+    } else if let Some(args) = handler_bvh.as_ref().or(handler_bvh_threaded.as_ref()) {
         use io::Read as _;
 
         let config_handler: handlers::BvhConfig = match args.len() {
@@ -158,14 +335,14 @@ fn main() -> anyhow::Result<()> {
                     Ok(eps) => handlers::BvhConfig::Runtime { eps, },
                     Err(_) => match fs::File::open(&args[0]) {
                         Ok(file) => {
-                            let bytes = file
+                            let bytes = io::BufReader::new(file)
                                 .bytes()
                                 .collect::<Result<Vec<_>, io::Error>>()?;
 
                             handlers::BvhConfig::Bytes(bytes)
                         },
                         Err(_) => anyhow::bail!("\
-                            Flag --handler-bvh requires either:
+                            Flags --handler-bvh & --handler-bvh-threaded require either:
                               - The path to a precomputed BVH file
                               - An epsilon value (f32)\
                         "),
@@ -175,19 +352,77 @@ fn main() -> anyhow::Result<()> {
             _ => unreachable!(),
         };
 
-        start::<handlers::BvhIntrs>
-            (benchmark, resolution, fps, config_compute, config_handler, scene)
+        // Restricted to valid builders by the parser
+        let config_handler = match bvh_builder.as_deref().map(str::parse::<bvh::BvhBuilder>) {
+            Some(Ok(builder)) => match config_handler {
+                handlers::BvhConfig::Runtime { eps } => //
+                    handlers::BvhConfig::Builder { eps, builder },
+                handlers::BvhConfig::Default => handlers::BvhConfig::Builder {
+                    eps: handlers::BvhIntrs::default().eps,
+                    builder,
+                },
+                config_handler => config_handler,
+            },
+            Some(Err(e)) => anyhow::bail!(e),
+            None => config_handler,
+        };
+
+        if handler_bvh.is_some() {
+            start::<handlers::BvhIntrs>
+                (benchmark, resolution, fps, config_compute, config_overlay, config_handler, scene, session)
+        } else {
+            start::<handlers::ThreadedBvhIntrs>
+                (benchmark, resolution, fps, config_compute, config_overlay, config_handler, scene, session)
+        }
     } else if let Some(args) = handler_bvh_rf {
         let config_handler = match args.len() {
             0 => handlers::RfBvhConfig::default(),
-            1 => handlers::RfBvhConfig::Eps(args[0]),
-            _ => unreachable!(),
+            _ => match parse_handler_args("--handler-bvh-rf", &args)? {
+                (eps, None) => handlers::RfBvhConfig::Eps(eps),
+                (_, Some(0)) => {
+                    anyhow::bail!("Leaf size passed to --handler-bvh-rf must be a positive integer");
+                },
+                (eps, Some(leaf_size)) => handlers::RfBvhConfig::Runtime { eps, leaf_size },
+            },
         };
 
         start::<handlers::RfBvhIntrs>
-            (benchmark, resolution, fps, config_compute, config_handler, scene)
+            (benchmark, resolution, fps, config_compute, config_overlay, config_handler, scene, session)
+    } else if let Some(args) = handler_bvh_wide {
+        let config_handler = match args.len() {
+            0 => handlers::WideBvhConfig::Default,
+            _ => match parse_handler_args("--handler-bvh-wide", &args)? {
+                (eps, None) => handlers::WideBvhConfig::Runtime { eps, width: 4 },
+                (eps, Some(width @ (4 | 8))) => handlers::WideBvhConfig::Runtime { eps, width },
+                (_, Some(_)) => {
+                    anyhow::bail!("Width passed to --handler-bvh-wide must be either 4 or 8");
+                },
+            },
+        };
+
+        start::<handlers::WideBvhIntrs>
+            (benchmark, resolution, fps, config_compute, config_overlay, config_handler, scene, session)
+    } else if let Some(args) = handler_bvh_quant {
+        let config_handler = match args.len() {
+            0 => handlers::QuantBvhConfig::Default,
+            _ => {
+                let (eps, precision) = parse_handler_args("--handler-bvh-quant", &args)?;
+
+                let precision = match precision {
+                    None => bvh::QuantPrecision::default(),
+                    Some(8) => bvh::QuantPrecision::Bits8,
+                    Some(16) => bvh::QuantPrecision::Bits16,
+                    Some(_) => anyhow::bail!("Precision passed to --handler-bvh-quant must be either 8 or 16"),
+                };
+
+                handlers::QuantBvhConfig::Runtime { eps, precision }
+            },
+        };
+
+        start::<handlers::QuantBvhIntrs>
+            (benchmark, resolution, fps, config_compute, config_overlay, config_handler, scene, session)
     } else {
         start::<handlers::BlankIntrs>
-            (benchmark, resolution, fps, config_compute, (), scene)
+            (benchmark, resolution, fps, config_compute, config_overlay, (), scene, session)
     }
 }
\ No newline at end of file
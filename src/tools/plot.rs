@@ -0,0 +1,198 @@
+use std::{collections, fs, io, path};
+
+use rt::timing;
+
+#[derive(clap::Parser)]
+#[derive(Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    // Benchmark reports (JSON), or directories containing them
+    #[clap(value_parser, required = true)]
+    inputs: Vec<String>,
+
+    // The directory figures are written to
+    #[clap(long, value_parser, default_value_t = String::from("figures"))]
+    out: String,
+
+    #[clap(
+        long = "format",
+        value_parser = ["png", "svg"],
+        default_values_t = vec![String::from("png"), String::from("svg")]
+    )]
+    formats: Vec<String>,
+
+    // The handler speedups are measured against
+    #[clap(long, value_parser, default_value_t = String::from("Naive"))]
+    baseline: String,
+
+    // The handler memory savings and traversal overhead are measured against
+    #[clap(long = "memory-baseline", value_parser, default_value_t = String::from("BVH"))]
+    memory_baseline: String,
+}
+
+// Reports are only compared when they share a configuration
+type Key = (String, u32, u32, u32, u32);
+
+fn key(report: &timing::BenchReport) -> Key {
+    let timing::BenchMeta {
+        scene,
+        width,
+        height,
+        bounces,
+        workgroup_size, ..
+    } = &report.meta;
+
+    (scene.clone(), *width, *height, *bounces, *workgroup_size)
+}
+
+fn read(path: &path::Path) -> anyhow::Result<timing::BenchReport> {
+    let reader = io::BufReader::new(fs::File::open(path)?);
+
+    Ok(serde_json::from_reader(reader)?)
+}
+
+fn collect(inputs: &[String]) -> anyhow::Result<Vec<timing::BenchReport>> {
+    let mut reports = Vec::new();
+
+    for input in inputs.iter().map(path::Path::new) {
+        if input.is_dir() {
+            let mut entries = fs::read_dir(input)?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            entries.sort();
+
+            // Other JSON files (i.e. bench results tables) are skipped
+            reports.extend(entries
+                .iter()
+                .filter(|entry| entry.extension().is_some_and(|ext| ext == "json"))
+                .filter_map(|entry| read(entry).ok()));
+        } else {
+            reports.push(read(input)?);
+        }
+    }
+
+    Ok(reports)
+}
+
+fn save(
+    bytes: Vec<u8>,
+    out: &path::Path,
+    name: &str,
+    formats: &[timing::BenchFormat],
+) -> anyhow::Result<()> {
+    for format in formats {
+        let file = out.join(format!("{name}.{}", format.extension()));
+
+        match format {
+            timing::BenchFormat::Png => {
+                timing::plot::rasterize(&bytes)?.save_png(file)?;
+            },
+            timing::BenchFormat::Svg => {
+                fs::write(file, &bytes)?;
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    Ok(())
+}
+
+// Tabulates memory saved against traversal overhead
+// Node visits and intersection tests are only known for instrumented runs
+fn memory(group: &[&timing::BenchReport], baseline: &timing::BenchReport) -> String {
+    let mut lines = vec![String::from("handler,size,saved,mean,overhead,nodes_avg,tests_avg")];
+
+    for report in group {
+        let (saved, overhead) = timing::plot::memory_tradeoff(report, baseline);
+
+        let (nodes, tests) = match report.counters {
+            Some(timing::BenchCounters { nodes_avg, tests_avg, .. }) => //
+                (nodes_avg.to_string(), tests_avg.to_string()),
+            None => (String::new(), String::new()),
+        };
+
+        let timing::BenchReport { meta, summary, .. } = report;
+
+        lines.push(format!(
+            "{},{},{saved:.2},{},{overhead:.2},{nodes},{tests}",
+            meta.handler, meta.size, summary.mean,
+        ));
+    }
+
+    lines.join("\n")
+}
+
+fn main() -> anyhow::Result<()> {
+    use clap::Parser as _;
+
+    let Args {
+        inputs,
+        out,
+        formats,
+        baseline,
+        memory_baseline,
+    } = Args::parse();
+
+    let formats = formats
+        .iter()
+        .map(|format| format.parse::<timing::BenchFormat>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(anyhow::Error::msg)?;
+
+    let out = path::PathBuf::from(out);
+
+    fs::create_dir_all(&out)?;
+
+    let reports = collect(&inputs)?;
+
+    if reports.is_empty() {
+        anyhow::bail!("No benchmark reports were found");
+    }
+
+    let mut groups: collections::BTreeMap<Key, Vec<&timing::BenchReport>> = //
+        collections::BTreeMap::new();
+
+    for report in reports.iter() {
+        groups.entry(key(report)).or_default().push(report);
+    }
+
+    for ((scene, width, height, bounces, wg), group) in groups {
+        let name = format!("{scene}-{width}x{height}-b{bounces}-wg{wg}");
+
+        println!("Plotting [{scene}] {width}x{height} ({bounces} bounces, {} reports)", group.len());
+
+        save(timing::plot::lines(&group)?, &out, &format!("{name}-lines"), &formats)?;
+        save(timing::plot::boxes(&group)?, &out, &format!("{name}-boxes"), &formats)?;
+
+        let report = group
+            .iter()
+            .find(|report| report.meta.handler.eq_ignore_ascii_case(&baseline));
+
+        match report {
+            Some(report) => {
+                let bytes = timing::plot::speedup(&group, report)?;
+
+                save(bytes, &out, &format!("{name}-speedup"), &formats)?;
+            },
+            None => eprintln!("Skipping speedup chart for [{name}], no {baseline} report"),
+        }
+
+        let report = group
+            .iter()
+            .find(|report| report.meta.handler.eq_ignore_ascii_case(&memory_baseline));
+
+        match report {
+            Some(report) => {
+                let bytes = timing::plot::memory(&group, report)?;
+
+                save(bytes, &out, &format!("{name}-memory"), &formats)?;
+
+                fs::write(out.join(format!("{name}-memory.csv")), memory(&group, report))?;
+            },
+            None => eprintln!("Skipping memory report for [{name}], no {memory_baseline} report"),
+        }
+    }
+
+    Ok(())
+}
@@ -27,6 +27,12 @@ fn main() -> anyhow::Result<()> {
                 .number_of_values(1)
                 .value_parser(clap::value_parser!(usize))
                 .required(true))
+        .arg(
+            clap::Arg::new("builder")
+                .long("builder")
+                .number_of_values(1)
+                .value_parser(["object", "sbvh", "lbvh"])
+                .default_value("object"))
         .get_matches();
 
     let out = parsed
@@ -54,9 +60,14 @@ fn main() -> anyhow::Result<()> {
         .get_one::<>("item-count")
         .unwrap();
 
-    let bvh = rt::bvh::BvhData::new({
-        &bvh::Aabb::from_scene(eps, &scene, *item_count)
-    });
+    // Restricted to valid builders by the parser above
+    let builder = parsed
+        .get_one::<String>("builder")
+        .unwrap()
+        .parse::<bvh::BvhBuilder>()
+        .map_err(anyhow::Error::msg)?;
+
+    let bvh = rt::bvh::BvhData::new(&builder.build(eps, &scene, *item_count));
     
     fs::File::create(out)?
         .write_all(serde_json::to_string(&bvh)?.as_bytes())?;
@@ -0,0 +1,608 @@
+// Property tests for BVH construction on random triangle soups
+#![cfg(not(target_arch = "wasm32"))]
+
+use proptest::prelude::*;
+
+use rt::{bvh, geom, scene};
+
+type Tri = [[f32; 3]; 3];
+
+// Matches the default `eps` of both BVH handlers
+const EPS: f32 = 0.02;
+
+fn soup(tris: &[Tri]) -> scene::Scene {
+    let vertices = tris
+        .iter()
+        .flatten()
+        .map(|&pos| geom::PrimVertex::new(pos, [0., 0., 1.]))
+        .collect();
+
+    let prims = (0..tris.len() as u32)
+        .map(|idx| geom::Prim { indices: [idx * 3, idx * 3 + 1, idx * 3 + 2], material: 0 })
+        .collect();
+
+    scene::Scene::Active {
+        camera: scene::CameraUniform::new([0., 0., -10.], [0., 0., 0.]),
+        camera_controller: scene::CameraController::Fixed,
+        prims,
+        vertices,
+        lights: Vec::new(),
+        materials: Vec::new(),
+        animation: scene::Animation::default(),
+    }
+}
+
+fn coord() -> impl Strategy<Value = f32> { -10f32..10f32 }
+
+fn point() -> impl Strategy<Value = [f32; 3]> { [coord(), coord(), coord()] }
+
+fn tri() -> impl Strategy<Value = Tri> { [point(), point(), point()] }
+
+// Random soups, soups lying in a single plane
+// and soups containing many copies of the same triangle
+fn tris() -> impl Strategy<Value = Vec<Tri>> {
+    let random = prop::collection::vec(tri(), 1..64);
+
+    let coplanar = (coord(), prop::collection::vec(tri(), 1..64))
+        .prop_map(|(z, tris)| {
+            tris.into_iter()
+                .map(|tri| tri.map(|[x, y, _]| [x, y, z]))
+                .collect()
+        });
+
+    let coincident = (tri(), 1..32usize, prop::collection::vec(tri(), 0..8))
+        .prop_map(|(tri, copies, mut tris)| {
+            tris.extend(std::iter::repeat_n(tri, copies)); tris
+        });
+
+    prop_oneof![random, coplanar, coincident]
+}
+
+fn ray() -> impl Strategy<Value = ([f32; 3], [f32; 3])> {
+    let dir = [-1f32..1f32, -1f32..1f32, -1f32..1f32]
+        .prop_filter("direction must be non-zero", |d| {
+            d.iter().any(|c| c.abs() > 0.001)
+        });
+
+    ([-20f32..20f32, -20f32..20f32, -20f32..20f32], dir)
+}
+
+fn contains(outer: &bvh::Bounds, inner: &bvh::Bounds) -> bool {
+    (0..3).all(|i| outer.min[i] <= inner.min[i] && inner.max[i] <= outer.max[i])
+}
+
+fn contains_point(bounds: &bvh::Bounds, point: [f32; 3]) -> bool {
+    (0..3).all(|i| bounds.min[i] <= point[i] && point[i] <= bounds.max[i])
+}
+
+fn is_leaf(uniform: &bvh::AabbUniform) -> bool {
+    uniform.fst == 0 && uniform.snd == 0
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] { [0, 1, 2].map(|i| a[i] - b[i]) }
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 { a[0] * b[0] + a[1] * b[1] + a[2] * b[2] }
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+// The same Moller-Trumbore test used by the shaders
+fn intrs_tri(origin: [f32; 3], dir: [f32; 3], [a, b, c]: Tri) -> Option<f32> {
+    let e1 = sub(b, a);
+    let e2 = sub(c, a);
+
+    let p = cross(dir, e2);
+    let t = sub(origin, a);
+    let q = cross(t, e1);
+
+    let det = dot(e1, p);
+
+    let (u, v) = (dot(t, p), dot(dir, q));
+    if det > 1e-7 {
+        if u < 0. || u > det || v < 0. || u + v > det { return None; }
+    } else if det < -1e-7 {
+        if u > 0. || u < det || v > 0. || u + v < det { return None; }
+    } else {
+        return None;
+    }
+
+    let w = dot(e2, q) / det;
+
+    (w >= 0.).then_some(w)
+}
+
+fn collides(bounds: &bvh::Bounds, origin: [f32; 3], dir: [f32; 3]) -> bool {
+    let mut t_min = 0f32;
+    let mut t_max = f32::MAX;
+
+    for i in 0..3 {
+        let d = if dir[i].abs() < 1e-12 { 1e-12 } else { dir[i] };
+
+        let t0 = (bounds.min[i] - 1e-4 - origin[i]) / d;
+        let t1 = (bounds.max[i] + 1e-4 - origin[i]) / d;
+
+        t_min = t_min.max(t0.min(t1));
+        t_max = t_max.min(t0.max(t1));
+    }
+
+    t_min <= t_max
+}
+
+fn nearest<I: Iterator<Item = Tri>>(tris: I, origin: [f32; 3], dir: [f32; 3]) -> Option<f32> {
+    tris.filter_map(|tri| intrs_tri(origin, dir, tri)).reduce(f32::min)
+}
+
+fn traverse(data: &bvh::BvhData, tris: &[Tri], origin: [f32; 3], dir: [f32; 3]) -> Option<f32> {
+    let mut stack = vec![0usize];
+    let mut hit: Option<f32> = None;
+
+    while let Some(idx) = stack.pop() {
+        let uniform = &data.uniforms[idx];
+
+        if !collides(&uniform.bounds, origin, dir) { continue; }
+
+        if is_leaf(uniform) {
+            let range = uniform.item_idx as usize..(uniform.item_idx + uniform.item_count) as usize;
+
+            let leaf = data.indices[range].iter().map(|&i| tris[i as usize]);
+
+            if let Some(t) = nearest(leaf, origin, dir) {
+                hit = Some(hit.map_or(t, |hit| hit.min(t)));
+            }
+        } else {
+            stack.push(uniform.fst as usize);
+            stack.push(uniform.snd as usize);
+        }
+    }
+
+    hit
+}
+
+fn traverse_wide(data: &bvh::WideBvhData, tris: &[Tri], origin: [f32; 3], dir: [f32; 3]) -> Option<f32> {
+    let mut stack = vec![0usize];
+    let mut hit: Option<f32> = None;
+
+    while let Some(node) = stack.pop() {
+        let children = &data.children[node * data.width..(node + 1) * data.width];
+
+        for child in children.iter().filter(|child| !child.is_empty()) {
+            if !collides(&bounds(child), origin, dir) { continue; }
+
+            if child.is_leaf() {
+                let range = child.idx as usize..(child.idx + child.count) as usize;
+
+                let leaf = data.indices[range].iter().map(|&i| tris[i as usize]);
+
+                if let Some(t) = nearest(leaf, origin, dir) {
+                    hit = Some(hit.map_or(t, |hit| hit.min(t)));
+                }
+            } else {
+                stack.push(child.idx as usize);
+            }
+        }
+    }
+
+    hit
+}
+
+fn bounds(child: &bvh::WideChild) -> bvh::Bounds {
+    let mut bounds: bvh::Bounds = bytemuck::Zeroable::zeroed();
+
+    bounds.min = child.min;
+    bounds.max = child.max;
+    bounds
+}
+
+// Walks the tree alongside its flattened form
+fn check_flattened(aabb: &bvh::Aabb, data: &bvh::BvhData, idx: usize) -> Result<(), TestCaseError> {
+    let uniform = &data.uniforms[idx];
+
+    prop_assert_eq!(aabb.bounds.min, uniform.bounds.min);
+    prop_assert_eq!(aabb.bounds.max, uniform.bounds.max);
+
+    let range = uniform.item_idx as usize..(uniform.item_idx + uniform.item_count) as usize;
+
+    let items = data.indices[range].iter().map(|&i| i as usize).collect::<Vec<_>>();
+    prop_assert_eq!(&aabb.items, &items);
+
+    match (aabb.fst.get(), aabb.snd.get()) {
+        (Some(fst), Some(snd)) => {
+            prop_assert!(!is_leaf(uniform));
+
+            check_flattened(fst, data, uniform.fst as usize)?;
+            check_flattened(snd, data, uniform.snd as usize)?;
+        },
+        (None, None) => prop_assert!(is_leaf(uniform)),
+        _ => prop_assert!(false, "node has a single child"),
+    }
+
+    Ok(())
+}
+
+// Builds on a pool with the given number of threads
+fn build_with_threads(threads: usize, build: impl FnOnce() -> bvh::Aabb + Send) -> bvh::BvhData {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .unwrap();
+
+    pool.install(move || bvh::BvhData::new(&build()))
+}
+
+proptest! {
+    // Soups are large enough for subtrees to be built on other threads
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn build_is_deterministic(
+        tris in prop::collection::vec(tri(), 512..2048),
+        target in 1..8usize,
+    ) {
+        let scene = soup(&tris);
+
+        // Spatial splits share out their reference budget between subtrees
+        let builders: [fn(f32, &scene::Scene, usize) -> bvh::Aabb; 2] = [
+            bvh::Aabb::from_scene,
+            bvh::Aabb::from_scene_sbvh,
+        ];
+
+        for build in builders {
+            let serial = build_with_threads(1, || build(EPS, &scene, target));
+
+            for threads in [2, 8] {
+                let parallel = build_with_threads(threads, || build(EPS, &scene, target));
+
+                prop_assert_eq!(&serial.indices, &parallel.indices);
+                prop_assert_eq!(
+                    bytemuck::cast_slice::<_, u8>(&serial.uniforms),
+                    bytemuck::cast_slice::<_, u8>(&parallel.uniforms)
+                );
+            }
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn leaves_partition_prims(tris in tris(), target in 1..8usize) {
+        let aabb = bvh::Aabb::from_scene(EPS, &soup(&tris), target);
+        let data = bvh::BvhData::new(&aabb);
+
+        let mut seen = vec![0; tris.len()];
+        for uniform in data.uniforms.iter() {
+            let end = uniform.item_idx + uniform.item_count;
+
+            prop_assert!(end as usize <= data.indices.len());
+
+            if is_leaf(uniform) {
+                prop_assert!(uniform.item_count > 0);
+
+                for &idx in &data.indices[uniform.item_idx as usize..end as usize] {
+                    seen[idx as usize] += 1;
+                }
+            } else {
+                prop_assert_eq!(uniform.item_count, 0);
+            }
+        }
+
+        prop_assert!(seen.iter().all(|&count| count == 1), "{:?}", seen);
+        prop_assert_eq!(data.indices.len(), tris.len());
+    }
+
+    #[test]
+    fn bounds_are_nested(tris in tris(), target in 1..8usize) {
+        let aabb = bvh::Aabb::from_scene(EPS, &soup(&tris), target);
+        let data = bvh::BvhData::new(&aabb);
+
+        for uniform in data.uniforms.iter() {
+            if is_leaf(uniform) {
+                let range = uniform.item_idx as usize..(uniform.item_idx + uniform.item_count) as usize;
+
+                for &idx in &data.indices[range] {
+                    for point in tris[idx as usize] {
+                        prop_assert!(contains_point(&uniform.bounds, point));
+                    }
+                }
+            } else {
+                for child in [uniform.fst, uniform.snd] {
+                    let child = &data.uniforms[child as usize];
+
+                    prop_assert!(contains(&uniform.bounds, &child.bounds));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn flattened_links_match_tree(tris in tris(), target in 1..8usize) {
+        let aabb = bvh::Aabb::from_scene(EPS, &soup(&tris), target);
+        let data = bvh::BvhData::new(&aabb);
+
+        // Every node but the root is referenced exactly once
+        let mut parents = vec![0; data.uniforms.len()];
+        for uniform in data.uniforms.iter().filter(|uniform| !is_leaf(uniform)) {
+            for child in [uniform.fst, uniform.snd] {
+                prop_assert!(child != 0 && (child as usize) < data.uniforms.len());
+
+                parents[child as usize] += 1;
+            }
+        }
+
+        prop_assert_eq!(parents[0], 0);
+        prop_assert!(parents[1..].iter().all(|&count| count == 1));
+
+        check_flattened(&aabb, &data, 0)?;
+    }
+
+    #[test]
+    fn traversal_matches_brute_force(
+        tris in tris(),
+        target in 1..8usize,
+        rays in prop::collection::vec(ray(), 1..16),
+    ) {
+        let aabb = bvh::Aabb::from_scene(EPS, &soup(&tris), target);
+        let data = bvh::BvhData::new(&aabb);
+
+        for (origin, dir) in rays {
+            let expected = nearest(tris.iter().copied(), origin, dir);
+
+            prop_assert_eq!(traverse(&data, &tris, origin, dir), expected);
+        }
+    }
+
+    #[test]
+    fn wide_leaves_partition_prims(tris in tris(), width in prop::sample::select(vec![4usize, 8])) {
+        let aabb = bvh::Aabb::from_scene(EPS, &soup(&tris), 2);
+        let data = bvh::WideBvhData::new(&aabb, width);
+
+        let mut seen = vec![0; tris.len()];
+        let mut parents = vec![0; data.nodes()];
+        for (idx, child) in data.children.iter().enumerate() {
+            if child.is_leaf() {
+                for &item in &data.indices[child.idx as usize..(child.idx + child.count) as usize] {
+                    seen[item as usize] += 1;
+                }
+            } else if !child.is_empty() {
+                // Children always come after their parent
+                prop_assert!(child.idx as usize > idx / width);
+
+                parents[child.idx as usize] += 1;
+            }
+        }
+
+        prop_assert!(seen.iter().all(|&count| count == 1), "{:?}", seen);
+        prop_assert!(parents[1..].iter().all(|&count| count == 1));
+
+        // Occupied slots are packed at the front of each node
+        for node in data.children.chunks(width) {
+            let occupied = node.iter().take_while(|child| !child.is_empty()).count();
+
+            prop_assert!(occupied > 0);
+            prop_assert!(node[occupied..].iter().all(|child| child.is_empty()));
+        }
+    }
+
+    #[test]
+    fn wide_traversal_matches_brute_force(
+        tris in tris(),
+        width in prop::sample::select(vec![4usize, 8]),
+        rays in prop::collection::vec(ray(), 1..16),
+    ) {
+        let aabb = bvh::Aabb::from_scene(EPS, &soup(&tris), 2);
+        let data = bvh::WideBvhData::new(&aabb, width);
+
+        for (origin, dir) in rays {
+            let expected = nearest(tris.iter().copied(), origin, dir);
+
+            prop_assert_eq!(traverse_wide(&data, &tris, origin, dir), expected);
+        }
+    }
+
+    #[test]
+    fn wide_collapse_lowers_sah_cost(
+        tris in tris(),
+        target in 1..8usize,
+        width in prop::sample::select(vec![2usize, 4, 8]),
+    ) {
+        let aabb = bvh::Aabb::from_scene(EPS, &soup(&tris), target);
+        let data = bvh::BvhData::new(&aabb);
+
+        // A lone leaf still needs a wide node to hold it
+        prop_assume!(data.uniforms.len() > 1);
+
+        let binary = data.cost();
+        let wide = bvh::WideBvhData::new(&aabb, width).cost();
+
+        prop_assert!(wide <= binary * (1. + 1e-5), "wide {} > binary {}", wide, binary);
+    }
+
+    #[test]
+    fn sbvh_references_cover_prims(tris in tris(), target in 1..8usize) {
+        let aabb = bvh::Aabb::from_scene_sbvh(EPS, &soup(&tris), target);
+        let data = bvh::BvhData::new(&aabb);
+
+        let mut seen = vec![0; tris.len()];
+        for uniform in data.uniforms.iter() {
+            let range = uniform.item_idx as usize..(uniform.item_idx + uniform.item_count) as usize;
+
+            if is_leaf(uniform) {
+                let mut items = data.indices[range].to_vec();
+                    items.sort_unstable();
+
+                // Duplicated references always land in different leaves
+                prop_assert!(items.windows(2).all(|pair| pair[0] != pair[1]), "{:?}", items);
+
+                for idx in items {
+                    seen[idx as usize] += 1;
+                }
+            } else {
+                prop_assert_eq!(uniform.item_count, 0);
+
+                for child in [uniform.fst, uniform.snd] {
+                    prop_assert!(contains(&uniform.bounds, &data.uniforms[child as usize].bounds));
+                }
+            }
+        }
+
+        prop_assert!(seen.iter().all(|&count| count > 0), "{:?}", seen);
+        prop_assert_eq!(data.indices.len(), seen.iter().sum::<usize>());
+    }
+
+    #[test]
+    fn sbvh_traversal_matches_brute_force(
+        tris in tris(),
+        target in 1..8usize,
+        rays in prop::collection::vec(ray(), 1..16),
+    ) {
+        let aabb = bvh::Aabb::from_scene_sbvh(EPS, &soup(&tris), target);
+        let data = bvh::BvhData::new(&aabb);
+
+        for (origin, dir) in rays {
+            let expected = nearest(tris.iter().copied(), origin, dir);
+
+            prop_assert_eq!(traverse(&data, &tris, origin, dir), expected);
+        }
+    }
+
+    // Every triangle moves somewhere else entirely, but keeps its leaf
+    #[test]
+    fn refit_traversal_matches_brute_force(
+        (tris, moved) in tris().prop_flat_map(|tris| {
+            let count = tris.len();
+
+            (Just(tris), prop::collection::vec(tri(), count))
+        }),
+        target in 1..8usize,
+        rays in prop::collection::vec(ray(), 1..16),
+    ) {
+        let aabb = bvh::Aabb::from_scene(EPS, &soup(&tris), target);
+
+        let mut data = bvh::BvhData::new(&aabb);
+
+        let scene::Scene::Active { prims, vertices, .. } = soup(&moved) else {
+            unreachable!();
+        };
+
+        data.refit(&prims, &vertices);
+
+        for uniform in data.uniforms.iter().filter(|uniform| !is_leaf(uniform)) {
+            for child in [uniform.fst, uniform.snd] {
+                prop_assert!(contains(&uniform.bounds, &data.uniforms[child as usize].bounds));
+            }
+        }
+
+        for (origin, dir) in rays {
+            let expected = nearest(moved.iter().copied(), origin, dir);
+
+            prop_assert_eq!(traverse(&data, &moved, origin, dir), expected);
+        }
+    }
+
+    #[test]
+    fn lbvh_leaves_partition_prims(tris in tris(), target in 1..8usize) {
+        let aabb = bvh::Aabb::from_scene_lbvh(&soup(&tris), target);
+        let data = bvh::BvhData::new(&aabb);
+
+        let mut seen = vec![0; tris.len()];
+        for uniform in data.uniforms.iter() {
+            let range = uniform.item_idx as usize..(uniform.item_idx + uniform.item_count) as usize;
+
+            if is_leaf(uniform) {
+                // Subtrees are collapsed as soon as they fit in a leaf
+                prop_assert!(uniform.item_count > 0 && uniform.item_count as usize <= target);
+
+                for &idx in &data.indices[range] {
+                    seen[idx as usize] += 1;
+
+                    for point in tris[idx as usize] {
+                        prop_assert!(contains_point(&uniform.bounds, point));
+                    }
+                }
+            } else {
+                prop_assert_eq!(uniform.item_count, 0);
+
+                for child in [uniform.fst, uniform.snd] {
+                    prop_assert!(contains(&uniform.bounds, &data.uniforms[child as usize].bounds));
+                }
+            }
+        }
+
+        prop_assert!(seen.iter().all(|&count| count == 1), "{:?}", seen);
+        prop_assert_eq!(data.indices.len(), tris.len());
+    }
+
+    #[test]
+    fn lbvh_traversal_matches_brute_force(
+        tris in tris(),
+        target in 1..8usize,
+        rays in prop::collection::vec(ray(), 1..16),
+    ) {
+        let aabb = bvh::Aabb::from_scene_lbvh(&soup(&tris), target);
+        let data = bvh::BvhData::new(&aabb);
+
+        for (origin, dir) in rays {
+            let expected = nearest(tris.iter().copied(), origin, dir);
+
+            prop_assert_eq!(traverse(&data, &tris, origin, dir), expected);
+        }
+    }
+
+    // Decoded boxes must contain the boxes they were quantised from
+    #[test]
+    fn quantised_bounds_are_conservative(
+        tris in tris(),
+        precision in prop::sample::select(vec![bvh::QuantPrecision::Bits8, bvh::QuantPrecision::Bits16]),
+    ) {
+        let aabb = bvh::Aabb::from_scene(EPS, &soup(&tris), 2);
+
+        let wide = bvh::WideBvhData::new(&aabb, 2);
+        let quant = bvh::QuantBvhData::new(&wide, precision);
+
+        prop_assert_eq!(quant.nodes(), wide.nodes());
+
+        for (idx, child) in wide.children.iter().enumerate() {
+            let (node, slot) = (idx / 2, idx % 2);
+
+            prop_assert_eq!(quant.child(node, slot), (child.idx, child.count));
+
+            if child.is_empty() { continue; }
+
+            let (min, max) = quant.bounds(node, slot);
+
+            for i in 0..3 {
+                prop_assert!(min[i] <= child.min[i], "{:?} > {:?}", min, child.min);
+                prop_assert!(max[i] >= child.max[i], "{:?} < {:?}", max, child.max);
+            }
+        }
+    }
+
+    // Rays aimed directly at a triangle, so hits are common
+    #[test]
+    fn traversal_finds_targeted_hits(
+        tris in tris(),
+        target in 1..8usize,
+        origin in point(),
+        pick in any::<prop::sample::Index>(),
+        weights in [0.05f32..1., 0.05f32..1., 0.05f32..1.],
+    ) {
+        let aabb = bvh::Aabb::from_scene(EPS, &soup(&tris), target);
+        let data = bvh::BvhData::new(&aabb);
+
+        let [a, b, c] = tris[pick.index(tris.len())];
+
+        let sum = weights.iter().sum::<f32>();
+        let at = [0, 1, 2].map(|i| (a[i] * weights[0] + b[i] * weights[1] + c[i] * weights[2]) / sum);
+
+        let dir = sub(at, origin);
+        prop_assume!(dot(dir, dir) > 0.001);
+
+        let expected = nearest(tris.iter().copied(), origin, dir);
+
+        prop_assert_eq!(traverse(&data, &tris, origin, dir), expected);
+    }
+}
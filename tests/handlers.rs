@@ -0,0 +1,466 @@
+// Renders the bundled scenes headless with every intersection handler.
+// Set RT_BLESS=1 to regenerate the golden images in tests/golden
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::{env, fs, io, path};
+
+use rt::{bvh, geom, handlers, scene, CaptureImage};
+use winit::dpi;
+
+const SCENES: [&str; 2] = ["default", "teatime"];
+
+const SIZE: dpi::PhysicalSize<u32> = dpi::PhysicalSize::new(64, 48);
+
+// Maximum per-channel difference before a pixel is counted as differing
+const TOLERANCE: u8 = 2;
+
+// Fraction of pixels that may differ from the golden image
+const GOLDEN_FRACTION: f64 = 0.005;
+
+fn load(name: &str) -> anyhow::Result<scene::Scene> {
+    let path = format!("{}/scenes/{name}.json", env!("CARGO_MANIFEST_DIR"));
+
+    let reader = io::BufReader::new(fs::File::open(path)?);
+
+    Ok(serde_json::from_reader(reader)?)
+}
+
+// Headless rendering requires an adapter, which dev machines may lack.
+// Tests are skipped without one, unless they're running on CI
+fn adapter(tests: &str) -> Option<wgpu::Adapter> {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+
+    let adapter = [true, false].into_iter().find_map(|force_fallback_adapter| {
+        pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::default(),
+            compatible_surface: None,
+            force_fallback_adapter,
+        }))
+    });
+
+    if adapter.is_none() {
+        assert!(env::var_os("CI").is_none(), "No adapter is available for the {tests} tests");
+
+        eprintln!("Skipping {tests} tests, no adapter is available");
+    }
+
+    adapter
+}
+
+fn config(debug: rt::DebugMode) -> rt::Config {
+    let config = rt::Config::default();
+
+    rt::Config {
+        compute: rt::ComputeConfig { debug, ..config.compute },
+        resolution: rt::Resolution::Sized(SIZE),
+        ..config
+    }
+}
+
+// Renders the Depth, Normals and Shaded images (in that order)
+fn render<H: handlers::IntrsHandler>(
+    name: &str,
+    config_handler: H::Config,
+) -> anyhow::Result<Vec<CaptureImage>> {
+    let configs = [
+        rt::DebugMode::Depth,
+        rt::DebugMode::Normals,
+        rt::DebugMode::Shaded,
+    ].map(config);
+
+    pollster::block_on(rt::run_headless::<H>(&configs, config_handler, load(name)?))
+}
+
+// Returns the number of differing pixels and an image highlighting them
+fn diff(a: &CaptureImage, b: &CaptureImage) -> (usize, CaptureImage) {
+    let mut count = 0;
+    let mut pixels = Vec::with_capacity(a.pixels.len());
+
+    for (pa, pb) in a.pixels.chunks_exact(4).zip(b.pixels.chunks_exact(4)) {
+        let delta = pa.iter().zip(pb)
+            .map(|(ca, cb)| ca.abs_diff(*cb))
+            .max()
+            .unwrap_or(0);
+
+        if delta > TOLERANCE {
+            count += 1;
+
+            pixels.extend([255, 0, 0, 255]);
+        } else {
+            // Matching pixels are dimmed so differences stand out
+            pixels.extend(pa[0..3].iter().map(|c| c / 4));
+            pixels.push(255);
+        }
+    }
+
+    (count, CaptureImage { size: a.size, pixels })
+}
+
+fn out_dir() -> path::PathBuf {
+    path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("handlers")
+}
+
+// Fails if more than the given fraction of pixels differ,
+// writing both images and their diff to the target directory
+fn compare(
+    label: &str,
+    expected: &CaptureImage,
+    actual: &CaptureImage,
+    fraction: f64,
+) -> anyhow::Result<()> {
+    if expected.size != actual.size {
+        anyhow::bail!("{label}: expected a {:?} image, found {:?}", expected.size, actual.size);
+    }
+
+    let (count, image) = diff(expected, actual);
+
+    let allowed = (fraction * (SIZE.width * SIZE.height) as f64) as usize;
+    if count <= allowed { return Ok(()); }
+
+    let out = out_dir();
+
+    fs::create_dir_all(&out)?;
+
+    expected.clone().save(&out.join(format!("{label}-expected.png")))?;
+    actual.clone().save(&out.join(format!("{label}-actual.png")))?;
+    image.save(&out.join(format!("{label}-diff.png")))?;
+
+    anyhow::bail!("{label}: {count} pixels differ (allowed {allowed}), see {}", out.display())
+}
+
+// A labelled comparison, see `compare`
+type Pair = (String, CaptureImage, CaptureImage, f64);
+
+// Runs every comparison before failing, so each mismatch is reported
+fn compare_all(pairs: Vec<Pair>) {
+    let failures = pairs
+        .into_iter()
+        .filter_map(|(label, expected, actual, fraction)| {
+            compare(&label, &expected, &actual, fraction).err()
+        })
+        .collect::<Vec<_>>();
+
+    for e in &failures {
+        eprintln!("{e}");
+    }
+
+    assert!(failures.is_empty(), "{} image comparisons failed", failures.len());
+}
+
+fn golden(name: &str) -> path::PathBuf {
+    path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{name}.png"))
+}
+
+fn read_golden(name: &str) -> anyhow::Result<CaptureImage> {
+    let pixmap = resvg::tiny_skia::Pixmap::load_png(golden(name))?;
+
+    Ok(CaptureImage {
+        size: dpi::PhysicalSize::new(pixmap.width(), pixmap.height()),
+        pixels: pixmap.take(),
+    })
+}
+
+#[test]
+fn handlers_match_naive() -> anyhow::Result<()> {
+    if adapter("handler regression").is_none() { return Ok(()); }
+
+    let bless = env::var_os("RT_BLESS").is_some();
+
+    let mut pairs = Vec::new();
+    for name in SCENES {
+        let naive = render::<handlers::BasicIntrs>(name, ())?;
+
+        if bless {
+            fs::create_dir_all(golden(name).parent().unwrap())?;
+
+            naive[2].clone().save(&golden(name))?;
+        }
+
+        let expected = read_golden(name)?;
+
+        let rendered = [
+            ("naive", naive.clone()),
+            ("bvh", render::<handlers::BvhIntrs>(name, Default::default())?),
+            // Precomputed trees may reference a prim from several leaves
+            ("sbvh", render::<handlers::BvhIntrs>(name, handlers::BvhConfig::Bytes({
+                let aabb = bvh::Aabb::from_scene_sbvh(0.02, &load(name)?, 2);
+
+                serde_json::to_vec(&bvh::BvhData::new(&aabb))?
+            }))?),
+            ("lbvh", render::<handlers::BvhIntrs>(name, handlers::BvhConfig::Builder {
+                eps: 0.02,
+                builder: bvh::BvhBuilder::Lbvh,
+            })?),
+            ("threaded", render::<handlers::ThreadedBvhIntrs>(name, Default::default())?),
+            ("rf", render::<handlers::RfBvhIntrs>(name, Default::default())?),
+            // Leaves this large require the 32-bit layout
+            ("rf-wide", render::<handlers::RfBvhIntrs>(name, handlers::RfBvhConfig::Runtime {
+                eps: 0.02,
+                leaf_size: 12,
+            })?),
+            ("wide4", render::<handlers::WideBvhIntrs>(name, Default::default())?),
+            ("wide8", render::<handlers::WideBvhIntrs>(name, handlers::WideBvhConfig::Runtime {
+                eps: 0.02,
+                width: 8,
+            })?),
+            ("quant8", render::<handlers::QuantBvhIntrs>(name, handlers::QuantBvhConfig::Runtime {
+                eps: 0.02,
+                precision: bvh::QuantPrecision::Bits8,
+            })?),
+            ("quant16", render::<handlers::QuantBvhIntrs>(name, Default::default())?),
+            // Boxed handlers selected at runtime
+            ("dyn-naive", render::<handlers::DynIntrs>(name, handlers::IntrsKind::Naive)?),
+            ("dyn-bvh", render::<handlers::DynIntrs>(name, handlers::IntrsKind::Bvh)?),
+            ("dyn-rf", render::<handlers::DynIntrs>(name, handlers::IntrsKind::BvhRf)?),
+        ];
+
+        for (handler, images) in rendered {
+            let label = format!("{name}-{handler}");
+
+            // Every handler has to find the same hits as the naive handler
+            for (mode, idx) in [("depth", 0), ("normals", 1)] {
+                pairs.push((format!("{label}-{mode}"), naive[idx].clone(), images[idx].clone(), 0.));
+            }
+
+            pairs.push((format!("{label}-shaded"), expected.clone(), images[2].clone(), GOLDEN_FRACTION));
+        }
+    }
+
+    compare_all(pairs);
+
+    Ok(())
+}
+
+// Bends the scene along its height, moving every prim out of its original bounds
+fn deform(scene: &scene::Scene) -> Vec<geom::PrimVertex> {
+    let scene::Scene::Active { vertices, .. } = scene else {
+        return Vec::new();
+    };
+
+    let (lo, hi) = vertices
+        .iter()
+        .fold((f32::MAX, f32::MIN), |(lo, hi), vertex| (lo.min(vertex.pos[1]), hi.max(vertex.pos[1])));
+
+    let height = (hi - lo).max(f32::MIN_POSITIVE);
+
+    vertices
+        .iter()
+        .map(|&geom::PrimVertex { pos: [x, y, z], normal, .. }| {
+            let t = (y - lo) / height;
+
+            geom::PrimVertex::new([x + 0.2 * height * (t * 6.).sin(), y, z], normal)
+        })
+        .collect()
+}
+
+#[test]
+fn refit_matches_naive() -> anyhow::Result<()> {
+    if adapter("refit regression").is_none() { return Ok(()); }
+
+    let configs = [rt::DebugMode::Depth, rt::DebugMode::Normals].map(config);
+
+    let mut pairs = Vec::new();
+    for name in SCENES {
+        let scene = load(name)?;
+
+        let moved = deform(&scene);
+
+        // The naive handler sees the deformed scene from the start
+        let mut expected = load(name)?;
+        if let scene::Scene::Active { vertices, .. } = &mut expected {
+            vertices.clone_from(&moved);
+        }
+
+        let naive = pollster::block_on(rt::run_headless::<handlers::BasicIntrs>(&configs, (), expected))?;
+
+        let frames = [moved];
+
+        let rendered = [
+            ("bvh", pollster::block_on({
+                rt::run_headless_animated::<handlers::BvhIntrs>(&configs, Default::default(), load(name)?, &frames)
+            })?),
+            ("threaded", pollster::block_on({
+                rt::run_headless_animated::<handlers::ThreadedBvhIntrs>(&configs, Default::default(), scene, &frames)
+            })?),
+        ];
+
+        for (handler, images) in rendered {
+            for (mode, idx) in [("depth", 0), ("normals", 1)] {
+                let label = format!("{name}-refit-{handler}-{mode}");
+
+                pairs.push((label, naive[idx].clone(), images[0][idx].clone(), 0.));
+            }
+        }
+    }
+
+    compare_all(pairs);
+
+    Ok(())
+}
+
+fn edits() -> anyhow::Result<Vec<scene::SceneEdit>> {
+    let path = format!("{}/scenes/teatime.path.json", env!("CARGO_MANIFEST_DIR"));
+
+    let path: scene::CameraPath = serde_json::from_reader(io::BufReader::new(fs::File::open(path)?))?;
+
+    // A quad between the camera and the scene
+    let normal = [0., 0., -1.];
+    let vertices = [[-1., -1.], [1., -1.], [1., 1.], [-1., 1.]]
+        .map(|[x, y]| geom::PrimVertex::new([x, y, -5.], normal))
+        .to_vec();
+
+    Ok(vec![
+        scene::SceneEdit::AddLight { 
+            light: geom::light::Light { pos: [0., 10., -10.], strength: 1., },
+        },
+        scene::SceneEdit::EditMaterial {
+            material: 1,
+            value: geom::PrimMat::new([0.1, 0.3, 0.1], [0.9, 0.1, 0.], 10.),
+        },
+        scene::SceneEdit::RemoveLight { light: 0, },
+        scene::SceneEdit::AppendMesh {
+            prims: vec![
+                geom::Prim { indices: [0, 1, 2], material: 2, },
+                geom::Prim { indices: [0, 2, 3], material: 2, },
+            ],
+            vertices,
+        },
+        scene::SceneEdit::CameraController { 
+            controller: scene::CameraController::path(path),
+        },
+    ])
+}
+
+#[test]
+fn edits_match_reload() -> anyhow::Result<()> {
+    if adapter("scene edit").is_none() { return Ok(()); }
+
+    let configs = [
+        rt::DebugMode::Depth,
+        rt::DebugMode::Normals,
+        rt::DebugMode::Shaded,
+    ].map(config);
+
+    let edited = pollster::block_on({
+        rt::run_headless_edited::<handlers::BvhIntrs>(&configs, Default::default(), load("default")?, edits()?)
+    })?;
+
+    let mut pairs = Vec::new();
+
+    // Each edit is compared against the edited scene loaded from scratch
+    let mut expected = load("default")?;
+    for (step, (edit, images)) in edits()?.into_iter().zip(edited).enumerate() {
+        expected.edit(edit)?;
+
+        let scene = serde_json::from_value(serde_json::to_value(&expected)?)?;
+
+        let naive = pollster::block_on(rt::run_headless::<handlers::BasicIntrs>(&configs, (), scene))?;
+
+        for (mode, idx) in [("depth", 0), ("normals", 1)] {
+            let label = format!("default-edit{step}-{mode}");
+
+            pairs.push((label, naive[idx].clone(), images[idx].clone(), 0.));
+        }
+
+        let label = format!("default-edit{step}-shaded");
+
+        pairs.push((label, naive[2].clone(), images[2].clone(), GOLDEN_FRACTION));
+    }
+
+    compare_all(pairs);
+
+    Ok(())
+}
+
+// Both halves of a comparison are checked against the naive handler.
+// The naive handler keeps the scene's prim order, so BVHs need their own prims
+#[test]
+fn compared_handlers_match_naive() -> anyhow::Result<()> {
+    if adapter("handler comparison").is_none() { return Ok(()); }
+
+    let configs = [
+        rt::DebugMode::Depth,
+        rt::DebugMode::Normals,
+    ].map(config);
+
+    let mut pairs = Vec::new();
+    for name in SCENES {
+        let naive = pollster::block_on(rt::run_headless::<handlers::BasicIntrs>(&configs, (), load(name)?))?;
+
+        let kinds = [
+            ("bvh", handlers::IntrsKind::Bvh),
+            ("rf", handlers::IntrsKind::BvhRf),
+            ("quant", handlers::IntrsKind::BvhQuant),
+        ];
+
+        for (handler, kind) in kinds {
+            let compared = pollster::block_on(rt::run_headless_compared::<handlers::BasicIntrs>(
+                &configs,
+                (),
+                rt::CompareConfig { handler: kind, mode: rt::CompareMode::Split },
+                load(name)?,
+            ))?;
+
+            for (mode, idx) in [("depth", 0), ("normals", 1)] {
+                let (primary, compared) = &compared[idx];
+
+                for (half, image) in [("primary", primary), (handler, compared)] {
+                    let label = format!("{name}-compare-{handler}-{half}-{mode}");
+
+                    pairs.push((label, naive[idx].clone(), image.clone(), 0.));
+                }
+            }
+        }
+    }
+
+    compare_all(pairs);
+
+    Ok(())
+}
+
+// Handlers order the prims themselves, the same way each time a scene is loaded.
+// Spatial splits reference some prims from several leaves
+#[test]
+fn reload_keeps_prims() -> anyhow::Result<()> {
+    let Some(adapter) = adapter("reload") else { return Ok(()); };
+
+    let (device, _) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None))?;
+
+    for name in SCENES {
+        let scene = load(name)?;
+
+        let scene::Scene::Active { prims, .. } = &scene else { unreachable!(); };
+
+        let count = prims.len();
+
+        let aabb = bvh::Aabb::from_scene_sbvh(0.02, &scene, 2);
+
+        let config = handlers::BvhConfig::Bytes(serde_json::to_vec(&bvh::BvhData::new(&aabb))?);
+
+        let handler = <handlers::BvhIntrs as handlers::IntrsHandler>::new(config)?;
+
+        let mut orders = Vec::new();
+        for _ in 0..2 {
+            let (pack, _) = handlers::IntrsHandler::vars(&handler, &scene, &device)?;
+
+            pack.destroy();
+
+            orders.push(pack.prims.unwrap_or_default());
+        }
+
+        let [fst, snd] = &orders[..] else { unreachable!(); };
+
+        assert!(fst.len() >= count, "{name}: {} ordered prims for {count} scene prims", fst.len());
+
+        assert_eq!(
+            bytemuck::cast_slice::<_, u8>(fst),
+            bytemuck::cast_slice::<_, u8>(snd),
+            "{name}: reloading changed the prim order",
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,167 @@
+// Mesh processing checks that don't require an adapter
+#![cfg(not(target_arch = "wasm32"))]
+
+use rt::geom::mesh;
+
+fn mesh(positions: Vec<[f32; 3]>, faces: Vec<[usize; 3]>) -> mesh::Mesh {
+    mesh::Mesh {
+        normals: vec![[None; 3]; faces.len()],
+        positions,
+        faces,
+    }
+}
+
+// A unit cube with outward-facing triangles
+fn cube() -> mesh::Mesh {
+    let positions = (0..8)
+        .map(|idx| [idx & 1, (idx >> 1) & 1, (idx >> 2) & 1].map(|c| c as f32))
+        .collect();
+
+    let quads = [
+        [0, 2, 3, 1], // -z
+        [4, 5, 7, 6], // +z
+        [0, 1, 5, 4], // -y
+        [2, 6, 7, 3], // +y
+        [0, 4, 6, 2], // -x
+        [1, 3, 7, 5], // +x
+    ];
+
+    let faces = quads
+        .iter()
+        .flat_map(|&[a, b, c, d]| [[a, b, c], [a, c, d]])
+        .collect();
+
+    mesh(positions, faces)
+}
+
+// A bumpy square grid of `n` by `n` quads
+fn grid(n: usize) -> mesh::Mesh {
+    let positions = (0..=n)
+        .flat_map(|y| (0..=n).map(move |x| {
+            let (u, v) = (x as f32 / n as f32, y as f32 / n as f32);
+
+            [u, v, 0.1 * (u * 7.).sin() * (v * 5.).cos()]
+        }))
+        .collect();
+
+    let idx = |x: usize, y: usize| y * (n + 1) + x;
+
+    let faces = (0..n)
+        .flat_map(|y| (0..n).flat_map(move |x| [
+            [idx(x, y), idx(x + 1, y), idx(x + 1, y + 1)],
+            [idx(x, y), idx(x + 1, y + 1), idx(x, y + 1)],
+        ]))
+        .collect();
+
+    mesh(positions, faces)
+}
+
+#[test]
+fn weld_merges_coincident_vertices() {
+    // Two triangles sharing an edge, but not its vertices
+    let mut mesh = mesh(
+        vec![
+            [0., 0., 0.], [1., 0., 0.], [1., 1., 0.],
+            [0., 0., 0.00001], [1., 1., 0.], [0., 1., 0.],
+        ],
+        vec![[0, 1, 2], [3, 4, 5]],
+    );
+
+    mesh.weld(0.001);
+
+    assert_eq!(mesh.positions.len(), 4);
+    assert_eq!(mesh.faces, vec![[0, 1, 2], [0, 2, 3]]);
+
+    // Vertices further apart than the tolerance are kept
+    let mut mesh = cube();
+
+    mesh.weld(0.5);
+
+    assert_eq!(mesh.positions.len(), 8);
+}
+
+#[test]
+fn cull_drops_degenerate_faces() {
+    let mut mesh = mesh(
+        vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [2., 0., 0.]],
+        vec![
+            [0, 1, 2],
+            // Repeated vertex
+            [0, 1, 1],
+            // Collinear vertices
+            [0, 1, 3],
+        ],
+    );
+
+    assert_eq!(mesh.cull(), 2);
+    assert_eq!(mesh.faces, vec![[0, 1, 2]]);
+    assert_eq!(mesh.normals.len(), 1);
+}
+
+#[test]
+fn decimate_reaches_target() {
+    let mut mesh = grid(10);
+
+    assert_eq!(mesh.faces.len(), 200);
+
+    mesh.decimate(50);
+
+    assert_eq!(mesh.faces.len(), 50);
+    assert_eq!(mesh.normals.len(), 50);
+
+    // Every remaining face references a valid position
+    assert!(mesh.faces.iter().flatten().all(|&idx| idx < mesh.positions.len()));
+
+    // Meshes already below the target are left alone
+    let mut mesh = cube();
+
+    mesh.decimate(20);
+
+    assert_eq!(mesh.faces.len(), 12);
+}
+
+#[test]
+fn cube_normals_follow_shading() {
+    let mesh = cube();
+
+    let config = mesh::MeshConfig::new();
+
+    // Each face of the cube gets its own 4 vertices
+    let (vertices, indices) = mesh.assemble(mesh::MeshConfig {
+        shading: mesh::MeshShading::Flat, ..config
+    });
+
+    assert_eq!(vertices.len(), 24);
+    assert_eq!(indices.len(), 12);
+
+    for vertex in vertices.iter() {
+        let axes = vertex.normal.iter().filter(|c| c.abs() > 0.999).count();
+
+        assert_eq!(axes, 1, "flat normal {:?} isn't axis-aligned", vertex.normal);
+    }
+
+    // Corners are shared, with normals pointing away from the center
+    let (vertices, indices) = mesh.assemble(mesh::MeshConfig {
+        shading: mesh::MeshShading::Smooth, ..config
+    });
+
+    assert_eq!(vertices.len(), 8);
+    assert_eq!(indices.len(), 12);
+
+    for vertex in vertices.iter() {
+        for (pos, normal) in vertex.pos.iter().zip(vertex.normal) {
+            let expected = (pos - 0.5).signum() / 3_f32.sqrt();
+
+            assert!((normal - expected).abs() < 0.0001, "smooth normal {:?} at {:?}", vertex.normal, vertex.pos);
+        }
+    }
+
+    // Faces meeting at right angles are split by the crease
+    let (vertices, _) = mesh.assemble(mesh::MeshConfig {
+        shading: mesh::MeshShading::Smooth,
+        crease: Some(45.),
+        ..config
+    });
+
+    assert_eq!(vertices.len(), 24);
+}
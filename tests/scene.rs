@@ -0,0 +1,158 @@
+// Scene format checks that don't require an adapter
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::{fs, io};
+
+use rt::scene;
+
+fn load(name: &str) -> anyhow::Result<scene::Scene> {
+    let path = format!("{}/scenes/{name}.json", env!("CARGO_MANIFEST_DIR"));
+
+    let reader = io::BufReader::new(fs::File::open(path)?);
+
+    Ok(serde_json::from_reader(reader)?)
+}
+
+#[test]
+fn animation_plays_and_loops() -> anyhow::Result<()> {
+    let scene::Scene::Active {
+        mut lights,
+        mut materials,
+        mut animation, ..
+    } = load("animated")? else { unreachable!(); };
+
+    // Four seconds of keyframes at 30 frames per second
+    assert_eq!(animation.frames(), 121);
+    assert!(animation.playing());
+
+    // The authored light starts where the first frame puts it
+    assert_eq!(lights[0].pos, [-20., 20., 20.]);
+
+    for _ in 0..30 {
+        animation.advance();
+    }
+
+    let changes = animation.apply(&mut lights, &mut materials);
+
+    assert!(changes.lights && changes.materials);
+    assert_eq!(lights[0].pos, [20., 20., 20.]);
+
+    // Halfway between the first two strength keyframes
+    assert!((lights[1].strength - 1.).abs() < 1e-5, "{}", lights[1].strength);
+
+    for _ in 30..121 {
+        animation.advance();
+    }
+
+    animation.apply(&mut lights, &mut materials);
+
+    assert_eq!(lights[0].pos, [-20., 20., 20.]);
+    assert!(animation.playing());
+
+    Ok(())
+}
+
+#[test]
+fn animate_applies_the_current_frame() -> anyhow::Result<()> {
+    let mut authored = serde_json::to_value(load("animated")?)?;
+
+    authored["lights"][0]["pos"] = serde_json::json!([0., 0., 0.]);
+
+    // Loading (and saving) keeps the scene as it was authored
+    let mut scene: scene::Scene = serde_json::from_value(authored.clone())?;
+
+    assert_eq!(serde_json::to_value(&scene)?, authored);
+
+    let changes = scene.animate();
+
+    let scene::Scene::Active { lights, .. } = &scene else { unreachable!(); };
+
+    assert!(changes.lights && changes.materials);
+    assert_eq!(lights[0].pos, [-20., 20., 20.]);
+
+    // Unloaded scenes have nothing to animate
+    let changes = scene::Scene::Unloaded.animate();
+
+    assert!(!changes.lights && !changes.materials);
+
+    Ok(())
+}
+
+#[test]
+fn static_scenes_have_no_animation() -> anyhow::Result<()> {
+    let scene = load("default")?;
+
+    let scene::Scene::Active { animation, .. } = &scene else { unreachable!(); };
+
+    assert_eq!(animation.frames(), 0);
+    assert!(!animation.playing());
+
+    // Written back out without an animation section
+    assert!(!serde_json::to_string(&scene)?.contains("animation"));
+
+    Ok(())
+}
+
+#[test]
+fn invalid_tracks_are_rejected() -> anyhow::Result<()> {
+    let scene = serde_json::to_value(load("default")?)?;
+
+    let tracks = [
+        // There are only three lights
+        serde_json::json!({
+            "target": "light_strength",
+            "light": 3,
+            "keyframes": [{ "time": 0.0, "value": 1.0 }],
+        }),
+        serde_json::json!({
+            "target": "material_color",
+            "material": 0,
+            "keyframes": [
+                { "time": 1.0, "value": [1.0, 1.0, 1.0] },
+                { "time": 0.0, "value": [0.0, 0.0, 0.0] },
+            ],
+        }),
+        serde_json::json!({
+            "target": "light_pos",
+            "light": 0,
+            "keyframes": [],
+        }),
+    ];
+
+    for track in tracks {
+        let mut scene = scene.clone();
+
+        scene["animation"] = serde_json::json!({ "tracks": [track] });
+
+        assert!(serde_json::from_value::<scene::Scene>(scene).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn removing_lights_updates_tracks() -> anyhow::Result<()> {
+    let mut scene = load("animated")?;
+
+    scene.edit(scene::SceneEdit::RemoveLight { light: 0 })?;
+
+    let scene::Scene::Active { lights, animation, .. } = &scene else { unreachable!(); };
+
+    assert_eq!(lights.len(), 2);
+
+    // The orbit is dropped, the pulse follows its light down an index
+    assert!(animation.tracks.iter().all(|track| match track {
+        scene::AnimationTrack::LightPos { .. } => false,
+        scene::AnimationTrack::LightStrength { light, .. } => *light == 0,
+        scene::AnimationTrack::MaterialColor { .. } => true,
+    }));
+
+    assert_eq!(animation.tracks.len(), 2);
+
+    // Removing the remaining lights would leave an empty buffer
+    scene.edit(scene::SceneEdit::RemoveLight { light: 1 })?;
+
+    assert!(scene.edit(scene::SceneEdit::RemoveLight { light: 0 }).is_err());
+
+    Ok(())
+}